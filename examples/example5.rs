@@ -1,3 +1,4 @@
+#![allow(clippy::field_reassign_with_default)]
 use std::path::Path;
 
 use sunhouse::canvas::Canvas;
@@ -10,7 +11,7 @@ use sunhouse::ray::Ray;
 use sunhouse::sphere::Sphere;
 
 /// Build a world with a big purple sphere.
-pub fn main() {
+pub fn render() -> Canvas {
   /*
   ray_origin ← point(0, 0, -5)
   wall_z ← 10
@@ -61,5 +62,10 @@ pub fn main() {
       }
     }
   }
-  canvas.to_png(Path::new("examples/example5.png"));
+  canvas
+}
+
+/// Render the scene to a PNG.
+pub fn main() -> sunhouse::Result<()> {
+  render().to_png(Path::new("examples/example5.png"))
 }
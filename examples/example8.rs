@@ -1,5 +1,8 @@
+#![allow(clippy::field_reassign_with_default)]
 use std::f64::consts::PI;
+use std::path::Path;
 use sunhouse::camera::Camera;
+use sunhouse::canvas::Canvas;
 use sunhouse::color::Color;
 use sunhouse::material::Material;
 use sunhouse::matrix::Matrix;
@@ -11,7 +14,7 @@ use sunhouse::vector::Vector;
 use sunhouse::world::World;
 
 /// Build a world with a floor, two walls, and three spheres.
-pub fn main() {
+pub fn render() -> Canvas {
   let mut world = World::empty();
 
   /*
@@ -128,5 +131,10 @@ pub fn main() {
   /*
     canvas ← render(camera, world)
   */
-  world.render_png(&camera, "examples/example8.png");
+  camera.render(&mut world)
+}
+
+/// Render the scene to a PNG.
+pub fn main() -> sunhouse::Result<()> {
+  render().to_png(Path::new("examples/example8.png"))
 }
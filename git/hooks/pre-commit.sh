@@ -4,4 +4,6 @@ set -e;
 
 cargo fmt --check;
 cargo clippy -- -D warnings;
+cargo clippy --all-targets --all-features -- -D warnings;
 cargo test;
+cargo test --all-features;
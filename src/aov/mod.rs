@@ -0,0 +1,188 @@
+use crate::canvas::exr::{ExrChannel, ExrOptions, ExrPixelType};
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::error::Result;
+use crate::point::Point;
+use crate::vector::Vector;
+use std::path::Path;
+
+/// An arbitrary output variable: something other than the final color that
+/// a renderer can record for each pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Aov {
+  /// The distance in front of the camera, along its view axis; infinite
+  /// where nothing was hit.
+  Depth,
+  /// The surface normal in world space, facing the eye.
+  Normal,
+  /// The surface color, before any lighting.
+  Albedo,
+  /// The position of the object in the world's list, starting at 1; 0 where
+  /// nothing was hit.
+  ObjectId,
+  /// The position of the first object in the world's list with the same
+  /// material, starting at 1; 0 where nothing was hit.
+  MaterialId,
+  /// 1 where the surface is in shadow, 0 elsewhere.
+  ShadowMask,
+  /// The hit point in world space.
+  Position,
+}
+
+impl Aov {
+  /// Every kind of output variable.
+  pub fn all() -> Vec<Aov> {
+    vec![
+      Aov::Depth,
+      Aov::Normal,
+      Aov::Albedo,
+      Aov::ObjectId,
+      Aov::MaterialId,
+      Aov::ShadowMask,
+      Aov::Position,
+    ]
+  }
+
+  /// A short name, suitable for a file name.
+  pub fn name(self) -> &'static str {
+    match self {
+      Aov::Depth => "depth",
+      Aov::Normal => "normal",
+      Aov::Albedo => "albedo",
+      Aov::ObjectId => "object_id",
+      Aov::MaterialId => "material_id",
+      Aov::ShadowMask => "shadow",
+      Aov::Position => "position",
+    }
+  }
+
+  /// The names of the EXR channels this variable is stored in; scalar
+  /// variables use a single channel.
+  pub fn channel_names(self) -> Vec<&'static str> {
+    match self {
+      Aov::Depth => vec!["Z"],
+      Aov::Normal => vec!["N.X", "N.Y", "N.Z"],
+      Aov::Albedo => vec!["albedo.R", "albedo.G", "albedo.B"],
+      Aov::ObjectId => vec!["objectId"],
+      Aov::MaterialId => vec!["materialId"],
+      Aov::ShadowMask => vec!["shadow"],
+      Aov::Position => vec!["P.X", "P.Y", "P.Z"],
+    }
+  }
+
+  /// The value recorded where a ray hits nothing.
+  pub fn background(self) -> Color {
+    match self {
+      Aov::Depth => Color(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+      _ => Color(0.0, 0.0, 0.0),
+    }
+  }
+}
+
+/// The per-pixel values of a surface seen through a pixel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AovSample {
+  pub depth: f64,
+  pub normal: Vector,
+  pub albedo: Color,
+  pub object_id: usize,
+  pub material_id: usize,
+  pub shadowed: bool,
+  pub position: Point,
+}
+
+impl AovSample {
+  /// The value of the given variable, with scalars in all three channels.
+  pub fn value(&self, aov: Aov) -> Color {
+    let scalar = |v: f64| Color(v, v, v);
+    match aov {
+      Aov::Depth => scalar(self.depth),
+      Aov::Normal => Color(self.normal.0, self.normal.1, self.normal.2),
+      Aov::Albedo => self.albedo,
+      Aov::ObjectId => scalar(self.object_id as f64),
+      Aov::MaterialId => scalar(self.material_id as f64),
+      Aov::ShadowMask => scalar(if self.shadowed { 1.0 } else { 0.0 }),
+      Aov::Position => Color(self.position.0, self.position.1, self.position.2),
+    }
+  }
+}
+
+/// A rendered color canvas along with a canvas for each requested variable.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AovBuffers {
+  pub color: Canvas,
+  pub buffers: Vec<(Aov, Canvas)>,
+}
+
+impl AovBuffers {
+  /// Create empty buffers for the given variables.
+  pub fn new(width: usize, height: usize, aovs: &[Aov]) -> Self {
+    let buffers = aovs
+      .iter()
+      .map(|aov| {
+        let mut canvas = Canvas::new(width, height);
+        canvas.pixels.fill(aov.background());
+        (*aov, canvas)
+      })
+      .collect();
+    AovBuffers {
+      color: Canvas::new(width, height),
+      buffers,
+    }
+  }
+
+  /// Retrieve the canvas for the given variable, if it was rendered.
+  pub fn get(&self, aov: Aov) -> Option<&Canvas> {
+    self.buffers.iter().find(|(a, _)| *a == aov).map(|(_, canvas)| canvas)
+  }
+
+  /// Record the variables seen through a pixel; `None` means a miss.
+  pub fn set_sample_at(&mut self, x: usize, y: usize, sample: Option<&AovSample>) {
+    for (aov, canvas) in self.buffers.iter_mut() {
+      let value = sample.map_or(aov.background(), |s| s.value(*aov));
+      canvas.set_color_at(x, y, value);
+    }
+  }
+
+  /// Convert every variable into named EXR channels.
+  ///
+  /// Depth and positions are stored at full precision; everything else fits
+  /// in half floats.
+  pub fn exr_channels(&self) -> Vec<ExrChannel> {
+    let mut channels = vec![];
+    for (aov, canvas) in &self.buffers {
+      let pixel_type = match aov {
+        Aov::Depth | Aov::Position | Aov::ObjectId | Aov::MaterialId => ExrPixelType::Float,
+        _ => ExrPixelType::Half,
+      };
+      for (i, name) in aov.channel_names().into_iter().enumerate() {
+        let values = canvas
+          .pixels
+          .iter()
+          .map(|c| match i {
+            0 => c.0,
+            1 => c.1,
+            _ => c.2,
+          })
+          .collect();
+        channels.push(ExrChannel::new(name, pixel_type, values));
+      }
+    }
+    channels
+  }
+
+  /// Convert the color and every variable into a single OpenEXR file.
+  pub fn to_exr(&self, options: &ExrOptions) -> Result<Vec<u8>> {
+    self.color.to_exr_with_channels(options, &self.exr_channels())
+  }
+
+  /// Write the color and each variable to its own OpenEXR file in the given
+  /// directory, named after the variable (`color.exr`, `depth.exr`, ...).
+  pub fn save_exr_files(&self, directory: &Path, options: &ExrOptions) -> Result<()> {
+    self.color.save_exr(&directory.join("color.exr"), options)?;
+    for (aov, canvas) in &self.buffers {
+      canvas.save_exr(&directory.join(format!("{}.exr", aov.name())), options)?;
+    }
+    Ok(())
+  }
+}
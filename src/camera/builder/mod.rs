@@ -0,0 +1,94 @@
+use super::Camera;
+use crate::matrix::Matrix;
+use crate::point::Point;
+use crate::vector::Vector;
+use std::f64::consts::PI;
+
+/// Describes a perspective camera by where it is and what it looks at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraBuilder {
+  pub hsize: usize,
+  pub vsize: usize,
+  pub field_of_view: f64,
+  pub position: Point,
+  pub target: Point,
+  pub up: Vector,
+}
+
+impl CameraBuilder {
+  /// Create a new builder for a camera at the origin looking down -z.
+  pub fn new() -> Self {
+    CameraBuilder {
+      hsize: 100,
+      vsize: 100,
+      field_of_view: PI / 2.0,
+      position: Point(0.0, 0.0, 0.0),
+      target: Point(0.0, 0.0, -1.0),
+      up: Vector(0.0, 1.0, 0.0),
+    }
+  }
+
+  /// Describe an existing perspective camera by where it is and what it looks
+  /// at, recovered from its view transform.
+  ///
+  /// The target is one unit in front of the eye.  The view transform's scale
+  /// depends on the angle between the up vector and the view direction, so
+  /// the up vector is rebuilt at that angle.  Settings the builder doesn't
+  /// know about, such as the lens, are left behind.
+  pub fn from_camera(camera: &Camera) -> Self {
+    let m = |row, col| camera.transform.get_value(row, col);
+    let left = Vector(m(0, 0), m(0, 1), m(0, 2));
+    let true_up = Vector(m(1, 0), m(1, 1), m(1, 2));
+    let forward = Vector(-m(2, 0), -m(2, 1), -m(2, 2));
+    let sine = left.magnitude();
+    let cosine = (1.0 - sine * sine).max(0.0).sqrt();
+    let position = camera.transform.inverse() * Point(0.0, 0.0, 0.0);
+    CameraBuilder {
+      hsize: camera.hsize,
+      vsize: camera.vsize,
+      field_of_view: camera.field_of_view,
+      position,
+      target: position + forward,
+      up: true_up.normalize() * sine + forward * cosine,
+    }
+  }
+
+  /// Set the size of the canvas in pixels.
+  pub fn resolution(self, hsize: usize, vsize: usize) -> Self {
+    CameraBuilder { hsize, vsize, ..self }
+  }
+
+  /// Set the field of view.
+  pub fn field_of_view(self, field_of_view: f64) -> Self {
+    CameraBuilder { field_of_view, ..self }
+  }
+
+  /// Set the position of the eye.
+  pub fn position(self, position: Point) -> Self {
+    CameraBuilder { position, ..self }
+  }
+
+  /// Set the point the camera looks at.
+  pub fn target(self, target: Point) -> Self {
+    CameraBuilder { target, ..self }
+  }
+
+  /// Set the direction that is up.
+  pub fn up(self, up: Vector) -> Self {
+    CameraBuilder { up, ..self }
+  }
+
+  /// Build the camera.
+  pub fn build(self) -> Camera {
+    let mut camera = Camera::new(self.hsize, self.vsize, self.field_of_view);
+    camera.transform = Matrix::view_transform(self.position, self.target, self.up);
+    camera
+  }
+}
+
+impl Default for CameraBuilder {
+  fn default() -> Self {
+    CameraBuilder::new()
+  }
+}
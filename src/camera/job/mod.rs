@@ -0,0 +1,217 @@
+use super::Camera;
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::world::World;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A rectangle of pixels rendered as one unit of work.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tile {
+  pub x: usize,
+  pub y: usize,
+  pub width: usize,
+  pub height: usize,
+}
+
+/// How far a render job has got; passed to the progress callback as each tile
+/// finishes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Progress {
+  /// The tile that just finished.
+  pub tile: Tile,
+  pub tiles_done: usize,
+  pub tiles_total: usize,
+  /// The time since the job started.
+  pub elapsed: Duration,
+}
+
+impl Progress {
+  /// The fraction of the tiles that are done, from 0 to 1.
+  pub fn fraction(&self) -> f64 {
+    if self.tiles_total == 0 {
+      return 1.0;
+    }
+    self.tiles_done as f64 / self.tiles_total as f64
+  }
+
+  /// Estimate the time left, assuming the remaining tiles take as long as the
+  /// ones done so far.
+  pub fn eta(&self) -> Option<Duration> {
+    if self.tiles_done == 0 {
+      return None;
+    }
+    let remaining = self.tiles_total.saturating_sub(self.tiles_done);
+    Some(self.elapsed.mul_f64(remaining as f64 / self.tiles_done as f64))
+  }
+}
+
+/// A flag shared between a render job and whoever may want to stop it.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+  /// Create a new token that hasn't been cancelled.
+  pub fn new() -> Self {
+    CancelToken::default()
+  }
+
+  /// Ask the job to stop; tiles already being rendered still finish.
+  pub fn cancel(&self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+
+  pub fn is_cancelled(&self) -> bool {
+    self.0.load(Ordering::SeqCst)
+  }
+}
+
+/// What a render job produced.
+#[derive(Clone, Debug, Default)]
+pub struct RenderOutcome {
+  /// The rendered image; pixels in tiles that weren't rendered are black and
+  /// uncovered.
+  pub canvas: Canvas,
+  pub tiles_done: usize,
+  /// Whether the job was cancelled before it rendered every tile.  A job
+  /// that is cancelled after its last tile finishes is not.
+  pub cancelled: bool,
+}
+
+/// A render that is split into tiles, reports progress as each one finishes,
+/// and can be cancelled part way through.
+///
+/// The job owns its camera and world, so it can be moved to a background
+/// thread.
+#[derive(Clone, Debug)]
+pub struct RenderJob {
+  pub camera: Camera,
+  pub world: World,
+  /// The width and height of each tile, in pixels.
+  pub tile_size: usize,
+  /// The number of threads to render on.
+  pub threads: usize,
+  pub cancel: CancelToken,
+}
+
+impl RenderJob {
+  /// Create a new job rendering 16-pixel tiles on one thread per CPU.
+  pub fn new(camera: Camera, world: World) -> Self {
+    RenderJob {
+      camera,
+      world,
+      tile_size: 16,
+      threads: thread::available_parallelism().map_or(1, |n| n.get()),
+      cancel: CancelToken::new(),
+    }
+  }
+
+  /// Return a copy of the job with the given tile size.
+  pub fn with_tile_size(&self, tile_size: usize) -> Self {
+    RenderJob {
+      tile_size: tile_size.max(1),
+      ..self.clone()
+    }
+  }
+
+  /// Return a copy of the job that renders on the given number of threads.
+  pub fn with_threads(&self, threads: usize) -> Self {
+    RenderJob {
+      threads: threads.max(1),
+      ..self.clone()
+    }
+  }
+
+  /// Return a copy of the job that stops when the given token is cancelled.
+  pub fn with_cancel_token(&self, cancel: CancelToken) -> Self {
+    RenderJob { cancel, ..self.clone() }
+  }
+
+  /// Get a token that cancels this job.
+  pub fn cancel_token(&self) -> CancelToken {
+    self.cancel.clone()
+  }
+
+  /// List the tiles covering the canvas, row by row from the top left.
+  pub fn tiles(&self) -> Vec<Tile> {
+    let size = self.tile_size;
+    let (hsize, vsize) = (self.camera.hsize, self.camera.vsize);
+    (0..vsize)
+      .step_by(size)
+      .flat_map(|y| {
+        (0..hsize).step_by(size).map(move |x| Tile {
+          x,
+          y,
+          width: size.min(hsize - x),
+          height: size.min(vsize - y),
+        })
+      })
+      .collect()
+  }
+
+  /// Render the tiles in order, calling `on_progress` from the rendering
+  /// thread as each one finishes.
+  ///
+  /// If the job is cancelled, the threads stop taking new tiles, and the
+  /// canvas is returned as far as it got.
+  pub fn run(&self, on_progress: impl Fn(Progress) + Sync) -> RenderOutcome {
+    let tiles = self.tiles();
+    let mut canvas = Canvas::new(self.camera.hsize, self.camera.vsize);
+    canvas.alpha.iter_mut().for_each(|alpha| *alpha = 0.0);
+    let canvas = Mutex::new(canvas);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let start = Instant::now();
+    let (tiles_ref, canvas_ref, next, done, on_progress) = (&tiles, &canvas, &next, &done, &on_progress);
+    thread::scope(|scope| {
+      for _ in 0..self.threads.max(1) {
+        let mut world = self.world.clone();
+        scope.spawn(move || {
+          while !self.cancel.is_cancelled() {
+            let Some(&tile) = tiles_ref.get(next.fetch_add(1, Ordering::SeqCst)) else {
+              break;
+            };
+            let pixels = self.render_tile(&mut world, tile);
+            {
+              let mut canvas = canvas_ref.lock().unwrap();
+              for (i, (color, alpha)) in pixels.into_iter().enumerate() {
+                let (x, y) = (tile.x + i % tile.width, tile.y + i / tile.width);
+                canvas.set_color_at(x, y, color);
+                canvas.set_alpha_at(x, y, alpha);
+              }
+            }
+            on_progress(Progress {
+              tile,
+              tiles_done: done.fetch_add(1, Ordering::SeqCst) + 1,
+              tiles_total: tiles_ref.len(),
+              elapsed: start.elapsed(),
+            });
+          }
+        });
+      }
+    });
+    let tiles_done = done.load(Ordering::SeqCst);
+    RenderOutcome {
+      canvas: canvas.into_inner().unwrap(),
+      tiles_done,
+      cancelled: self.cancel.is_cancelled() && tiles_done < tiles.len(),
+    }
+  }
+
+  /// Compute the color and coverage of every pixel in the tile, row by row.
+  fn render_tile(&self, world: &mut World, tile: Tile) -> Vec<(Color, f64)> {
+    let camera = &self.camera;
+    (tile.y..tile.y + tile.height)
+      .flat_map(|y| (tile.x..tile.x + tile.width).map(move |x| (x, y)))
+      .map(|(x, y)| {
+        if camera.is_in_view(x, y) {
+          camera.color_and_alpha_for_pixel(world, x, y)
+        } else {
+          (Color::default(), 0.0)
+        }
+      })
+      .collect()
+  }
+}
@@ -1,13 +1,51 @@
+use crate::aov::{Aov, AovBuffers, AovSample};
 use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::comps::Comps;
+use crate::error::Result;
 use crate::matrix::Matrix;
+use crate::output::OutputOptions;
 use crate::point::Point;
 use crate::ray::Ray;
-
-use crate::world::World;
+use crate::sampling::{concentric_disk, hammersley, polygon, radical_inverse};
+use crate::trace::PixelTrace;
+use crate::vector::Vector;
+use crate::world::{Background, World};
+use std::f64::consts::PI;
+use std::ops::Range;
 use std::path::Path;
+use std::thread;
+
+pub mod builder;
+pub use builder::CameraBuilder;
+pub mod job;
+pub use job::{CancelToken, Progress, RenderJob, RenderOutcome, Tile};
+pub mod progressive;
+pub use progressive::ProgressiveRender;
+pub mod track;
+pub use track::{CameraTrack, Interpolation, Keyframe};
+
+/// How the camera maps pixels on the canvas to rays in the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+  /// Rays fan out from the eye through a view plane one unit away.
+  #[default]
+  Perspective,
+  /// Rays are parallel, and the view extent is measured in world units.
+  Orthographic,
+  /// Longitude runs across the canvas and latitude down it, covering the full
+  /// sphere of directions around the eye.
+  Equirectangular,
+  /// Equidistant fisheye; the angle from the view axis grows linearly with the
+  /// distance from the center of the canvas, reaching half the field of view
+  /// at the edge of the circle inscribed in the canvas.
+  Fisheye,
+}
 
 /// A camera.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Camera {
   pub hsize: usize,
   pub vsize: usize,
@@ -17,13 +55,101 @@ pub struct Camera {
   pub half_width: f64,
   pub half_height: f64,
   pub half_view: f64,
+  pub projection: Projection,
+  /// The radius of the lens; zero gives a pinhole camera.  An open lens needs
+  /// several `samples` to blur anything: a single one only shifts the image.
+  pub aperture: f64,
+  /// The distance along the view axis at which objects are in perfect focus.
+  pub focal_distance: f64,
+  /// The number of blades forming a polygonal aperture; fewer than three
+  /// gives a circular aperture.
+  pub aperture_blades: usize,
+  /// The number of samples taken per pixel when the lens or shutter is open.
+  pub samples: usize,
+  /// The time at which the shutter opens.
+  pub shutter_open: f64,
+  /// The time at which the shutter closes.
+  pub shutter_close: f64,
 }
 
 impl Camera {
   /// Create a new camera.
   pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
-    let transform = Matrix::identity();
     let half_view = (field_of_view / 2.0).tan();
+    Camera::with_half_view(hsize, vsize, field_of_view, half_view, Projection::Perspective)
+  }
+
+  /// Start building a camera from a position and target.
+  pub fn builder() -> CameraBuilder {
+    CameraBuilder::new()
+  }
+
+  /// Create a new orthographic camera.
+  ///
+  /// The `view_size` is the extent, in world units, of the longer side of the
+  /// canvas.
+  pub fn orthographic(hsize: usize, vsize: usize, view_size: f64) -> Self {
+    Camera::with_half_view(hsize, vsize, 0.0, view_size / 2.0, Projection::Orthographic)
+  }
+
+  /// Create a new equirectangular (latitude-longitude) camera.
+  pub fn equirectangular(hsize: usize, vsize: usize) -> Self {
+    let mut camera = Camera::with_half_view(hsize, vsize, 2.0 * PI, PI, Projection::Equirectangular);
+    camera.half_width = PI;
+    camera.half_height = PI / 2.0;
+    camera.pixel_size = (2.0 * PI) / hsize as f64;
+    camera
+  }
+
+  /// Create a new fisheye camera.
+  ///
+  /// The `field_of_view` may be anything up to a full turn (2π).
+  pub fn fisheye(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+    let mut camera = Camera::with_half_view(hsize, vsize, field_of_view, 1.0, Projection::Fisheye);
+    camera.pixel_size = 2.0 / hsize.min(vsize) as f64;
+    camera.half_width = (hsize as f64 * camera.pixel_size) / 2.0;
+    camera.half_height = (vsize as f64 * camera.pixel_size) / 2.0;
+    camera
+  }
+
+  /// Return a copy of the camera with a canvas of a different size, keeping
+  /// its projection, view, lens and shutter.
+  pub fn with_resolution(&self, hsize: usize, vsize: usize) -> Self {
+    self.reshaped(hsize, vsize, self.field_of_view)
+  }
+
+  /// Return a copy of the camera with a different field of view.
+  ///
+  /// Orthographic and equirectangular cameras have no field of view to
+  /// change, and are returned as they are.
+  pub fn with_field_of_view(&self, field_of_view: f64) -> Self {
+    self.reshaped(self.hsize, self.vsize, field_of_view)
+  }
+
+  /// Rebuild the projection for a new canvas size and field of view, and copy
+  /// every other setting across.
+  fn reshaped(&self, hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+    let projected = match self.projection {
+      Projection::Perspective => Camera::new(hsize, vsize, field_of_view),
+      Projection::Orthographic => Camera::orthographic(hsize, vsize, self.half_view * 2.0),
+      Projection::Equirectangular => Camera::equirectangular(hsize, vsize),
+      Projection::Fisheye => Camera::fisheye(hsize, vsize, field_of_view),
+    };
+    Camera {
+      transform: self.transform,
+      aperture: self.aperture,
+      focal_distance: self.focal_distance,
+      aperture_blades: self.aperture_blades,
+      samples: self.samples,
+      shutter_open: self.shutter_open,
+      shutter_close: self.shutter_close,
+      ..projected
+    }
+  }
+
+  /// Create a camera whose longer side spans `half_view` in each direction.
+  fn with_half_view(hsize: usize, vsize: usize, field_of_view: f64, half_view: f64, projection: Projection) -> Self {
+    let transform = Matrix::identity();
     let aspect = hsize as f64 / vsize as f64;
     let (half_width, half_height) = if aspect >= 1.0 {
       (half_view, half_view / aspect)
@@ -40,6 +166,28 @@ impl Camera {
       half_width,
       half_height,
       half_view,
+      projection,
+      aperture: 0.0,
+      focal_distance: 1.0,
+      aperture_blades: 0,
+      samples: 1,
+      shutter_open: 0.0,
+      shutter_close: 0.0,
+    }
+  }
+
+  /// Determine whether the given pixel sees anything at all.
+  ///
+  /// Only the corners of a fisheye canvas, outside the image circle, are
+  /// out of view.
+  pub fn is_in_view(&self, px: usize, py: usize) -> bool {
+    match self.projection {
+      Projection::Fisheye => {
+        let x = self.half_width - (px as f64 + 0.5) * self.pixel_size;
+        let y = self.half_height - (py as f64 + 0.5) * self.pixel_size;
+        x.hypot(y) <= 1.0
+      },
+      _ => true,
     }
   }
 
@@ -49,28 +197,298 @@ impl Camera {
     let yoffset = (py as f64 + 0.5) * self.pixel_size;
     let world_x = self.half_width - xoffset;
     let world_y = self.half_height - yoffset;
-    let pixel = self.transform.inverse() * Point(world_x, world_y, -self.half_view);
-    let origin = self.transform.inverse() * Point::default();
-    let direction = (pixel - origin).normalize();
+    let inverse = self.transform.inverse();
+    match self.projection {
+      Projection::Perspective => {
+        let pixel = inverse * Point(world_x, world_y, -self.half_view);
+        let origin = inverse * Point::default();
+        let direction = (pixel - origin).normalize();
+        Ray::new(origin, direction)
+      },
+      Projection::Orthographic => {
+        let origin = inverse * Point(world_x, world_y, 0.0);
+        let direction = (inverse * Vector(0.0, 0.0, -1.0)).normalize();
+        Ray::new(origin, direction)
+      },
+      Projection::Equirectangular => {
+        let u = (px as f64 + 0.5) / self.hsize as f64;
+        let v = (py as f64 + 0.5) / self.vsize as f64;
+        let longitude = (u - 0.5) * 2.0 * PI;
+        let latitude = (0.5 - v) * PI;
+        let local = Vector(
+          -longitude.sin() * latitude.cos(),
+          latitude.sin(),
+          -longitude.cos() * latitude.cos(),
+        );
+        let origin = inverse * Point::default();
+        let direction = (inverse * local).normalize();
+        Ray::new(origin, direction)
+      },
+      Projection::Fisheye => {
+        let radius = world_x.hypot(world_y);
+        let theta = radius * self.field_of_view / 2.0;
+        let local = if radius == 0.0 {
+          Vector(0.0, 0.0, -1.0)
+        } else {
+          Vector(
+            theta.sin() * world_x / radius,
+            theta.sin() * world_y / radius,
+            -theta.cos(),
+          )
+        };
+        let origin = inverse * Point::default();
+        let direction = (inverse * local).normalize();
+        Ray::new(origin, direction)
+      },
+    }
+  }
+
+  /// Map a sample in the unit square to a point on the lens, in camera space.
+  pub fn lens_point(&self, u: f64, v: f64) -> (f64, f64) {
+    let (x, y) = if self.aperture_blades >= 3 {
+      polygon(u, v, self.aperture_blades)
+    } else {
+      concentric_disk(u, v)
+    };
+    (x * self.aperture, y * self.aperture)
+  }
+
+  /// Compute the ray for the given pixel passing through the lens at the
+  /// sample `(u, v)` in the unit square.
+  ///
+  /// The ray starts on the lens and passes through the point where the pinhole
+  /// ray meets the focal plane.  With a zero aperture, or with a panoramic
+  /// projection, this is the same ray as `ray_for_pixel`.
+  pub fn ray_for_lens_sample(&self, px: usize, py: usize, u: f64, v: f64) -> Ray {
+    if self.aperture == 0.0 {
+      return self.ray_for_pixel(px, py);
+    }
+    let world_x = self.half_width - (px as f64 + 0.5) * self.pixel_size;
+    let world_y = self.half_height - (py as f64 + 0.5) * self.pixel_size;
+    let (origin, direction) = match self.projection {
+      Projection::Perspective => (Point::default(), Vector(world_x, world_y, -self.half_view)),
+      Projection::Orthographic => (Point(world_x, world_y, 0.0), Vector(0.0, 0.0, -1.0)),
+      Projection::Equirectangular | Projection::Fisheye => return self.ray_for_pixel(px, py),
+    };
+    let focus = origin + direction * (self.focal_distance / -direction.2);
+    let (lens_x, lens_y) = self.lens_point(u, v);
+    let lens = Point(origin.0 + lens_x, origin.1 + lens_y, origin.2);
+    let inverse = self.transform.inverse();
+    let origin = inverse * lens;
+    let direction = (inverse * focus - origin).normalize();
     Ray::new(origin, direction)
   }
 
+  /// Compute the time of the given sample while the shutter is open.
+  ///
+  /// This is the time rays carry; moving shapes work out where they are at
+  /// it from their own motion's start and end times.
+  pub fn time_for_sample(&self, i: usize) -> f64 {
+    if self.shutter_close > self.shutter_open {
+      self.shutter_open + (self.shutter_close - self.shutter_open) * radical_inverse(i, 3)
+    } else {
+      self.shutter_open
+    }
+  }
+
+  /// Compute the color seen through the given pixel, averaging the samples
+  /// taken across the lens and while the shutter is open.
+  pub fn color_for_pixel(&self, world: &mut World, px: usize, py: usize) -> Color {
+    self.color_and_alpha_for_pixel(world, px, py).0
+  }
+
+  /// Compute the color and coverage of the given pixel; the color is
+  /// premultiplied by the coverage.
+  pub fn color_and_alpha_for_pixel(&self, world: &mut World, px: usize, py: usize) -> (Color, f64) {
+    let samples = self.samples_per_pixel();
+    let (color, alpha) = (0..samples).fold((Color::default(), 0.0), |(color, alpha), i| {
+      let (sample_color, sample_alpha) = self.color_and_alpha_for_sample(world, px, py, i);
+      (color + sample_color, alpha + sample_alpha)
+    });
+    (color / samples as f64, alpha / samples as f64)
+  }
+
+  /// The number of samples taken per pixel: one for a pinhole camera with a
+  /// closed shutter, and `samples` otherwise.
+  pub fn samples_per_pixel(&self) -> usize {
+    if self.aperture == 0.0 && self.shutter_close <= self.shutter_open {
+      1
+    } else {
+      self.samples.max(1)
+    }
+  }
+
+  /// Compute the color and coverage of the `i`-th sample of the given pixel.
+  pub fn color_and_alpha_for_sample(&self, world: &mut World, px: usize, py: usize, i: usize) -> (Color, f64) {
+    world.color_and_alpha_at(self.ray_for_sample(px, py, i))
+  }
+
+  /// Compute the ray for the `i`-th sample of the given pixel.
+  ///
+  /// A single sample looks through the center of the lens, so it sees what a
+  /// pinhole camera would.
+  pub fn ray_for_sample(&self, px: usize, py: usize, i: usize) -> Ray {
+    if self.samples_per_pixel() == 1 {
+      return self.ray_for_pixel(px, py).with_time(self.time_for_sample(i));
+    }
+    let (u, v) = hammersley(i, self.samples_per_pixel());
+    self
+      .ray_for_lens_sample(px, py, u, v)
+      .with_time(self.time_for_sample(i))
+  }
+
   /// Render the world to a canvas.
+  ///
+  /// Pixels outside the field of view are left uncovered.
   pub fn render(&self, world: &mut World) -> Canvas {
+    self.render_region(world, 0, 0, self.hsize, self.vsize)
+  }
+
+  /// Render a rectangle of the image, with its top left corner at `(x, y)`,
+  /// to a canvas of its own size.
+  ///
+  /// The projection is still that of the full frame, so the pixels match the
+  /// same pixels of `render`.  The rectangle is clipped to the frame.
+  pub fn render_region(&self, world: &mut World, x: usize, y: usize, width: usize, height: usize) -> Canvas {
+    let (x, y) = (x.min(self.hsize), y.min(self.vsize));
+    let (width, height) = (width.min(self.hsize - x), height.min(self.vsize - y));
+    let mut canvas = Canvas::new(width, height);
+    for row in 0..height {
+      for (column, (color, alpha)) in self.render_row(world, y + row, x..x + width).into_iter().enumerate() {
+        canvas.set_color_at(column, row, color);
+        canvas.set_alpha_at(column, row, alpha);
+      }
+    }
+    canvas
+  }
+
+  /// Render the world to a canvas on the given number of threads.
+  ///
+  /// Each thread renders every `threads`-th row with its own copy of the
+  /// world, so the result is the same as `render`.
+  pub fn render_parallel(&self, world: &World, threads: usize) -> Canvas {
+    let threads = threads.max(1);
+    let rows: Vec<(usize, Vec<(Color, f64)>)> = thread::scope(|scope| {
+      let handles: Vec<_> = (0..threads)
+        .map(|first| {
+          let mut world = world.clone();
+          scope.spawn(move || {
+            (first..self.vsize)
+              .step_by(threads)
+              .map(|y| (y, self.render_row(&mut world, y, 0..self.hsize)))
+              .collect::<Vec<_>>()
+          })
+        })
+        .collect();
+      handles
+        .into_iter()
+        .flat_map(|handle| handle.join().expect("render thread panicked"))
+        .collect()
+    });
     let mut canvas = Canvas::new(self.hsize, self.vsize);
-    for y in 0..self.vsize {
-      for x in 0..self.hsize {
-        let ray = self.ray_for_pixel(x, y);
-        let color = world.color_at(ray);
+    for (y, row) in rows {
+      for (x, (color, alpha)) in row.into_iter().enumerate() {
         canvas.set_color_at(x, y, color);
+        canvas.set_alpha_at(x, y, alpha);
       }
     }
     canvas
   }
 
-  /// Render a PNG of the world.
-  pub fn render_png(&self, world: &mut World, filename: &str) {
+  /// Compute the color and coverage of the given pixels in a row.
+  fn render_row(&self, world: &mut World, y: usize, xs: Range<usize>) -> Vec<(Color, f64)> {
+    xs.map(|x| {
+      if self.is_in_view(x, y) {
+        self.color_and_alpha_for_pixel(world, x, y)
+      } else {
+        (Color::default(), 0.0)
+      }
+    })
+    .collect()
+  }
+
+  /// Trace the given pixel for debugging, recording every ray, intersection,
+  /// `Comps` and light contribution that goes into its color.
+  pub fn trace_pixel(&self, world: &mut World, px: usize, py: usize) -> PixelTrace {
+    if !self.is_in_view(px, py) {
+      return PixelTrace {
+        x: px,
+        y: py,
+        ..PixelTrace::default()
+      };
+    }
+    let samples: Vec<_> = (0..self.samples_per_pixel())
+      .map(|i| world.trace_ray(self.ray_for_sample(px, py, i)))
+      .collect();
+    let (color, alpha) = samples.iter().fold((Color::default(), 0.0), |(color, alpha), sample| {
+      (color + sample.color, alpha + sample.alpha)
+    });
+    let count = samples.len() as f64;
+    PixelTrace {
+      x: px,
+      y: py,
+      in_view: true,
+      color: color / count,
+      alpha: alpha / count,
+      samples,
+    }
+  }
+
+  /// Compute the output variables of the surface seen through the center of
+  /// the given pixel as the shutter opens, or `None` if the ray hits nothing.
+  pub fn aov_sample_for_pixel(&self, world: &mut World, px: usize, py: usize) -> Option<AovSample> {
+    let ray = self.ray_for_pixel(px, py).with_time(self.shutter_open);
+    let (index, hit) = world.hit_with_index(ray)?;
+    let comps = Comps::prepare(&hit, ray);
+    let material = comps.object.material();
+    let camera_point = self.transform * comps.point;
+    let depth = match self.projection {
+      Projection::Perspective | Projection::Orthographic => -camera_point.2,
+      // Panoramic views look in every direction, so use the distance instead.
+      Projection::Equirectangular | Projection::Fisheye => (camera_point - Point::default()).magnitude(),
+    };
+    let material_index = world
+      .objects
+      .iter()
+      .position(|o| o.material() == material)
+      .unwrap_or(index);
+    let shadowed = !world.lights.is_empty() && world.is_shadowed_at(comps.over_point, comps.time);
+    Some(AovSample {
+      depth,
+      normal: comps.normalv,
+      albedo: material.color,
+      object_id: index + 1,
+      material_id: material_index + 1,
+      shadowed,
+      position: comps.point,
+    })
+  }
+
+  /// Render the world to a color canvas along with a canvas for each of the
+  /// given output variables.
+  pub fn render_aovs(&self, world: &mut World, aovs: &[Aov]) -> AovBuffers {
+    let mut buffers = AovBuffers::new(self.hsize, self.vsize, aovs);
+    for y in 0..self.vsize {
+      for x in 0..self.hsize {
+        if !self.is_in_view(x, y) {
+          buffers.color.set_alpha_at(x, y, 0.0);
+          continue;
+        }
+        let (color, alpha) = self.color_and_alpha_for_pixel(world, x, y);
+        buffers.color.set_color_at(x, y, color);
+        buffers.color.set_alpha_at(x, y, alpha);
+        let sample = self.aov_sample_for_pixel(world, x, y);
+        buffers.set_sample_at(x, y, sample.as_ref());
+      }
+    }
+    buffers
+  }
+
+  /// Render a PNG of the world, with an alpha channel if the background is
+  /// transparent.
+  pub fn render_png(&self, world: &mut World, filename: &str) -> Result<()> {
     let canvas = self.render(world);
-    canvas.to_png(Path::new(filename));
+    let options = OutputOptions::default().with_alpha(world.background == Background::Transparent);
+    canvas.to_png_with(Path::new(filename), &options)
   }
 }
@@ -0,0 +1,142 @@
+use super::Camera;
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::world::World;
+
+/// A render that refines in passes, for interactive previews.
+///
+/// The first passes trace one pixel per block, halving the block size each
+/// time, and show each block in that pixel's color.  Once every pixel has its
+/// first sample, each pass adds one more sample per pixel, until there are as
+/// many as the camera takes.  The final image is the same as `render` gives.
+#[derive(Clone, Debug)]
+pub struct ProgressiveRender {
+  pub camera: Camera,
+  pub world: World,
+  /// The sum of the samples taken so far at each pixel, row by row.
+  sums: Vec<(Color, f64)>,
+  /// Whether each pixel has its first sample.
+  traced: Vec<bool>,
+  /// The block size of the next coarse pass, or 0 once every pixel has
+  /// been traced.
+  block_size: usize,
+  /// The number of samples every pixel has.
+  samples_taken: usize,
+  canvas: Canvas,
+}
+
+impl ProgressiveRender {
+  /// Create a new render starting with 8-pixel blocks.
+  pub fn new(camera: Camera, world: World) -> Self {
+    let pixels = camera.hsize * camera.vsize;
+    let mut canvas = Canvas::new(camera.hsize, camera.vsize);
+    canvas.alpha.iter_mut().for_each(|alpha| *alpha = 0.0);
+    ProgressiveRender {
+      sums: vec![(Color::default(), 0.0); pixels],
+      traced: vec![false; pixels],
+      block_size: 8,
+      samples_taken: 0,
+      canvas,
+      camera,
+      world,
+    }
+  }
+
+  /// Return a copy of the render whose first pass shows blocks of the given
+  /// size; call it before the first pass.
+  pub fn with_block_size(&self, block_size: usize) -> Self {
+    ProgressiveRender {
+      block_size: block_size.max(1),
+      ..self.clone()
+    }
+  }
+
+  /// The image as refined so far.
+  pub fn canvas(&self) -> &Canvas {
+    &self.canvas
+  }
+
+  /// The number of samples every pixel has so far.
+  pub fn samples_taken(&self) -> usize {
+    self.samples_taken
+  }
+
+  /// Whether the render has taken every sample, and so matches `render`.
+  pub fn is_done(&self) -> bool {
+    self.samples_taken >= self.camera.samples_per_pixel()
+  }
+
+  /// Run the next pass, returning `false` if there was nothing left to do.
+  pub fn refine(&mut self) -> bool {
+    if self.is_done() {
+      return false;
+    }
+    if self.block_size > 0 {
+      let size = self.block_size;
+      self.trace_blocks(size);
+      self.update_canvas(size);
+      self.block_size /= 2;
+    } else {
+      self.add_samples();
+      self.update_canvas(1);
+    }
+    true
+  }
+
+  /// Refine until every sample has been taken, and return the final image.
+  pub fn finish(mut self) -> Canvas {
+    while self.refine() {}
+    self.canvas
+  }
+
+  /// Take the first sample at the corner of every block not yet traced.
+  fn trace_blocks(&mut self, size: usize) {
+    for y in (0..self.camera.vsize).step_by(size) {
+      for x in (0..self.camera.hsize).step_by(size) {
+        let index = y * self.camera.hsize + x;
+        if !self.traced[index] {
+          self.sums[index] = self.sample(x, y, 0);
+          self.traced[index] = true;
+        }
+      }
+    }
+    if size == 1 {
+      self.samples_taken = 1;
+    }
+  }
+
+  /// Add the next sample to every pixel.
+  fn add_samples(&mut self) {
+    let i = self.samples_taken;
+    for y in 0..self.camera.vsize {
+      for x in 0..self.camera.hsize {
+        let (color, alpha) = self.sample(x, y, i);
+        let sum = &mut self.sums[y * self.camera.hsize + x];
+        *sum = (sum.0 + color, sum.1 + alpha);
+      }
+    }
+    self.samples_taken += 1;
+  }
+
+  /// Compute a sample of the given pixel; pixels out of view are uncovered.
+  fn sample(&mut self, x: usize, y: usize, i: usize) -> (Color, f64) {
+    if self.camera.is_in_view(x, y) {
+      self.camera.color_and_alpha_for_sample(&mut self.world, x, y, i)
+    } else {
+      (Color::default(), 0.0)
+    }
+  }
+
+  /// Show each pixel as the average of its samples, or while blocks are still
+  /// coarse, as the first sample at the corner of its block.
+  fn update_canvas(&mut self, size: usize) {
+    let samples = self.samples_taken.max(1) as f64;
+    for y in 0..self.camera.vsize {
+      for x in 0..self.camera.hsize {
+        let (color, alpha) = self.sums[(y - y % size) * self.camera.hsize + (x - x % size)];
+        self.canvas.set_color_at(x, y, color / samples);
+        self.canvas.set_alpha_at(x, y, alpha / samples);
+      }
+    }
+  }
+}
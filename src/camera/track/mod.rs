@@ -0,0 +1,191 @@
+use super::Camera;
+use super::CameraBuilder;
+use crate::point::Point;
+
+/// How a track moves between its keyframes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Interpolation {
+  /// Straight lines between keyframes, changing direction abruptly at each.
+  #[default]
+  Linear,
+  /// A Catmull-Rom spline, passing smoothly through every keyframe.
+  CatmullRom,
+}
+
+/// Where the camera is, what it looks at, and how wide it sees at a moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Keyframe {
+  pub time: f64,
+  pub position: Point,
+  pub target: Point,
+  pub field_of_view: f64,
+}
+
+impl Keyframe {
+  /// Create a new keyframe.
+  pub fn new(time: f64, position: Point, target: Point, field_of_view: f64) -> Self {
+    Keyframe {
+      time,
+      position,
+      target,
+      field_of_view,
+    }
+  }
+}
+
+/// A path for the camera to follow through a sequence of keyframes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CameraTrack {
+  /// Settings shared by every frame, such as the resolution and up vector.
+  pub builder: CameraBuilder,
+  /// The keyframes, in order of time.
+  pub keyframes: Vec<Keyframe>,
+  pub interpolation: Interpolation,
+}
+
+impl CameraTrack {
+  /// Create a new track with no keyframes.
+  pub fn new(builder: CameraBuilder, interpolation: Interpolation) -> Self {
+    CameraTrack {
+      builder,
+      keyframes: vec![],
+      interpolation,
+    }
+  }
+
+  /// Return a clone of the track with another keyframe, keeping the keyframes
+  /// in order of time.
+  pub fn with_keyframe(&self, keyframe: Keyframe) -> Self {
+    let mut keyframes = self.keyframes.clone();
+    let index = keyframes.partition_point(|k| k.time <= keyframe.time);
+    keyframes.insert(index, keyframe);
+    CameraTrack {
+      keyframes,
+      ..self.clone()
+    }
+  }
+
+  /// The time of the first keyframe.
+  pub fn start(&self) -> f64 {
+    self.keyframes.first().map_or(0.0, |k| k.time)
+  }
+
+  /// The time of the last keyframe.
+  pub fn end(&self) -> f64 {
+    self.keyframes.last().map_or(0.0, |k| k.time)
+  }
+
+  /// Compute the keyframe at the given time, holding the first and last
+  /// keyframes before and after the track, or `None` if it has no keyframes.
+  pub fn keyframe_at(&self, time: f64) -> Option<Keyframe> {
+    let keyframes = &self.keyframes;
+    let (first, last) = (keyframes.first()?, keyframes.last()?);
+    if time <= first.time {
+      return Some(Keyframe { time, ..*first });
+    }
+    if time >= last.time {
+      return Some(Keyframe { time, ..*last });
+    }
+    let i = keyframes.partition_point(|k| k.time <= time) - 1;
+    let (k1, k2) = (keyframes[i], keyframes[i + 1]);
+    Some(match self.interpolation {
+      Interpolation::Linear => {
+        let s = (time - k1.time) / (k2.time - k1.time);
+        Keyframe {
+          time,
+          position: lerp_point(k1.position, k2.position, s),
+          target: lerp_point(k1.target, k2.target, s),
+          field_of_view: lerp(k1.field_of_view, k2.field_of_view, s),
+        }
+      },
+      Interpolation::CatmullRom => {
+        // Past the ends, repeat the end keyframes one interval further out so
+        // that the spline reaches them.
+        let k0 = match i {
+          0 => Keyframe {
+            time: 2.0 * k1.time - k2.time,
+            ..k1
+          },
+          _ => keyframes[i - 1],
+        };
+        let k3 = match keyframes.get(i + 2) {
+          Some(k3) => *k3,
+          None => Keyframe {
+            time: 2.0 * k2.time - k1.time,
+            ..k2
+          },
+        };
+        let times = [k0.time, k1.time, k2.time, k3.time];
+        Keyframe {
+          time,
+          position: catmull_rom_point([k0.position, k1.position, k2.position, k3.position], times, time),
+          target: catmull_rom_point([k0.target, k1.target, k2.target, k3.target], times, time),
+          field_of_view: catmull_rom(
+            [k0.field_of_view, k1.field_of_view, k2.field_of_view, k3.field_of_view],
+            times,
+            time,
+          ),
+        }
+      },
+    })
+  }
+
+  /// Compute the camera at the given time, or `None` if the track has no
+  /// keyframes.
+  pub fn camera_at(&self, time: f64) -> Option<Camera> {
+    let keyframe = self.keyframe_at(time)?;
+    Some(
+      self
+        .builder
+        .position(keyframe.position)
+        .target(keyframe.target)
+        .field_of_view(keyframe.field_of_view)
+        .build(),
+    )
+  }
+
+  /// Compute a camera for each of `frames` evenly spaced moments from the
+  /// first keyframe to the last, inclusive; a track with no keyframes gives
+  /// none.
+  pub fn cameras(&self, frames: usize) -> Vec<Camera> {
+    let (start, end) = (self.start(), self.end());
+    (0..frames)
+      .filter_map(|frame| {
+        let s = if frames > 1 {
+          frame as f64 / (frames - 1) as f64
+        } else {
+          0.0
+        };
+        self.camera_at(lerp(start, end, s))
+      })
+      .collect()
+  }
+}
+
+fn lerp(a: f64, b: f64, s: f64) -> f64 {
+  a + (b - a) * s
+}
+
+fn lerp_point(a: Point, b: Point, s: f64) -> Point {
+  Point(lerp(a.0, b.0, s), lerp(a.1, b.1, s), lerp(a.2, b.2, s))
+}
+
+/// Interpolate between the middle two of four values, at the given times,
+/// with a Catmull-Rom spline.  The tangents are scaled by the time between
+/// keyframes, so the speed doesn't jump at unevenly spaced keyframes.
+fn catmull_rom(p: [f64; 4], t: [f64; 4], time: f64) -> f64 {
+  let h = t[2] - t[1];
+  let s = (time - t[1]) / h;
+  let m1 = (p[2] - p[0]) / (t[2] - t[0]) * h;
+  let m2 = (p[3] - p[1]) / (t[3] - t[1]) * h;
+  let (s2, s3) = (s * s, s * s * s);
+  (2.0 * s3 - 3.0 * s2 + 1.0) * p[1] + (s3 - 2.0 * s2 + s) * m1 + (3.0 * s2 - 2.0 * s3) * p[2] + (s3 - s2) * m2
+}
+
+fn catmull_rom_point(p: [Point; 4], t: [f64; 4], time: f64) -> Point {
+  Point(
+    catmull_rom(p.map(|p| p.0), t, time),
+    catmull_rom(p.map(|p| p.1), t, time),
+    catmull_rom(p.map(|p| p.2), t, time),
+  )
+}
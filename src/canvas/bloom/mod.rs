@@ -0,0 +1,94 @@
+use super::ops::ResizeFilter;
+use super::Canvas;
+use crate::color::Color;
+
+/// Settings for the glow `Canvas::bloom` adds around bright highlights.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bloom {
+  /// The luminance above which pixels glow.
+  pub threshold: f64,
+  /// How much of the light above the threshold is spread into the glow.
+  pub intensity: f64,
+  /// The standard deviation of the blur at full size, in pixels.
+  pub sigma: f64,
+  /// The number of scales blurred; each is half the size of the one before,
+  /// so its blur reaches twice as far.
+  pub levels: usize,
+}
+
+impl Default for Bloom {
+  fn default() -> Self {
+    Bloom {
+      threshold: 1.0,
+      intensity: 0.5,
+      sigma: 2.0,
+      levels: 4,
+    }
+  }
+}
+
+impl Bloom {
+  /// Return a copy of the settings with the given threshold.
+  pub fn with_threshold(&self, threshold: f64) -> Self {
+    Bloom { threshold, ..*self }
+  }
+
+  /// Return a copy of the settings with the given intensity.
+  pub fn with_intensity(&self, intensity: f64) -> Self {
+    Bloom { intensity, ..*self }
+  }
+
+  /// Return a copy of the settings with the given blur at full size.
+  pub fn with_sigma(&self, sigma: f64) -> Self {
+    Bloom { sigma, ..*self }
+  }
+
+  /// Return a copy of the settings that blurs at the given number of scales.
+  pub fn with_levels(&self, levels: usize) -> Self {
+    Bloom { levels, ..*self }
+  }
+}
+
+impl Canvas {
+  /// Keep only the light above the given luminance, scaling each bright
+  /// pixel down so that it keeps its hue; the rest go black.
+  pub fn bright_pass(&self, threshold: f64) -> Canvas {
+    let mut canvas = self.clone();
+    for color in canvas.pixels.iter_mut() {
+      let luminance = color.luminance();
+      *color = if luminance > threshold {
+        *color * ((luminance - threshold) / luminance)
+      } else {
+        Color::default()
+      };
+    }
+    canvas
+  }
+
+  /// Make bright highlights glow, as light scattering in a lens would.
+  ///
+  /// The light above the threshold is blurred at each scale, and the average
+  /// of the blurs, times the intensity, is added back.  This works on the
+  /// unbounded colors of a rendered canvas, so it belongs before exposure and
+  /// tone mapping.  Alpha is left as it is.
+  pub fn bloom(&self, bloom: &Bloom) -> Canvas {
+    let mut canvas = self.clone();
+    if bloom.levels == 0 || self.width == 0 || self.height == 0 {
+      return canvas;
+    }
+    let bright = self.bright_pass(bloom.threshold);
+    let weight = bloom.intensity / bloom.levels as f64;
+    for level in 0..bloom.levels {
+      let factor = 2usize.saturating_pow(level as u32);
+      let (width, height) = ((self.width / factor).max(1), (self.height / factor).max(1));
+      let glow = bright
+        .resize(width, height, ResizeFilter::Bilinear)
+        .gaussian_blur(bloom.sigma)
+        .resize(self.width, self.height, ResizeFilter::Bilinear);
+      for (color, glow) in canvas.pixels.iter_mut().zip(glow.pixels.iter()) {
+        *color = *color + *glow * weight;
+      }
+    }
+    canvas
+  }
+}
@@ -0,0 +1,110 @@
+use super::Canvas;
+use crate::color::Color;
+
+/// How far apart two canvases are, measured over every color channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImageDiff {
+  /// The mean of the absolute channel differences.
+  pub mean_absolute_error: f64,
+  /// The root of the mean of the squared channel differences.
+  pub rmse: f64,
+  /// The peak signal-to-noise ratio in decibels, for a peak value of 1;
+  /// infinite if the canvases are identical.
+  pub psnr: f64,
+  /// The largest absolute channel difference.
+  pub max_error: f64,
+}
+
+impl Canvas {
+  /// Iterate over the channel differences between this canvas and another,
+  /// or return `None` if the canvases are not the same size.
+  fn channel_differences<'a>(&'a self, other: &'a Canvas) -> Option<impl Iterator<Item = f64> + 'a> {
+    if self.width != other.width || self.height != other.height {
+      return None;
+    }
+    Some(
+      self
+        .pixels
+        .iter()
+        .zip(other.pixels.iter())
+        .flat_map(|(a, b)| [a.0 - b.0, a.1 - b.1, a.2 - b.2]),
+    )
+  }
+
+  /// Compare this canvas with another of the same size, or return `None` if
+  /// the sizes differ.
+  ///
+  /// Only the colors are compared, not the alpha.
+  pub fn compare(&self, other: &Canvas) -> Option<ImageDiff> {
+    let count = (self.pixels.len() * 3).max(1) as f64;
+    let (absolute, squared, max_error) = self
+      .channel_differences(other)?
+      .fold((0.0, 0.0, 0.0_f64), |(absolute, squared, max), d| {
+        (absolute + d.abs(), squared + d * d, max.max(d.abs()))
+      });
+    let rmse = (squared / count).sqrt();
+    Some(ImageDiff {
+      mean_absolute_error: absolute / count,
+      rmse,
+      psnr: if rmse == 0.0 {
+        f64::INFINITY
+      } else {
+        -20.0 * rmse.log10()
+      },
+      max_error,
+    })
+  }
+
+  /// The root mean squared error between this canvas and another of the same
+  /// size.
+  pub fn rmse(&self, other: &Canvas) -> Option<f64> {
+    self.compare(other).map(|diff| diff.rmse)
+  }
+
+  /// The peak signal-to-noise ratio between this canvas and another of the
+  /// same size.
+  pub fn psnr(&self, other: &Canvas) -> Option<f64> {
+    self.compare(other).map(|diff| diff.psnr)
+  }
+
+  /// The largest channel difference between this canvas and another of the
+  /// same size.
+  pub fn max_error(&self, other: &Canvas) -> Option<f64> {
+    self.compare(other).map(|diff| diff.max_error)
+  }
+
+  /// Build a canvas of the absolute channel differences with another of the
+  /// same size, or return `None` if the sizes differ.
+  pub fn abs_diff(&self, other: &Canvas) -> Option<Canvas> {
+    let mut diff = Canvas::new(self.width, self.height);
+    for (pixel, d) in diff
+      .pixels
+      .iter_mut()
+      .zip(self.channel_differences(other)?.collect::<Vec<_>>().chunks(3))
+    {
+      *pixel = Color(d[0].abs(), d[1].abs(), d[2].abs());
+    }
+    Some(diff)
+  }
+
+  /// Build a canvas that shows where this canvas differs from another.
+  ///
+  /// Matching pixels are a dim gray copy of this canvas; differing pixels run
+  /// from yellow for the smallest difference to red for the largest.  Returns
+  /// `None` if the sizes differ.
+  pub fn diff_visualization(&self, other: &Canvas) -> Option<Canvas> {
+    let diff = self.abs_diff(other)?;
+    let errors: Vec<f64> = diff.pixels.iter().map(|d| d.0.max(d.1).max(d.2)).collect();
+    let max_error = errors.iter().copied().fold(0.0, f64::max);
+    let mut visualization = Canvas::new(self.width, self.height);
+    for (i, error) in errors.iter().enumerate() {
+      visualization.pixels[i] = if *error == 0.0 {
+        let gray = self.pixels[i].luminance().clamp(0.0, 1.0) * 0.25;
+        Color(gray, gray, gray)
+      } else {
+        Color(1.0, 1.0 - error / max_error, 0.0)
+      };
+    }
+    Some(visualization)
+  }
+}
@@ -0,0 +1,341 @@
+use super::Canvas;
+use crate::error::{Error, Result};
+use flate2::write::ZlibEncoder;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// How each sample of a channel is stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExrPixelType {
+  /// 16-bit floating point; plenty for color.
+  #[default]
+  Half,
+  /// 32-bit floating point, for data like depth that needs the precision.
+  Float,
+}
+
+impl ExrPixelType {
+  fn code(self) -> i32 {
+    match self {
+      ExrPixelType::Half => 1,
+      ExrPixelType::Float => 2,
+    }
+  }
+
+  fn size(self) -> usize {
+    match self {
+      ExrPixelType::Half => 2,
+      ExrPixelType::Float => 4,
+    }
+  }
+}
+
+/// How the scanline blocks are compressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExrCompression {
+  #[default]
+  None,
+  /// Run-length encoding, one scanline per block.
+  Rle,
+  /// Zlib, one scanline per block.
+  Zips,
+  /// Zlib, sixteen scanlines per block.
+  Zip,
+}
+
+impl ExrCompression {
+  fn code(self) -> u8 {
+    match self {
+      ExrCompression::None => 0,
+      ExrCompression::Rle => 1,
+      ExrCompression::Zips => 2,
+      ExrCompression::Zip => 3,
+    }
+  }
+
+  /// The number of scanlines stored together in each block.
+  pub fn lines_per_block(self) -> usize {
+    match self {
+      ExrCompression::Zip => 16,
+      _ => 1,
+    }
+  }
+}
+
+/// How a canvas is written to an OpenEXR file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExrOptions {
+  pub pixel_type: ExrPixelType,
+  pub compression: ExrCompression,
+  /// Whether to write an alpha channel alongside the color.
+  pub alpha: bool,
+}
+
+impl ExrOptions {
+  /// Create new EXR options.
+  pub fn new(pixel_type: ExrPixelType, compression: ExrCompression, alpha: bool) -> Self {
+    ExrOptions {
+      pixel_type,
+      compression,
+      alpha,
+    }
+  }
+}
+
+/// An additional named channel to store, e.g. `Z` for depth.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExrChannel {
+  pub name: String,
+  pub pixel_type: ExrPixelType,
+  /// One value per pixel, in the same order as the canvas pixels.
+  pub values: Vec<f64>,
+}
+
+impl ExrChannel {
+  /// Create a new channel.
+  pub fn new(name: &str, pixel_type: ExrPixelType, values: Vec<f64>) -> Self {
+    ExrChannel {
+      name: name.to_string(),
+      pixel_type,
+      values,
+    }
+  }
+}
+
+/// Convert a value to the bits of the nearest 16-bit float, rounding ties to
+/// even as IEEE 754 does.
+pub fn f32_to_half(value: f32) -> u16 {
+  let bits = value.to_bits();
+  let sign = ((bits >> 16) & 0x8000) as u16;
+  let exponent = ((bits >> 23) & 0xff) as i32;
+  let mantissa = bits & 0x007f_ffff;
+  if exponent == 0xff {
+    // Infinity stays infinite, and NaN stays NaN.
+    return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+  }
+  let exponent = exponent - 127 + 15;
+  if exponent >= 0x1f {
+    return sign | 0x7c00;
+  }
+  if exponent <= 0 {
+    // Too small for a normal half, so shift into a subnormal.
+    if exponent < -10 {
+      return sign;
+    }
+    return sign | shift_rounding_to_even(mantissa | 0x0080_0000, (14 - exponent) as u32) as u16;
+  }
+  // A carry out of the mantissa rounds up into the exponent, which is still
+  // correct, and reaches infinity past the largest half.
+  sign | shift_rounding_to_even(((exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+/// Shift `bits` right, rounding to the nearest result and ties to even.
+fn shift_rounding_to_even(bits: u32, shift: u32) -> u32 {
+  let result = bits >> shift;
+  let remainder = bits & ((1 << shift) - 1);
+  let halfway = 1 << (shift - 1);
+  if remainder > halfway || (remainder == halfway && result & 1 == 1) {
+    result + 1
+  } else {
+    result
+  }
+}
+
+/// Write a header attribute.
+fn write_attribute(out: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+  out.extend_from_slice(name.as_bytes());
+  out.push(0);
+  out.extend_from_slice(kind.as_bytes());
+  out.push(0);
+  out.extend_from_slice(&(value.len() as i32).to_le_bytes());
+  out.extend_from_slice(value);
+}
+
+/// Reorder and difference the bytes of a block so that they compress well.
+fn predict(raw: &[u8]) -> Vec<u8> {
+  let mut reordered: Vec<u8> = raw.iter().step_by(2).copied().collect();
+  reordered.extend(raw.iter().skip(1).step_by(2));
+  let mut previous = reordered.first().copied().unwrap_or(0);
+  for byte in reordered.iter_mut().skip(1) {
+    let current = *byte;
+    *byte = current.wrapping_sub(previous).wrapping_add(128);
+    previous = current;
+  }
+  reordered
+}
+
+/// Run-length encode a block the way OpenEXR expects.
+fn rle_compress(data: &[u8]) -> Vec<u8> {
+  const MIN_RUN: usize = 3;
+  const MAX_RUN: usize = 127;
+  let mut out = vec![];
+  let mut run_start = 0;
+  let mut run_end = 1;
+  while run_start < data.len() {
+    while run_end < data.len() && data[run_start] == data[run_end] && run_end - run_start - 1 < MAX_RUN {
+      run_end += 1;
+    }
+    if run_end - run_start >= MIN_RUN {
+      out.push((run_end - run_start - 1) as u8);
+      out.push(data[run_start]);
+      run_start = run_end;
+    } else {
+      while run_end < data.len()
+        && ((run_end + 1 >= data.len() || data[run_end] != data[run_end + 1])
+          || (run_end + 2 >= data.len() || data[run_end + 1] != data[run_end + 2]))
+        && run_end - run_start < MAX_RUN
+      {
+        run_end += 1;
+      }
+      out.push((run_start as isize - run_end as isize) as u8);
+      out.extend_from_slice(&data[run_start..run_end]);
+      run_start = run_end;
+    }
+    run_end += 1;
+  }
+  out
+}
+
+/// Compress a block, falling back to the raw bytes if that is smaller.
+fn compress(raw: Vec<u8>, compression: ExrCompression) -> Result<Vec<u8>> {
+  let compressed = match compression {
+    ExrCompression::None => return Ok(raw),
+    ExrCompression::Rle => rle_compress(&predict(&raw)),
+    ExrCompression::Zips | ExrCompression::Zip => {
+      let mut encoder = ZlibEncoder::new(vec![], flate2::Compression::default());
+      encoder.write_all(&predict(&raw))?;
+      encoder.finish()?
+    },
+  };
+  if compressed.len() < raw.len() {
+    Ok(compressed)
+  } else {
+    Ok(raw)
+  }
+}
+
+impl Canvas {
+  /// Convert to an OpenEXR file of scanlines, keeping the full dynamic range.
+  ///
+  /// OpenEXR can't store an empty image, so the canvas must have at least one
+  /// pixel.
+  pub fn to_exr(&self, options: &ExrOptions) -> Result<Vec<u8>> {
+    self.to_exr_with_channels(options, &[])
+  }
+
+  /// Convert to an OpenEXR file, storing extra channels such as depth or
+  /// normals alongside the color.
+  ///
+  /// Each extra channel must have one value per pixel.
+  pub fn to_exr_with_channels(&self, options: &ExrOptions, extra: &[ExrChannel]) -> Result<Vec<u8>> {
+    if self.width == 0 || self.height == 0 {
+      return Err(Error::Format(format!(
+        "An EXR image can't be {}x{} pixels",
+        self.width, self.height
+      )));
+    }
+    let pixel_count = self.width * self.height;
+    let mut channels: Vec<(String, ExrPixelType, Vec<f64>)> = vec![
+      (
+        "R".to_string(),
+        options.pixel_type,
+        self.pixels.iter().map(|c| c.0).collect(),
+      ),
+      (
+        "G".to_string(),
+        options.pixel_type,
+        self.pixels.iter().map(|c| c.1).collect(),
+      ),
+      (
+        "B".to_string(),
+        options.pixel_type,
+        self.pixels.iter().map(|c| c.2).collect(),
+      ),
+    ];
+    if options.alpha {
+      channels.push(("A".to_string(), options.pixel_type, self.alpha.clone()));
+    }
+    for channel in extra {
+      if channel.values.len() != pixel_count {
+        return Err(Error::Format(format!(
+          "EXR channel {} has {} values for {} pixels",
+          channel.name,
+          channel.values.len(),
+          pixel_count
+        )));
+      }
+      channels.push((channel.name.clone(), channel.pixel_type, channel.values.clone()));
+    }
+    // Readers expect the channels in alphabetical order.
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+    if let Some(pair) = channels.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+      return Err(Error::Format(format!(
+        "EXR channel {} is given more than once",
+        pair[0].0
+      )));
+    }
+
+    let mut exr = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+    let mut chlist = vec![];
+    for (name, pixel_type, _) in &channels {
+      chlist.extend_from_slice(name.as_bytes());
+      chlist.push(0);
+      chlist.extend_from_slice(&pixel_type.code().to_le_bytes());
+      chlist.extend_from_slice(&[0, 0, 0, 0]);
+      chlist.extend_from_slice(&1i32.to_le_bytes());
+      chlist.extend_from_slice(&1i32.to_le_bytes());
+    }
+    chlist.push(0);
+    let window: Vec<u8> = [0, 0, self.width as i32 - 1, self.height as i32 - 1]
+      .iter()
+      .flat_map(|v| v.to_le_bytes())
+      .collect();
+    write_attribute(&mut exr, "channels", "chlist", &chlist);
+    write_attribute(&mut exr, "compression", "compression", &[options.compression.code()]);
+    write_attribute(&mut exr, "dataWindow", "box2i", &window);
+    write_attribute(&mut exr, "displayWindow", "box2i", &window);
+    write_attribute(&mut exr, "lineOrder", "lineOrder", &[0]);
+    write_attribute(&mut exr, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    write_attribute(&mut exr, "screenWindowCenter", "v2f", &[0; 8]);
+    write_attribute(&mut exr, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    exr.push(0);
+
+    let lines_per_block = options.compression.lines_per_block();
+    let block_count = self.height.div_ceil(lines_per_block);
+    let table_start = exr.len();
+    exr.resize(table_start + block_count * 8, 0);
+    for block in 0..block_count {
+      let offset = exr.len() as u64;
+      exr[table_start + block * 8..table_start + block * 8 + 8].copy_from_slice(&offset.to_le_bytes());
+      let first_line = block * lines_per_block;
+      let last_line = (first_line + lines_per_block).min(self.height);
+      let mut raw = vec![];
+      for y in first_line..last_line {
+        for (_, pixel_type, values) in &channels {
+          for value in &values[y * self.width..(y + 1) * self.width] {
+            match pixel_type {
+              ExrPixelType::Half => raw.extend_from_slice(&f32_to_half(*value as f32).to_le_bytes()),
+              ExrPixelType::Float => raw.extend_from_slice(&(*value as f32).to_le_bytes()),
+            }
+          }
+        }
+      }
+      debug_assert_eq!(
+        raw.len(),
+        (last_line - first_line) * self.width * channels.iter().map(|c| c.1.size()).sum::<usize>()
+      );
+      let data = compress(raw, options.compression)?;
+      exr.extend_from_slice(&(first_line as i32).to_le_bytes());
+      exr.extend_from_slice(&(data.len() as i32).to_le_bytes());
+      exr.extend_from_slice(&data);
+    }
+    Ok(exr)
+  }
+
+  /// Write the OpenEXR file to disk.
+  pub fn save_exr(&self, path: &Path, options: &ExrOptions) -> Result<()> {
+    fs::write(path, self.to_exr(options)?)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,280 @@
+use super::Canvas;
+use crate::color::Color;
+use crate::error::{Error, Result};
+use std::fs;
+use std::path::Path;
+
+/// Run-length encoded scanlines must be at least this wide...
+const MIN_RLE_WIDTH: usize = 8;
+/// ...and narrower than this.
+const MAX_RLE_WIDTH: usize = 0x7fff;
+/// Shorter runs are cheaper to store as literal bytes.
+const MIN_RUN: usize = 4;
+
+/// Encode a color as four bytes sharing a single exponent.
+///
+/// Negative and NaN channels are stored as zero, and channels too bright for
+/// the exponent byte as the brightest value it can hold.
+pub fn color_to_rgbe(color: Color) -> [u8; 4] {
+  let brightest = 2f64.powi(127) * 255.0 / 256.0;
+  let channel = |c: f64| c.max(0.0).min(brightest);
+  let (r, g, b) = (channel(color.0), channel(color.1), channel(color.2));
+  let v = r.max(g).max(b);
+  if v < 1e-32 {
+    return [0, 0, 0, 0];
+  }
+  // Split v into a mantissa in [0.5, 1) and a power of two.
+  let mut exponent = v.log2().floor() as i32 + 1;
+  let mut mantissa = v / 2f64.powi(exponent);
+  if mantissa >= 1.0 {
+    mantissa /= 2.0;
+    exponent += 1;
+  } else if mantissa < 0.5 {
+    mantissa *= 2.0;
+    exponent -= 1;
+  }
+  let scale = mantissa * 256.0 / v;
+  [
+    (r * scale) as u8,
+    (g * scale) as u8,
+    (b * scale) as u8,
+    (exponent + 128).clamp(0, 255) as u8,
+  ]
+}
+
+/// Decode four bytes sharing a single exponent into a color.
+pub fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+  if rgbe[3] == 0 {
+    return Color::default();
+  }
+  let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+  Color(
+    (rgbe[0] as f64 + 0.5) * f,
+    (rgbe[1] as f64 + 0.5) * f,
+    (rgbe[2] as f64 + 0.5) * f,
+  )
+}
+
+/// Run-length encode one component of a scanline.
+fn encode_component(data: &[u8], out: &mut Vec<u8>) {
+  let mut cur = 0;
+  while cur < data.len() {
+    // Find the start of the next run long enough to be worth encoding.
+    let mut begin_run = cur;
+    let mut run_count = 0;
+    let mut old_run_count = 0;
+    while run_count < MIN_RUN && begin_run < data.len() {
+      begin_run += run_count;
+      old_run_count = run_count;
+      run_count = 1;
+      while begin_run + run_count < data.len() && run_count < 127 && data[begin_run] == data[begin_run + run_count] {
+        run_count += 1;
+      }
+    }
+    // A short run right before the long one can still be written as a run.
+    if old_run_count > 1 && old_run_count == begin_run - cur {
+      out.push((128 + old_run_count) as u8);
+      out.push(data[cur]);
+      cur = begin_run;
+    }
+    // Everything else up to the run is written as literal bytes.
+    while cur < begin_run {
+      let count = (begin_run - cur).min(128);
+      out.push(count as u8);
+      out.extend_from_slice(&data[cur..cur + count]);
+      cur += count;
+    }
+    if run_count >= MIN_RUN {
+      out.push((128 + run_count) as u8);
+      out.push(data[begin_run]);
+      cur += run_count;
+    }
+  }
+}
+
+/// Read one run-length encoded component of a scanline into `component`.
+fn decode_component(bytes: &[u8], pos: &mut usize, component: &mut [u8]) -> std::result::Result<(), String> {
+  let mut filled = 0;
+  while filled < component.len() {
+    let count = *bytes.get(*pos).ok_or("Unexpected end of HDR data")? as usize;
+    *pos += 1;
+    if count > 128 {
+      let count = count - 128;
+      let value = *bytes.get(*pos).ok_or("Unexpected end of HDR data")?;
+      *pos += 1;
+      if filled + count > component.len() {
+        return Err("HDR run overflows its scanline".to_string());
+      }
+      component[filled..filled + count].fill(value);
+      filled += count;
+    } else {
+      if count == 0 || filled + count > component.len() {
+        return Err("Bad HDR literal count".to_string());
+      }
+      let literal = bytes.get(*pos..*pos + count).ok_or("Unexpected end of HDR data")?;
+      component[filled..filled + count].copy_from_slice(literal);
+      *pos += count;
+      filled += count;
+    }
+  }
+  Ok(())
+}
+
+/// What the header of an HDR file says about the pixels that follow it.
+struct Header {
+  width: usize,
+  height: usize,
+  /// The product of every `EXPOSURE=` line; the pixels were multiplied by it
+  /// when the file was written.
+  exposure: f64,
+  /// The offset of the first scanline.
+  data_start: usize,
+}
+
+/// Read the header up to and including the resolution line.
+fn parse_header(bytes: &[u8]) -> std::result::Result<Header, String> {
+  let mut pos = 0;
+  let mut next_line = || -> std::result::Result<String, String> {
+    let end = bytes[pos..]
+      .iter()
+      .position(|&b| b == b'\n')
+      .ok_or("Unexpected end of HDR header")?;
+    let line = String::from_utf8_lossy(&bytes[pos..pos + end]).into_owned();
+    pos += end + 1;
+    Ok(line)
+  };
+  if !next_line()?.starts_with("#?") {
+    return Err("Not a Radiance HDR file".to_string());
+  }
+  let mut exposure = 1.0;
+  loop {
+    let line = next_line()?;
+    if line.is_empty() {
+      break;
+    }
+    if let Some(format) = line.strip_prefix("FORMAT=") {
+      if format != "32-bit_rle_rgbe" {
+        return Err(format!("Unsupported HDR format: {}", format));
+      }
+    }
+    if let Some(value) = line.strip_prefix("EXPOSURE=") {
+      match value.trim().parse::<f64>() {
+        Ok(value) if value > 0.0 && value.is_finite() => exposure *= value,
+        _ => return Err(format!("Bad HDR exposure: {}", value)),
+      }
+    }
+  }
+  let resolution = next_line()?;
+  let fields: Vec<&str> = resolution.split_whitespace().collect();
+  match fields.as_slice() {
+    ["-Y", height, "+X", width] => {
+      let height = height.parse::<usize>().map_err(|e| format!("Bad HDR height: {}", e))?;
+      let width = width.parse::<usize>().map_err(|e| format!("Bad HDR width: {}", e))?;
+      Ok(Header {
+        width,
+        height,
+        exposure,
+        data_start: pos,
+      })
+    },
+    _ => Err(format!("Unsupported HDR resolution line: {}", resolution)),
+  }
+}
+
+/// The fewest bytes a scanline of the given width can be stored in.
+fn min_scanline_size(width: usize) -> usize {
+  if (MIN_RLE_WIDTH..MAX_RLE_WIDTH).contains(&width) {
+    // The scanline marker, then each component as runs of the longest length.
+    4 + 4 * 2 * width.div_ceil(127)
+  } else {
+    4 * width
+  }
+}
+
+impl Canvas {
+  /// Convert to a Radiance HDR file, keeping the full dynamic range.
+  pub fn to_hdr(&self) -> Vec<u8> {
+    let mut hdr = format!(
+      "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+      self.height, self.width
+    )
+    .into_bytes();
+    for y in 0..self.height {
+      let scanline: Vec<[u8; 4]> = (0..self.width)
+        .map(|x| color_to_rgbe(self.get_color_at(x, y)))
+        .collect();
+      if !(MIN_RLE_WIDTH..MAX_RLE_WIDTH).contains(&self.width) {
+        scanline.iter().for_each(|rgbe| hdr.extend_from_slice(rgbe));
+        continue;
+      }
+      hdr.extend_from_slice(&[2, 2, (self.width >> 8) as u8, (self.width & 0xff) as u8]);
+      for i in 0..4 {
+        let component: Vec<u8> = scanline.iter().map(|rgbe| rgbe[i]).collect();
+        encode_component(&component, &mut hdr);
+      }
+    }
+    hdr
+  }
+
+  /// Write the Radiance HDR file to disk.
+  pub fn save_hdr(&self, path: &Path) -> Result<()> {
+    fs::write(path, self.to_hdr())?;
+    Ok(())
+  }
+
+  /// Read a canvas from the contents of a Radiance HDR file.
+  ///
+  /// Flat and run-length encoded scanlines are supported; the obsolete
+  /// run-length encoding from before 1991 is not.  Pixels are divided by any
+  /// `EXPOSURE=` in the header, giving back the radiance the file was written
+  /// from.
+  pub fn from_hdr(bytes: &[u8]) -> Result<Canvas> {
+    Canvas::decode_hdr(bytes).map_err(Error::Format)
+  }
+
+  fn decode_hdr(bytes: &[u8]) -> std::result::Result<Canvas, String> {
+    let Header {
+      width,
+      height,
+      exposure,
+      data_start: mut pos,
+    } = parse_header(bytes)?;
+    // Check the resolution against the data before allocating the canvas.
+    width.checked_mul(height).ok_or("HDR resolution is too large")?;
+    match height.checked_mul(min_scanline_size(width)) {
+      Some(size) if size <= bytes.len() - pos => {},
+      _ => return Err("HDR data is too short for its resolution".to_string()),
+    }
+    let mut canvas = Canvas::new(width, height);
+    let mut components = vec![vec![0u8; width]; 4];
+    for y in 0..height {
+      let start = bytes.get(pos..pos + 4).ok_or("Unexpected end of HDR data")?;
+      let is_rle = (MIN_RLE_WIDTH..MAX_RLE_WIDTH).contains(&width) && start[0] == 2 && start[1] == 2;
+      if is_rle {
+        if ((start[2] as usize) << 8 | start[3] as usize) != width {
+          return Err("HDR scanline width does not match the header".to_string());
+        }
+        pos += 4;
+        for component in components.iter_mut() {
+          decode_component(bytes, &mut pos, component)?;
+        }
+        for x in 0..width {
+          let rgbe = [components[0][x], components[1][x], components[2][x], components[3][x]];
+          canvas.set_color_at(x, y, rgbe_to_color(rgbe) / exposure);
+        }
+      } else {
+        for x in 0..width {
+          let rgbe = bytes.get(pos..pos + 4).ok_or("Unexpected end of HDR data")?;
+          canvas.set_color_at(x, y, rgbe_to_color([rgbe[0], rgbe[1], rgbe[2], rgbe[3]]) / exposure);
+          pos += 4;
+        }
+      }
+    }
+    Ok(canvas)
+  }
+
+  /// Read a canvas from a Radiance HDR file on disk.
+  pub fn load_hdr(path: &Path) -> Result<Canvas> {
+    Canvas::from_hdr(&fs::read(path)?)
+  }
+}
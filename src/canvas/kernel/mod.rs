@@ -0,0 +1,117 @@
+use super::Canvas;
+use crate::color::Color;
+
+/// A grid of weights for convolving a canvas.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Kernel {
+  pub width: usize,
+  pub height: usize,
+  /// The weights, row by row.
+  pub weights: Vec<f64>,
+}
+
+impl Kernel {
+  /// Create a kernel from rows of weights, which must all be the same length.
+  pub fn from_rows(rows: Vec<Vec<f64>>) -> Self {
+    let width = rows.first().map_or(0, |row| row.len());
+    assert!(
+      rows.iter().all(|row| row.len() == width),
+      "Every row of a kernel must have the same length"
+    );
+    Kernel {
+      width,
+      height: rows.len(),
+      weights: rows.concat(),
+    }
+  }
+
+  /// A square kernel averaging the pixels within `radius` of the center.
+  pub fn box_blur(radius: usize) -> Self {
+    let size = 2 * radius + 1;
+    let weight = 1.0 / (size * size) as f64;
+    Kernel::from_rows(vec![vec![weight; size]; size])
+  }
+
+  /// A square Gaussian kernel with the given standard deviation, reaching
+  /// three deviations from the center.
+  pub fn gaussian(sigma: f64) -> Self {
+    let row = Kernel::gaussian_row(sigma).weights;
+    Kernel::from_rows(row.iter().map(|a| row.iter().map(|b| a * b).collect()).collect())
+  }
+
+  /// A single row of a Gaussian kernel; blurring with it and then with its
+  /// transpose is the same as blurring with `gaussian`, but much quicker.
+  pub fn gaussian_row(sigma: f64) -> Self {
+    if sigma <= 0.0 {
+      return Kernel::from_rows(vec![vec![1.0]]);
+    }
+    let radius = (3.0 * sigma).ceil() as isize;
+    let weights: Vec<f64> = (-radius..=radius)
+      .map(|x| (-(x * x) as f64 / (2.0 * sigma * sigma)).exp())
+      .collect();
+    let total: f64 = weights.iter().sum();
+    Kernel::from_rows(vec![weights.iter().map(|weight| weight / total).collect()])
+  }
+
+  /// A 3x3 kernel that sharpens edges.
+  pub fn sharpen() -> Self {
+    Kernel::from_rows(vec![vec![0.0, -1.0, 0.0], vec![-1.0, 5.0, -1.0], vec![0.0, -1.0, 0.0]])
+  }
+
+  /// Swap the rows and columns of the kernel.
+  pub fn transpose(&self) -> Self {
+    Kernel {
+      width: self.height,
+      height: self.width,
+      weights: (0..self.width)
+        .flat_map(|x| (0..self.height).map(move |y| self.get(x, y)))
+        .collect(),
+    }
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> f64 {
+    self.weights[y * self.width + x]
+  }
+}
+
+impl Canvas {
+  /// Convolve the canvas with the kernel.
+  ///
+  /// The middle of the kernel, or just below and right of it for even sizes,
+  /// sits on each pixel in turn, and the pixel becomes the weighted sum of
+  /// those under the kernel, which isn't flipped.  Past the edges of the
+  /// canvas, the edge pixels repeat.  Colors and alpha are convolved alike.
+  pub fn convolve(&self, kernel: &Kernel) -> Canvas {
+    let mut canvas = Canvas::new(self.width, self.height);
+    let (cx, cy) = ((kernel.width / 2) as isize, (kernel.height / 2) as isize);
+    let clamp = |value: isize, size: usize| value.clamp(0, size as isize - 1) as usize;
+    for y in 0..self.height {
+      for x in 0..self.width {
+        let mut color = Color::default();
+        let mut alpha = 0.0;
+        for ky in 0..kernel.height {
+          for kx in 0..kernel.width {
+            let weight = kernel.get(kx, ky);
+            if weight == 0.0 {
+              continue;
+            }
+            let sx = clamp(x as isize + kx as isize - cx, self.width);
+            let sy = clamp(y as isize + ky as isize - cy, self.height);
+            color = color + self.get_color_at(sx, sy) * weight;
+            alpha += self.get_alpha_at(sx, sy) * weight;
+          }
+        }
+        canvas.set_color_at(x, y, color);
+        canvas.set_alpha_at(x, y, alpha);
+      }
+    }
+    canvas
+  }
+
+  /// Blur the canvas with a Gaussian of the given standard deviation, as a
+  /// pass along the rows and then one down the columns.
+  pub fn gaussian_blur(&self, sigma: f64) -> Canvas {
+    let row = Kernel::gaussian_row(sigma);
+    self.convolve(&row).convolve(&row.transpose())
+  }
+}
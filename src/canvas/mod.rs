@@ -1,16 +1,30 @@
 use crate::color::Color;
-use png::{BitDepth, ColorType, Encoder};
-use std::fs::File;
-use std::io::BufWriter;
-use std::io::Write;
+use crate::error::Result;
+use crate::output::{Exposure, OutputOptions, Quantizer, SampleDepth, TransferFunction};
+use png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
+pub mod bloom;
+pub mod diff;
+pub mod exr;
+pub mod hdr;
+pub mod kernel;
+pub mod ops;
+pub mod ppm;
+pub mod terminal;
+
 /// A Canvas of pixels.
+///
+/// Each pixel also has an alpha value, the fraction of it covered by the
+/// image; colors are stored premultiplied by it.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Canvas {
   pub width: usize,
   pub height: usize,
   pub pixels: Vec<Color>,
+  pub alpha: Vec<f64>,
 }
 
 impl Canvas {
@@ -19,6 +33,7 @@ impl Canvas {
       width,
       height,
       pixels: vec![Color::default(); width * height],
+      alpha: vec![1.0; width * height],
     }
   }
 
@@ -30,61 +45,152 @@ impl Canvas {
     self.pixels[y * self.width + x]
   }
 
-  /// Convert to a PPM file.
-  pub fn to_ppm(&self) -> String {
-    let mut ppm = "P3\n".to_string();
-    ppm.push_str(&format!("{} {}\n255", self.width, self.height));
-    for y in 0..self.height {
-      ppm.push('\n');
-      for x in 0..self.width {
-        let color = self.get_color_at(x, y);
-        ppm.push_str(&format!(
-          "{} {} {}",
-          (color.0 * 256.0) as u8,
-          (color.1 * 256.0) as u8,
-          (color.2 * 256.0) as u8
-        ));
-        if x < self.width - 1 {
-          ppm.push(' ');
-        }
-      }
+  pub fn set_alpha_at(&mut self, x: usize, y: usize, alpha: f64) {
+    self.alpha[y * self.width + x] = alpha;
+  }
+
+  pub fn get_alpha_at(&self, x: usize, y: usize) -> f64 {
+    self.alpha[y * self.width + x]
+  }
+
+  /// Compute the log-average luminance of the pixels, a measure of overall
+  /// brightness that isn't dominated by a few very bright pixels.
+  pub fn log_average_luminance(&self) -> f64 {
+    if self.pixels.is_empty() {
+      return 0.0;
     }
-    ppm.push('\n');
-    ppm
+    let delta = 1e-4;
+    let sum = self
+      .pixels
+      .iter()
+      .map(|color| (delta + color.luminance().max(0.0)).ln())
+      .sum::<f64>();
+    (sum / self.pixels.len() as f64).exp()
   }
 
-  /// Write the PPM to a file.
-  pub fn save_ppm(&self, path: &Path) {
-    let ppm = self.to_ppm();
-    let display = path.display();
-    let mut file = match File::create(path) {
-      Err(why) => panic!("couldn't create {}: {}", display, why),
-      Ok(file) => file,
+  /// Work out the exposure the options give this canvas, ready to quantize
+  /// its colors.
+  pub fn quantizer(&self, options: &OutputOptions) -> Quantizer {
+    let log_average_luminance = match options.exposure {
+      Exposure::Auto { .. } => self.log_average_luminance(),
+      Exposure::Stops(_) => 0.0,
     };
-    match file.write_all(ppm.as_bytes()) {
-      Err(why) => panic!("couldn't write to {}: {}", display, why),
-      Ok(_) => println!("successfully wrote to {}", display),
-    }
+    Quantizer::new(options, log_average_luminance)
   }
 
   /// Convert to a PNG file.
-  pub fn to_png(&self, path: &Path) {
-    let file = File::create(path).unwrap();
-    let w = &mut BufWriter::new(file);
+  pub fn to_png(&self, path: &Path) -> Result<()> {
+    self.to_png_with(path, &OutputOptions::default())
+  }
+
+  /// Convert to a PNG file, quantizing colors with the given options.
+  pub fn to_png_with(&self, path: &Path, options: &OutputOptions) -> Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    self.write_png(&mut w, options)?;
+    w.flush()?;
+    Ok(())
+  }
+
+  /// Encode as the contents of a PNG file, quantizing colors with the given
+  /// options.
+  pub fn to_png_bytes(&self, options: &OutputOptions) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    self.write_png(&mut bytes, options)?;
+    Ok(bytes)
+  }
+
+  /// Encode as a PNG into the given writer.
+  fn write_png<W: Write>(&self, w: W, options: &OutputOptions) -> Result<()> {
     let mut encoder = Encoder::new(w, self.width as u32, self.height as u32);
-    encoder.set_color(ColorType::Rgb);
-    encoder.set_depth(BitDepth::Eight);
-    let mut writer = encoder.write_header().unwrap();
-    let data = self
+    encoder.set_color(if options.alpha { ColorType::Rgba } else { ColorType::Rgb });
+    encoder.set_depth(match options.depth {
+      SampleDepth::Eight => BitDepth::Eight,
+      SampleDepth::Sixteen => BitDepth::Sixteen,
+    });
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&self.to_png_data(options))?;
+    writer.finish()?;
+    Ok(())
+  }
+
+  /// Quantize the pixels into the byte layout of PNG image data.
+  ///
+  /// With alpha, colors are divided by it first, since PNG stores straight
+  /// rather than premultiplied colors.
+  pub fn to_png_data(&self, options: &OutputOptions) -> Vec<u8> {
+    let quantizer = self.quantizer(options);
+    self
       .pixels
       .iter()
-      .flat_map(|color| {
-        let r = (color.0 * 256.0) as u8;
-        let g = (color.1 * 256.0) as u8;
-        let b = (color.2 * 256.0) as u8;
-        vec![r, g, b]
+      .zip(self.alpha.iter())
+      .flat_map(|(color, alpha)| {
+        if !options.alpha {
+          return quantizer.quantize_color(*color).to_vec();
+        }
+        let straight = if *alpha > 0.0 {
+          *color / *alpha
+        } else {
+          Color::default()
+        };
+        let [r, g, b] = quantizer.quantize_color(straight);
+        vec![r, g, b, quantizer.quantize_alpha(*alpha)]
+      })
+      .flat_map(|sample| match options.depth {
+        SampleDepth::Eight => vec![sample as u8],
+        SampleDepth::Sixteen => sample.to_be_bytes().to_vec(),
+      })
+      .collect::<Vec<u8>>()
+  }
+
+  /// Read a canvas from the contents of a PNG file, without decoding.
+  pub fn from_png(bytes: &[u8]) -> Result<Canvas> {
+    Canvas::from_png_with(bytes, TransferFunction::Linear)
+  }
+
+  /// Read a canvas from the contents of a PNG file, decoding the samples with
+  /// the given transfer function to get linear colors.
+  ///
+  /// Any bit depth and color type is accepted; palettes are expanded, gray is
+  /// copied to all three channels, and any alpha is kept.
+  pub fn from_png_with(bytes: &[u8], transfer: TransferFunction) -> Result<Canvas> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.set_transformations(Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    let (sample_size, max_value) = match info.bit_depth {
+      BitDepth::Sixteen => (2, 65535.0),
+      _ => (1, 255.0),
+    };
+    let samples: Vec<f64> = buffer[..info.buffer_size()]
+      .chunks(sample_size)
+      .map(|sample| match sample {
+        [high, low] => u16::from_be_bytes([*high, *low]) as f64 / max_value,
+        _ => sample[0] as f64 / max_value,
       })
-      .collect::<Vec<u8>>();
-    writer.write_image_data(&data).unwrap(); // Save
+      .collect();
+    let channels = info.color_type.samples();
+    let mut canvas = Canvas::new(info.width as usize, info.height as usize);
+    for (i, samples) in samples.chunks(channels).enumerate() {
+      let (color, alpha) = match info.color_type {
+        ColorType::Grayscale => (Color(samples[0], samples[0], samples[0]), 1.0),
+        ColorType::GrayscaleAlpha => (Color(samples[0], samples[0], samples[0]), samples[1]),
+        ColorType::Rgba => (Color(samples[0], samples[1], samples[2]), samples[3]),
+        _ => (Color(samples[0], samples[1], samples[2]), 1.0),
+      };
+      let color = Color(
+        transfer.decode(color.0),
+        transfer.decode(color.1),
+        transfer.decode(color.2),
+      );
+      canvas.pixels[i] = color * alpha;
+      canvas.alpha[i] = alpha;
+    }
+    Ok(canvas)
+  }
+
+  /// Read a canvas from a PNG file on disk, without decoding.
+  pub fn load_png(path: &Path) -> Result<Canvas> {
+    Canvas::from_png(&fs::read(path)?)
   }
 }
@@ -0,0 +1,203 @@
+use super::Canvas;
+use crate::color::Color;
+use std::f64::consts::PI;
+
+/// How `resize` works out the new pixels from the old ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+  /// Take the old pixel nearest the center of each new one.
+  Nearest,
+  /// Blend the nearest old pixels linearly.
+  #[default]
+  Bilinear,
+  /// Windowed sinc with three lobes; sharper than bilinear, but it can ring
+  /// around hard edges.
+  Lanczos3,
+}
+
+impl ResizeFilter {
+  /// How far from its center the filter reaches, in pixels.
+  fn support(self) -> f64 {
+    match self {
+      ResizeFilter::Nearest => 0.5,
+      ResizeFilter::Bilinear => 1.0,
+      ResizeFilter::Lanczos3 => 3.0,
+    }
+  }
+
+  /// The weight of a pixel at the given distance from the center.
+  fn weight(self, x: f64) -> f64 {
+    match self {
+      ResizeFilter::Nearest => 1.0,
+      ResizeFilter::Bilinear => (1.0 - x.abs()).max(0.0),
+      ResizeFilter::Lanczos3 if x.abs() < 3.0 => sinc(x) * sinc(x / 3.0),
+      ResizeFilter::Lanczos3 => 0.0,
+    }
+  }
+
+  /// List the old pixels, and their weights, that make up each new pixel
+  /// along one axis.
+  fn contributions(self, old: usize, new: usize) -> Vec<Vec<(usize, f64)>> {
+    let ratio = old as f64 / new as f64;
+    // Shrinking widens the filter, so every old pixel counts.
+    let scale = ratio.max(1.0);
+    (0..new)
+      .map(|i| {
+        let center = (i as f64 + 0.5) * ratio;
+        if self == ResizeFilter::Nearest {
+          return vec![((center as usize).min(old - 1), 1.0)];
+        }
+        let reach = self.support() * scale;
+        let first = (center - reach).floor().max(0.0) as usize;
+        let last = ((center + reach).ceil() as usize).min(old);
+        let mut weights: Vec<(usize, f64)> = (first..last)
+          .map(|j| (j, self.weight((j as f64 + 0.5 - center) / scale)))
+          .filter(|(_, weight)| *weight != 0.0)
+          .collect();
+        let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+        if total == 0.0 {
+          return vec![((center as usize).min(old - 1), 1.0)];
+        }
+        weights.iter_mut().for_each(|(_, weight)| *weight /= total);
+        weights
+      })
+      .collect()
+  }
+}
+
+fn sinc(x: f64) -> f64 {
+  if x == 0.0 {
+    1.0
+  } else {
+    (PI * x).sin() / (PI * x)
+  }
+}
+
+impl Canvas {
+  /// Copy a rectangle of the canvas, with its top left corner at `(x, y)`;
+  /// the rectangle is clipped to the canvas.
+  pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Canvas {
+    let (x, y) = (x.min(self.width), y.min(self.height));
+    let (width, height) = (width.min(self.width - x), height.min(self.height - y));
+    self.remap(width, height, |cx, cy| (x + cx, y + cy))
+  }
+
+  /// Scale the canvas to the given size with the given filter.
+  ///
+  /// Colors and alpha are filtered together, which is right for the
+  /// premultiplied colors a canvas holds.
+  pub fn resize(&self, width: usize, height: usize, filter: ResizeFilter) -> Canvas {
+    if self.width == 0 || self.height == 0 || width == 0 || height == 0 {
+      return Canvas::new(width, height);
+    }
+    // Filter the rows first, then the columns of the result.
+    let columns = filter.contributions(self.width, width);
+    let mut wide = Canvas::new(width, self.height);
+    for y in 0..self.height {
+      for (x, contributions) in columns.iter().enumerate() {
+        let (color, alpha) = self.weigh(contributions.iter().map(|&(sx, weight)| (sx, y, weight)));
+        wide.set_color_at(x, y, color);
+        wide.set_alpha_at(x, y, alpha);
+      }
+    }
+    let rows = filter.contributions(self.height, height);
+    let mut canvas = Canvas::new(width, height);
+    for (y, contributions) in rows.iter().enumerate() {
+      for x in 0..width {
+        let (color, alpha) = wide.weigh(contributions.iter().map(|&(sy, weight)| (x, sy, weight)));
+        canvas.set_color_at(x, y, color);
+        canvas.set_alpha_at(x, y, alpha);
+      }
+    }
+    canvas
+  }
+
+  /// Mirror the canvas left to right.
+  pub fn flip_horizontal(&self) -> Canvas {
+    self.remap(self.width, self.height, |x, y| (self.width - 1 - x, y))
+  }
+
+  /// Mirror the canvas top to bottom.
+  pub fn flip_vertical(&self) -> Canvas {
+    self.remap(self.width, self.height, |x, y| (x, self.height - 1 - y))
+  }
+
+  /// Turn the canvas a quarter turn clockwise.
+  pub fn rotate_90(&self) -> Canvas {
+    self.remap(self.height, self.width, |x, y| (y, self.height - 1 - x))
+  }
+
+  /// Turn the canvas half a turn.
+  pub fn rotate_180(&self) -> Canvas {
+    self.remap(self.width, self.height, |x, y| {
+      (self.width - 1 - x, self.height - 1 - y)
+    })
+  }
+
+  /// Turn the canvas a quarter turn counterclockwise.
+  pub fn rotate_270(&self) -> Canvas {
+    self.remap(self.height, self.width, |x, y| (self.width - 1 - y, x))
+  }
+
+  /// Copy another canvas onto this one with its top left corner at `(x, y)`,
+  /// replacing the pixels underneath.  Whatever falls outside this canvas is
+  /// dropped.
+  pub fn blit(&mut self, source: &Canvas, x: isize, y: isize) {
+    self.combine(source, x, y, |_, _, color, alpha| (color, alpha));
+  }
+
+  /// Lay another canvas over this one with its top left corner at `(x, y)`,
+  /// letting the pixels underneath show through where it isn't fully
+  /// covered.  Whatever falls outside this canvas is dropped.
+  pub fn composite(&mut self, top: &Canvas, x: isize, y: isize) {
+    self.combine(top, x, y, |under, under_alpha, color, alpha| {
+      (color + under * (1.0 - alpha), alpha + under_alpha * (1.0 - alpha))
+    });
+  }
+
+  /// Build a canvas of the given size, taking each pixel from the position in
+  /// this one that `source` gives.
+  fn remap(&self, width: usize, height: usize, source: impl Fn(usize, usize) -> (usize, usize)) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+      for x in 0..width {
+        let (sx, sy) = source(x, y);
+        canvas.set_color_at(x, y, self.get_color_at(sx, sy));
+        canvas.set_alpha_at(x, y, self.get_alpha_at(sx, sy));
+      }
+    }
+    canvas
+  }
+
+  /// Sum the color and alpha of the given pixels, each times its weight.
+  fn weigh(&self, pixels: impl Iterator<Item = (usize, usize, f64)>) -> (Color, f64) {
+    pixels.fold((Color::default(), 0.0), |(color, alpha), (x, y, weight)| {
+      (
+        color + self.get_color_at(x, y) * weight,
+        alpha + self.get_alpha_at(x, y) * weight,
+      )
+    })
+  }
+
+  /// Merge another canvas into this one at `(x, y)`, working out each pixel
+  /// from the color and alpha underneath and the color and alpha on top.
+  fn combine(&mut self, other: &Canvas, x: isize, y: isize, merge: impl Fn(Color, f64, Color, f64) -> (Color, f64)) {
+    for oy in 0..other.height {
+      for ox in 0..other.width {
+        let (tx, ty) = (x + ox as isize, y + oy as isize);
+        if tx < 0 || ty < 0 || tx as usize >= self.width || ty as usize >= self.height {
+          continue;
+        }
+        let (tx, ty) = (tx as usize, ty as usize);
+        let (color, alpha) = merge(
+          self.get_color_at(tx, ty),
+          self.get_alpha_at(tx, ty),
+          other.get_color_at(ox, oy),
+          other.get_alpha_at(ox, oy),
+        );
+        self.set_color_at(tx, ty, color);
+        self.set_alpha_at(tx, ty, alpha);
+      }
+    }
+  }
+}
@@ -0,0 +1,198 @@
+use super::Canvas;
+use crate::color::Color;
+use crate::error::{Error, Result};
+use crate::output::{OutputOptions, SampleDepth, TransferFunction};
+use std::fs;
+use std::path::Path;
+
+/// No line in a plain PPM file should be longer than this.
+const MAX_LINE_LENGTH: usize = 70;
+
+/// Splits the text of a PPM header into tokens, skipping comments.
+struct Tokens<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+  fn new(bytes: &'a [u8]) -> Self {
+    Tokens { bytes, pos: 0 }
+  }
+
+  fn next_token(&mut self) -> Option<&'a str> {
+    loop {
+      match self.bytes.get(self.pos)? {
+        b'#' => {
+          while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+            self.pos += 1;
+          }
+        },
+        b if b.is_ascii_whitespace() => self.pos += 1,
+        _ => break,
+      }
+    }
+    let start = self.pos;
+    while self
+      .bytes
+      .get(self.pos)
+      .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+    {
+      self.pos += 1;
+    }
+    std::str::from_utf8(&self.bytes[start..self.pos]).ok()
+  }
+
+  fn next_number(&mut self, what: &str) -> std::result::Result<usize, String> {
+    let token = self.next_token().ok_or(format!("Missing PPM {}", what))?;
+    token.parse().map_err(|_| format!("Invalid PPM {}: {}", what, token))
+  }
+}
+
+impl Canvas {
+  /// Convert to a PPM file.
+  pub fn to_ppm(&self) -> String {
+    self.to_ppm_with(&OutputOptions::default())
+  }
+
+  /// Convert to a PPM file, quantizing colors with the given options.
+  ///
+  /// Each row starts on a new line, and lines are wrapped so that none is
+  /// longer than 70 characters.
+  pub fn to_ppm_with(&self, options: &OutputOptions) -> String {
+    let quantizer = self.quantizer(options);
+    let mut ppm = "P3\n".to_string();
+    ppm.push_str(&format!(
+      "{} {}\n{}",
+      self.width,
+      self.height,
+      options.depth.max_value()
+    ));
+    for y in 0..self.height {
+      ppm.push('\n');
+      let mut line_length = 0;
+      for x in 0..self.width {
+        for sample in quantizer.quantize_color(self.get_color_at(x, y)) {
+          let sample = sample.to_string();
+          if line_length > 0 && line_length + 1 + sample.len() > MAX_LINE_LENGTH {
+            ppm.push('\n');
+            line_length = 0;
+          } else if line_length > 0 {
+            ppm.push(' ');
+            line_length += 1;
+          }
+          ppm.push_str(&sample);
+          line_length += sample.len();
+        }
+      }
+    }
+    ppm.push('\n');
+    ppm
+  }
+
+  /// Write the PPM to a file.
+  pub fn save_ppm(&self, path: &Path) -> Result<()> {
+    fs::write(path, self.to_ppm())?;
+    Ok(())
+  }
+
+  /// Convert to a binary (P6) PPM file.
+  pub fn to_p6(&self) -> Vec<u8> {
+    self.to_p6_with(&OutputOptions::default())
+  }
+
+  /// Convert to a binary (P6) PPM file, quantizing colors with the given
+  /// options; 16-bit samples are stored most significant byte first.
+  pub fn to_p6_with(&self, options: &OutputOptions) -> Vec<u8> {
+    let quantizer = self.quantizer(options);
+    let mut ppm = format!("P6\n{} {}\n{}\n", self.width, self.height, options.depth.max_value()).into_bytes();
+    for color in &self.pixels {
+      for sample in quantizer.quantize_color(*color) {
+        match options.depth {
+          SampleDepth::Eight => ppm.push(sample as u8),
+          SampleDepth::Sixteen => ppm.extend_from_slice(&sample.to_be_bytes()),
+        }
+      }
+    }
+    ppm
+  }
+
+  /// Write the binary PPM to a file.
+  pub fn save_p6(&self, path: &Path) -> Result<()> {
+    fs::write(path, self.to_p6())?;
+    Ok(())
+  }
+
+  /// Read a canvas from the contents of a plain (P3) or binary (P6) PPM file.
+  ///
+  /// Samples are scaled by the maximum value, so they end up in [0, 1].
+  pub fn from_ppm(bytes: &[u8]) -> Result<Canvas> {
+    Canvas::from_ppm_with(bytes, TransferFunction::Linear)
+  }
+
+  /// Read a canvas from the contents of a PPM file, decoding the samples with
+  /// the given transfer function to get linear colors.
+  pub fn from_ppm_with(bytes: &[u8], transfer: TransferFunction) -> Result<Canvas> {
+    Canvas::decode_ppm(bytes, transfer).map_err(Error::Format)
+  }
+
+  fn decode_ppm(bytes: &[u8], transfer: TransferFunction) -> std::result::Result<Canvas, String> {
+    let mut tokens = Tokens::new(bytes);
+    let magic = tokens.next_token().ok_or("Missing PPM magic number")?;
+    if magic != "P3" && magic != "P6" {
+      return Err(format!("Unsupported PPM magic number: {}", magic));
+    }
+    let width = tokens.next_number("width")?;
+    let height = tokens.next_number("height")?;
+    let max_value = tokens.next_number("maximum value")?;
+    if max_value == 0 || max_value > 65535 {
+      return Err(format!("Invalid PPM maximum value: {}", max_value));
+    }
+    let count = width
+      .checked_mul(height)
+      .and_then(|pixels| pixels.checked_mul(3))
+      .ok_or("PPM resolution is too large")?;
+    let samples = if magic == "P3" {
+      // Every sample takes at least one digit, so check the resolution against
+      // the data before reading it.
+      if count > bytes.len() - tokens.pos {
+        return Err("PPM data is too short for its resolution".to_string());
+      }
+      (0..count)
+        .map(|_| tokens.next_number("sample"))
+        .collect::<std::result::Result<Vec<usize>, String>>()?
+    } else {
+      // Exactly one whitespace character separates the header from the data.
+      let start = tokens.pos + 1;
+      let size = if max_value < 256 { 1 } else { 2 };
+      let data = count
+        .checked_mul(size)
+        .and_then(|length| bytes.get(start..start.checked_add(length)?))
+        .ok_or("PPM data is too short for its resolution")?;
+      match size {
+        1 => data.iter().map(|b| *b as usize).collect(),
+        _ => data
+          .chunks(2)
+          .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize)
+          .collect(),
+      }
+    };
+    if let Some(sample) = samples.iter().find(|s| **s > max_value) {
+      return Err(format!("PPM sample {} exceeds the maximum value {}", sample, max_value));
+    }
+    let mut canvas = Canvas::new(width, height);
+    let max_value = max_value as f64;
+    for (pixel, rgb) in canvas.pixels.iter_mut().zip(samples.chunks(3)) {
+      *pixel = Color(
+        transfer.decode(rgb[0] as f64 / max_value),
+        transfer.decode(rgb[1] as f64 / max_value),
+        transfer.decode(rgb[2] as f64 / max_value),
+      );
+    }
+    Ok(canvas)
+  }
+
+  /// Read a canvas from a PPM file on disk.
+  pub fn load_ppm(path: &Path) -> Result<Canvas> {
+    Canvas::from_ppm(&fs::read(path)?)
+  }
+}
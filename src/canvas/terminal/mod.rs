@@ -0,0 +1,135 @@
+use super::Canvas;
+use crate::color::Color;
+use crate::output::{OutputOptions, SampleDepth};
+use std::fmt::Write;
+
+/// The character drawn for each pair of pixels: its top half shows the upper
+/// pixel in the foreground color, and the rest shows the lower pixel in the
+/// background color.
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// The channel levels of the 6x6x6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors a terminal can show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TerminalColors {
+  /// 24-bit color, set with `ESC[38;2;r;g;bm`.
+  #[default]
+  TrueColor,
+  /// The xterm 256-color palette, set with `ESC[38;5;nm`.
+  Ansi256,
+}
+
+impl Canvas {
+  /// Draw the canvas as text for a terminal, at most `columns` characters
+  /// wide.
+  pub fn to_ansi(&self, columns: usize, colors: TerminalColors) -> String {
+    self.to_ansi_with(columns, colors, &OutputOptions::default())
+  }
+
+  /// Draw the canvas as text for a terminal, quantizing colors with the given
+  /// options.
+  ///
+  /// Each character shows two pixels, one above the other, so the picture
+  /// keeps its shape in a terminal whose characters are twice as tall as they
+  /// are wide.  Canvases wider than `columns` are scaled down, averaging the
+  /// pixels that fall in each character.  Every line ends by resetting the
+  /// colors.
+  pub fn to_ansi_with(&self, columns: usize, colors: TerminalColors, options: &OutputOptions) -> String {
+    let quantizer = self.quantizer(&OutputOptions {
+      depth: SampleDepth::Eight,
+      ..*options
+    });
+    let preview = self.shrink_to_width(columns.max(1));
+    let mut text = String::new();
+    for y in (0..preview.height).step_by(2) {
+      for x in 0..preview.width {
+        let upper = quantizer.quantize_color(preview.get_color_at(x, y)).map(|c| c as u8);
+        text += &colors.foreground(upper);
+        if y + 1 < preview.height {
+          let lower = quantizer
+            .quantize_color(preview.get_color_at(x, y + 1))
+            .map(|c| c as u8);
+          text += &colors.background(lower);
+        } else {
+          text += "\x1b[49m";
+        }
+        text.push(UPPER_HALF_BLOCK);
+      }
+      text += "\x1b[0m\n";
+    }
+    text
+  }
+
+  /// Scale the canvas down to at most the given width, keeping its aspect
+  /// ratio, by averaging the pixels that fall in each new pixel.
+  fn shrink_to_width(&self, width: usize) -> Canvas {
+    if self.width <= width {
+      return self.clone();
+    }
+    let height = ((self.height * width) as f64 / self.width as f64).round().max(1.0) as usize;
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+      let rows = y * self.height / height..(y + 1) * self.height / height;
+      for x in 0..width {
+        let columns = x * self.width / width..(x + 1) * self.width / width;
+        let count = (rows.len() * columns.len()) as f64;
+        let sum = rows.clone().fold(Color::default(), |sum, sy| {
+          columns.clone().fold(sum, |sum, sx| sum + self.get_color_at(sx, sy))
+        });
+        canvas.set_color_at(x, y, sum / count);
+      }
+    }
+    canvas
+  }
+}
+
+impl TerminalColors {
+  /// The escape sequence setting the foreground color.
+  fn foreground(self, rgb: [u8; 3]) -> String {
+    self.escape(38, rgb)
+  }
+
+  /// The escape sequence setting the background color.
+  fn background(self, rgb: [u8; 3]) -> String {
+    self.escape(48, rgb)
+  }
+
+  fn escape(self, code: u8, [r, g, b]: [u8; 3]) -> String {
+    let mut escape = String::new();
+    match self {
+      TerminalColors::TrueColor => write!(escape, "\x1b[{};2;{};{};{}m", code, r, g, b),
+      TerminalColors::Ansi256 => write!(escape, "\x1b[{};5;{}m", code, ansi256([r, g, b])),
+    }
+    .unwrap();
+    escape
+  }
+}
+
+/// Find the closest color in the xterm 256-color palette, from the 6x6x6 cube
+/// or the gray ramp; the first 16 colors vary between terminals, so they're
+/// never used.
+pub fn ansi256(rgb: [u8; 3]) -> u8 {
+  let distance = |a: [u8; 3]| {
+    a.iter()
+      .zip(rgb.iter())
+      .map(|(&a, &b)| (a as i32 - b as i32).pow(2))
+      .sum::<i32>()
+  };
+  let nearest_level = |c: u8| {
+    (0..6)
+      .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs())
+      .unwrap()
+  };
+  let [r, g, b] = rgb.map(nearest_level);
+  let cube = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+  let average = rgb.iter().map(|&c| c as i32).sum::<i32>() / 3;
+  let gray_step = ((average - 8 + 5) / 10).clamp(0, 23);
+  let gray = (8 + 10 * gray_step) as u8;
+  if distance([gray; 3]) < distance(cube) {
+    232 + gray_step as u8
+  } else {
+    16 + 36 * r as u8 + 6 * g as u8 + b as u8
+  }
+}
@@ -5,6 +5,7 @@ use std::ops::Sub;
 
 /// A three-dimensional color.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color(pub f64, pub f64, pub f64);
 
 impl Color {
@@ -15,6 +16,11 @@ impl Color {
   pub fn abs(self) -> Self {
     Color(self.0.abs(), self.1.abs(), self.2.abs())
   }
+
+  /// The perceived brightness of the color, using the Rec. 709 weights.
+  pub fn luminance(self) -> f64 {
+    0.2126 * self.0 + 0.7152 * self.1 + 0.0722 * self.2
+  }
 }
 
 impl Add for Color {
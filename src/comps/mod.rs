@@ -15,6 +15,8 @@ pub struct Comps {
   pub normalv: Vector,
   pub inside: bool,
   pub over_point: Point,
+  /// The time at which the ray was cast.
+  pub time: f64,
 }
 
 impl Comps {
@@ -36,6 +38,7 @@ impl Comps {
       normalv,
       inside,
       over_point,
+      time: 0.0,
     }
   }
 
@@ -47,14 +50,17 @@ impl Comps {
     let inside = normalv.dot(eyev) < 0.0;
     normalv = if inside { -normalv } else { normalv };
     let over_point = point + normalv * 0.0001;
-    Comps::new(
-      intersection.t,
-      intersection.object.clone(),
-      point,
-      eyev,
-      normalv,
-      inside,
-      over_point,
-    )
+    Comps {
+      time: ray.time,
+      ..Comps::new(
+        intersection.t,
+        intersection.object.clone(),
+        point,
+        eyev,
+        normalv,
+        inside,
+        over_point,
+      )
+    }
   }
 }
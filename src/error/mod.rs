@@ -0,0 +1,68 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while reading or writing images and scenes.
+#[derive(Debug)]
+pub enum Error {
+  /// The file couldn't be read or written.
+  Io(io::Error),
+  /// The PNG encoder failed.
+  PngEncoding(png::EncodingError),
+  /// The PNG decoder failed.
+  PngDecoding(png::DecodingError),
+  /// The contents of a file couldn't be understood.
+  Format(String),
+  /// A scene file couldn't be parsed; `line` is 1-based.
+  Parse { line: usize, message: String },
+}
+
+/// A result whose error is a `sunhouse::Error`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Error::Io(error) => write!(f, "I/O error: {}", error),
+      Error::PngEncoding(error) => write!(f, "PNG encoding error: {}", error),
+      Error::PngDecoding(error) => write!(f, "PNG decoding error: {}", error),
+      Error::Format(message) => write!(f, "Format error: {}", message),
+      Error::Parse { line, message } => write!(f, "Parse error on line {}: {}", line, message),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Error::Io(error) => Some(error),
+      Error::PngEncoding(error) => Some(error),
+      Error::PngDecoding(error) => Some(error),
+      Error::Format(_) | Error::Parse { .. } => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(error: io::Error) -> Self {
+    Error::Io(error)
+  }
+}
+
+impl From<png::EncodingError> for Error {
+  fn from(error: png::EncodingError) -> Self {
+    // The encoder wraps I/O failures; keep them distinguishable.
+    match error {
+      png::EncodingError::IoError(error) => Error::Io(error),
+      error => Error::PngEncoding(error),
+    }
+  }
+}
+
+impl From<png::DecodingError> for Error {
+  fn from(error: png::DecodingError) -> Self {
+    match error {
+      png::DecodingError::IoError(error) => Error::Io(error),
+      error => Error::PngDecoding(error),
+    }
+  }
+}
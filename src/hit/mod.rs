@@ -11,7 +11,7 @@ impl Hit for Vec<Intersection> {
     self
       .iter()
       .filter(|i| i.t >= 0.0)
-      .min_by(|a, b| a.t.partial_cmp(&b.t).unwrap())
+      .min_by(|a, b| a.t.total_cmp(&b.t))
       .cloned()
   }
 }
@@ -1,21 +1,29 @@
 #![allow(unused_macros)]
 #![allow(clippy::needless_range_loop)]
 
+pub mod aov;
 pub mod camera;
 pub mod canvas;
 pub mod color;
 pub mod comps;
+pub mod error;
+pub use error::{Error, Result};
 pub mod hit;
 pub mod intersection;
 pub mod material;
 pub mod matrix;
+pub mod motion;
 pub mod object;
+pub mod output;
 pub mod plane;
 pub mod point;
 pub mod point_light;
 pub mod ray;
+pub mod sampling;
+pub mod scene;
 pub mod sphere;
 pub mod test_shape;
+pub mod trace;
 pub mod tuple;
 pub mod vector;
 pub mod world;
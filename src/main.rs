@@ -1,3 +1,310 @@
-fn main() {
-  println!("Hello, world!");
+use clap::{Parser, ValueEnum};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use sunhouse::camera::CameraBuilder;
+use sunhouse::canvas::exr::ExrOptions;
+use sunhouse::canvas::terminal::TerminalColors;
+use sunhouse::canvas::Canvas;
+use sunhouse::matrix::Matrix;
+use sunhouse::output::OutputOptions;
+use sunhouse::point::Point;
+use sunhouse::scene::Scene;
+use sunhouse::vector::Vector;
+use sunhouse::world::Background;
+
+/// Render a scene file to an image.
+#[derive(Debug, Parser)]
+#[command(name = "sunhouse", version)]
+struct Args {
+  /// The YAML scene file to render.
+  scene: PathBuf,
+
+  /// Where to write the image.
+  #[arg(short, long, default_value = "out.png")]
+  output: PathBuf,
+
+  /// The image format; worked out from the output file's extension if not
+  /// given.
+  #[arg(short, long, value_enum)]
+  format: Option<Format>,
+
+  /// The width of the image in pixels; if only one of width and height is
+  /// given, the other keeps the scene's aspect ratio.
+  #[arg(long)]
+  width: Option<usize>,
+
+  /// The height of the image in pixels.
+  #[arg(long)]
+  height: Option<usize>,
+
+  /// The number of samples per pixel taken for depth of field and motion
+  /// blur; only used when the aperture or the shutter is open.
+  #[arg(short, long)]
+  samples: Option<usize>,
+
+  /// The radius of the camera's lens; zero gives a pinhole camera, with
+  /// everything in focus.
+  #[arg(long)]
+  aperture: Option<f64>,
+
+  /// The distance from the camera at which objects are in perfect focus.
+  #[arg(long)]
+  focal_distance: Option<f64>,
+
+  /// The time at which the shutter opens.
+  #[arg(long, value_name = "TIME")]
+  shutter_open: Option<f64>,
+
+  /// The time at which the shutter closes; moving shapes are blurred over
+  /// the part of their motion that happens while it is open.
+  #[arg(long, value_name = "TIME")]
+  shutter_close: Option<f64>,
+
+  /// The number of threads to render on; defaults to one per CPU.
+  #[arg(short = 'j', long)]
+  threads: Option<usize>,
+
+  /// Move the camera to `x,y,z`; unless `--to` is given too, it keeps
+  /// looking in the same direction.
+  #[arg(long, value_name = "X,Y,Z", value_parser = parse_triple)]
+  from: Option<(f64, f64, f64)>,
+
+  /// Point the camera at `x,y,z`.
+  #[arg(long, value_name = "X,Y,Z", value_parser = parse_triple)]
+  to: Option<(f64, f64, f64)>,
+
+  /// Use `x,y,z` as the camera's up direction.
+  #[arg(long, value_name = "X,Y,Z", value_parser = parse_triple)]
+  up: Option<(f64, f64, f64)>,
+
+  /// The camera's field of view, in radians.
+  #[arg(long)]
+  fov: Option<f64>,
+
+  /// Also draw the image in the terminal.
+  #[arg(long)]
+  preview: bool,
+
+  /// The width of the preview in characters; defaults to `$COLUMNS`, or 80.
+  #[arg(long, value_name = "COLUMNS")]
+  preview_width: Option<usize>,
+
+  /// The colors the preview uses; defaults to truecolor if `$COLORTERM` says
+  /// the terminal supports it, and 256 otherwise.
+  #[arg(long, value_enum)]
+  preview_colors: Option<PreviewColors>,
+}
+
+/// The colors a terminal preview can use.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum PreviewColors {
+  /// 24-bit color.
+  #[value(name = "truecolor")]
+  TrueColor,
+  /// The xterm 256-color palette.
+  #[value(name = "256")]
+  Ansi256,
+}
+
+impl PreviewColors {
+  /// Guess what the terminal supports from `$COLORTERM`.
+  fn from_env() -> Self {
+    match env::var("COLORTERM").as_deref() {
+      Ok("truecolor" | "24bit") => PreviewColors::TrueColor,
+      _ => PreviewColors::Ansi256,
+    }
+  }
+}
+
+impl From<PreviewColors> for TerminalColors {
+  fn from(colors: PreviewColors) -> Self {
+    match colors {
+      PreviewColors::TrueColor => TerminalColors::TrueColor,
+      PreviewColors::Ansi256 => TerminalColors::Ansi256,
+    }
+  }
+}
+
+/// The image formats the renderer can write.
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum Format {
+  /// 8-bit PNG.
+  Png,
+  /// Plain-text PPM (P3).
+  Ppm,
+  /// Binary PPM (P6).
+  P6,
+  /// Radiance RGBE.
+  Hdr,
+  /// OpenEXR with half-float channels.
+  Exr,
+}
+
+impl Format {
+  /// Guess the format from a file's extension.
+  fn from_path(path: &Path) -> Option<Self> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+      "png" => Some(Format::Png),
+      "ppm" => Some(Format::Ppm),
+      "hdr" => Some(Format::Hdr),
+      "exr" => Some(Format::Exr),
+      _ => None,
+    }
+  }
+
+  /// Write the image, with an alpha channel if the format has one and the
+  /// background is transparent.
+  fn save(self, canvas: &Canvas, path: &Path, transparent: bool) -> sunhouse::Result<()> {
+    match self {
+      Format::Png => canvas.to_png_with(path, &OutputOptions::default().with_alpha(transparent)),
+      Format::Ppm => canvas.save_ppm(path),
+      Format::P6 => canvas.save_p6(path),
+      Format::Hdr => canvas.save_hdr(path),
+      Format::Exr => canvas.save_exr(
+        path,
+        &ExrOptions {
+          alpha: transparent,
+          ..ExrOptions::default()
+        },
+      ),
+    }
+  }
+}
+
+/// Parse `x,y,z` into three numbers.
+fn parse_triple(text: &str) -> Result<(f64, f64, f64), String> {
+  let numbers = text
+    .split(',')
+    .map(|part| part.trim().parse::<f64>())
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|error| error.to_string())?;
+  match numbers[..] {
+    [x, y, z] => Ok((x, y, z)),
+    _ => Err(format!(
+      "expected three numbers separated by commas, found {}",
+      numbers.len()
+    )),
+  }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+  let format = match args.format.or_else(|| Format::from_path(&args.output)) {
+    Some(format) => format,
+    None => {
+      return Err(format!(
+        "can't tell the image format from `{}`; use --format",
+        args.output.display()
+      ))
+    },
+  };
+  if args.width == Some(0) || args.height == Some(0) {
+    return Err("the image must be at least one pixel wide and high".to_string());
+  }
+  if args.samples == Some(0) {
+    return Err("at least one sample per pixel is needed".to_string());
+  }
+  if args
+    .aperture
+    .is_some_and(|aperture| aperture < 0.0 || aperture.is_nan())
+  {
+    return Err("the aperture can't be negative".to_string());
+  }
+  if args
+    .focal_distance
+    .is_some_and(|distance| distance <= 0.0 || distance.is_nan())
+  {
+    return Err("the focal distance must be greater than zero".to_string());
+  }
+  if args
+    .fov
+    .is_some_and(|fov| fov <= 0.0 || fov >= std::f64::consts::PI || fov.is_nan())
+  {
+    return Err("the field of view must be between 0 and π radians".to_string());
+  }
+  if args.threads == Some(0) {
+    return Err("at least one thread is needed".to_string());
+  }
+  if args.preview_width == Some(0) {
+    return Err("the preview must be at least one column wide".to_string());
+  }
+
+  let scene = Scene::load(&args.scene).map_err(|error| format!("{}: {}", args.scene.display(), error))?;
+  let mut camera = scene.camera();
+  let aspect = camera.hsize as f64 / camera.vsize as f64;
+  let (hsize, vsize) = match (args.width, args.height) {
+    (Some(width), Some(height)) => (width, height),
+    (Some(width), None) => (width, ((width as f64 / aspect).round() as usize).max(1)),
+    (None, Some(height)) => (((height as f64 * aspect).round() as usize).max(1), height),
+    (None, None) => (camera.hsize, camera.vsize),
+  };
+  camera = camera.with_resolution(hsize, vsize);
+  if args.from.is_some() || args.to.is_some() || args.up.is_some() {
+    let view = CameraBuilder::from_camera(&camera);
+    let from = args.from.map_or(view.position, |(x, y, z)| Point(x, y, z));
+    let to = args
+      .to
+      .map_or(from + (view.target - view.position), |(x, y, z)| Point(x, y, z));
+    let up = args.up.map_or(view.up, |(x, y, z)| Vector(x, y, z));
+    if (to - from).magnitude() < 0.0001 {
+      return Err("the camera can't look at the point it is at; use a different --from or --to".to_string());
+    }
+    let sideways = (to - from).normalize().cross(up.normalize()).magnitude();
+    if sideways < 0.0001 || sideways.is_nan() {
+      return Err("the camera's up direction can't be along the way it looks; use --up".to_string());
+    }
+    camera.transform = Matrix::view_transform(from, to, up);
+  }
+  if let Some(fov) = args.fov {
+    camera = camera.with_field_of_view(fov);
+  }
+  if let Some(samples) = args.samples {
+    camera.samples = samples;
+  }
+  if let Some(aperture) = args.aperture {
+    camera.aperture = aperture;
+  }
+  if let Some(focal_distance) = args.focal_distance {
+    camera.focal_distance = focal_distance;
+  }
+  if let Some(shutter_open) = args.shutter_open {
+    camera.shutter_open = shutter_open;
+  }
+  if let Some(shutter_close) = args.shutter_close {
+    camera.shutter_close = shutter_close;
+  }
+  if camera.aperture > 0.0 && camera.samples < 2 {
+    return Err("an open aperture needs more than one sample per pixel; use --samples".to_string());
+  }
+
+  let threads = args
+    .threads
+    .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+  let canvas = camera.render_parallel(&scene.world, threads);
+  let transparent = scene.world.background == Background::Transparent;
+  format
+    .save(&canvas, &args.output, transparent)
+    .map_err(|error| format!("{}: {}", args.output.display(), error))?;
+  if args.preview {
+    let columns = args
+      .preview_width
+      .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+      .unwrap_or(80);
+    let colors = args.preview_colors.unwrap_or_else(PreviewColors::from_env);
+    print!("{}", canvas.to_ansi(columns, colors.into()));
+  }
+  Ok(())
+}
+
+fn main() -> ExitCode {
+  let args = Args::parse();
+  match run(&args) {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(message) => {
+      eprintln!("sunhouse: {}", message);
+      ExitCode::FAILURE
+    },
+  }
 }
@@ -5,6 +5,7 @@ use crate::vector::Vector;
 
 /// Encapsulates the surface color and attributes from the reflection model.
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Material {
   pub color: Color,
   pub ambient: f64,
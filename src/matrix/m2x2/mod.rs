@@ -5,6 +5,7 @@ use std::ops::Mul;
 
 /// A 2x2 Matrix.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix2x2(pub [[f64; 2]; 2]);
 
 impl Matrix2x2 {
@@ -32,7 +33,7 @@ impl Matrix2x2 {
 
   pub fn cofactor(&self, row: usize, col: usize) -> f64 {
     let minor = self.minor(row, col);
-    if (row + col) % 2 == 0 {
+    if (row + col).is_multiple_of(2) {
       minor
     } else {
       -minor
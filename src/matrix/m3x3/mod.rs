@@ -6,6 +6,7 @@ use std::ops::Mul;
 
 /// A 3x3 Matrix.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix3x3(pub [[f64; 3]; 3]);
 
 impl Matrix3x3 {
@@ -49,7 +50,7 @@ impl Matrix3x3 {
 
   pub fn cofactor(&self, row: usize, col: usize) -> f64 {
     let minor = self.minor(row, col);
-    if (row + col) % 2 == 0 {
+    if (row + col).is_multiple_of(2) {
       minor
     } else {
       -minor
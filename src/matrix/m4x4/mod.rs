@@ -6,6 +6,7 @@ use std::ops::Mul;
 
 /// A 4x4 Matrix.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Matrix4x4(pub [[f64; 4]; 4]);
 
 impl Matrix4x4 {
@@ -61,7 +62,7 @@ impl Matrix4x4 {
 
   pub fn cofactor(&self, row: usize, col: usize) -> f64 {
     let minor = self.minor(row, col);
-    if (row + col) % 2 == 0 {
+    if (row + col).is_multiple_of(2) {
       minor
     } else {
       -minor
@@ -161,6 +162,171 @@ impl Matrix4x4 {
     }
     result
   }
+
+  /// Split an affine transform into a translation, a rotation, and whatever
+  /// scaling and shearing remain.
+  pub fn decompose(&self) -> Decomposition {
+    let translation = Vector(self.0[0][3], self.0[1][3], self.0[2][3]);
+    let mut linear = Matrix4x4::identity();
+    for i in 0..3 {
+      for j in 0..3 {
+        linear.0[i][j] = self.0[i][j];
+      }
+    }
+    // Polar decomposition: averaging with the inverse transpose converges on
+    // the nearest orthogonal matrix.
+    let mut rotation = linear;
+    for _ in 0..100 {
+      let inverse_transpose = rotation.inverse().transpose();
+      let mut change: f64 = 0.0;
+      for i in 0..3 {
+        for j in 0..3 {
+          let next = 0.5 * (rotation.0[i][j] + inverse_transpose.0[i][j]);
+          change = change.max((next - rotation.0[i][j]).abs());
+          rotation.0[i][j] = next;
+        }
+      }
+      if change < 1e-12 {
+        break;
+      }
+    }
+    // A reflection can't be expressed as a rotation, so push it into the scale.
+    if rotation.determinant() < 0.0 {
+      for i in 0..3 {
+        for j in 0..3 {
+          rotation.0[i][j] = -rotation.0[i][j];
+        }
+      }
+    }
+    Decomposition {
+      translation,
+      rotation: rotation.to_quaternion(),
+      stretch: rotation.transpose() * linear,
+    }
+  }
+
+  /// Interpolate between this transform (at `t = 0`) and another (at `t = 1`),
+  /// blending translation and scale linearly and rotation spherically.
+  ///
+  /// Both transforms are decomposed on every call; to interpolate between the
+  /// same pair many times, decompose them once and use `Decomposition::blend`.
+  pub fn interpolate(&self, other: &Matrix4x4, t: f64) -> Self {
+    self.decompose().blend(&other.decompose(), t)
+  }
+
+  /// Convert the rotation in the upper 3x3 block to a unit quaternion.
+  fn to_quaternion(self) -> [f64; 4] {
+    let m = self.0;
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    if trace > 0.0 {
+      let s = (trace + 1.0).sqrt() * 2.0;
+      [
+        0.25 * s,
+        (m[2][1] - m[1][2]) / s,
+        (m[0][2] - m[2][0]) / s,
+        (m[1][0] - m[0][1]) / s,
+      ]
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+      let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+      [
+        (m[2][1] - m[1][2]) / s,
+        0.25 * s,
+        (m[0][1] + m[1][0]) / s,
+        (m[0][2] + m[2][0]) / s,
+      ]
+    } else if m[1][1] > m[2][2] {
+      let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+      [
+        (m[0][2] - m[2][0]) / s,
+        (m[0][1] + m[1][0]) / s,
+        0.25 * s,
+        (m[1][2] + m[2][1]) / s,
+      ]
+    } else {
+      let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+      [
+        (m[1][0] - m[0][1]) / s,
+        (m[0][2] + m[2][0]) / s,
+        (m[1][2] + m[2][1]) / s,
+        0.25 * s,
+      ]
+    }
+  }
+
+  /// Build a rotation matrix from a unit quaternion.
+  fn from_quaternion(q: [f64; 4]) -> Self {
+    let [w, x, y, z] = q;
+    Matrix4x4([
+      [
+        1.0 - 2.0 * (y * y + z * z),
+        2.0 * (x * y - w * z),
+        2.0 * (x * z + w * y),
+        0.0,
+      ],
+      [
+        2.0 * (x * y + w * z),
+        1.0 - 2.0 * (x * x + z * z),
+        2.0 * (y * z - w * x),
+        0.0,
+      ],
+      [
+        2.0 * (x * z - w * y),
+        2.0 * (y * z + w * x),
+        1.0 - 2.0 * (x * x + y * y),
+        0.0,
+      ],
+      [0.0, 0.0, 0.0, 1.0],
+    ])
+  }
+}
+
+/// An affine transform split into the parts that interpolate well.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decomposition {
+  pub translation: Vector,
+  /// The rotation, as a unit quaternion `[w, x, y, z]`.
+  pub rotation: [f64; 4],
+  /// The scaling and shearing left after the rotation is taken out.
+  pub stretch: Matrix4x4,
+}
+
+impl Decomposition {
+  /// Blend this decomposition (at `t = 0`) with another (at `t = 1`) and put
+  /// the parts back together: translation and stretch linearly, rotation
+  /// spherically.
+  pub fn blend(&self, other: &Decomposition, t: f64) -> Matrix4x4 {
+    let translation = self.translation + (other.translation - self.translation) * t;
+    let mut stretch = Matrix4x4::identity();
+    for i in 0..3 {
+      for j in 0..3 {
+        stretch.0[i][j] = self.stretch.0[i][j] + (other.stretch.0[i][j] - self.stretch.0[i][j]) * t;
+      }
+    }
+    let rotation = Matrix4x4::from_quaternion(slerp(self.rotation, other.rotation, t));
+    Matrix4x4::translation(translation.0, translation.1, translation.2) * rotation * stretch
+  }
+}
+
+/// Spherically interpolate between two unit quaternions along the shorter arc.
+fn slerp(q0: [f64; 4], q1: [f64; 4], t: f64) -> [f64; 4] {
+  let mut dot = q0.iter().zip(q1.iter()).map(|(a, b)| a * b).sum::<f64>();
+  let q1 = if dot < 0.0 {
+    dot = -dot;
+    q1.map(|c| -c)
+  } else {
+    q1
+  };
+  let (w0, w1) = if dot > 0.9995 {
+    (1.0 - t, t)
+  } else {
+    let theta = dot.acos();
+    let sin_theta = theta.sin();
+    (((1.0 - t) * theta).sin() / sin_theta, (t * theta).sin() / sin_theta)
+  };
+  let q = [0, 1, 2, 3].map(|i| w0 * q0[i] + w1 * q1[i]);
+  let length = q.iter().map(|c| c * c).sum::<f64>().sqrt();
+  q.map(|c| c / length)
 }
 
 impl Mul for Matrix4x4 {
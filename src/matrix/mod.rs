@@ -8,10 +8,11 @@ pub use m2x2::Matrix2x2;
 pub mod m3x3;
 pub use m3x3::Matrix3x3;
 pub mod m4x4;
-pub use m4x4::Matrix4x4;
+pub use m4x4::{Decomposition, Matrix4x4};
 
 /// A Matrix Enum.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Matrix {
   /// A 2x2 Matrix.
   Matrix2x2(Matrix2x2),
@@ -138,6 +139,22 @@ impl Matrix {
     Matrix::Matrix4x4(Matrix4x4::identity())
   }
 
+  /// Interpolate between this transform (at `t = 0`) and another (at `t = 1`).
+  pub fn interpolate(&self, other: &Matrix, t: f64) -> Self {
+    match (self, other) {
+      (Matrix::Matrix4x4(m1), Matrix::Matrix4x4(m2)) => Matrix::Matrix4x4(m1.interpolate(m2, t)),
+      _ => panic!("Only 4x4 transforms can be interpolated!"),
+    }
+  }
+
+  /// Decompose a transform into the parts that interpolate well.
+  pub fn decompose(&self) -> Decomposition {
+    match self {
+      Matrix::Matrix4x4(m) => m.decompose(),
+      _ => panic!("Only 4x4 transforms can be decomposed!"),
+    }
+  }
+
   pub fn from_rows(rows: Vec<Vec<f64>>) -> Self {
     match rows.len() {
       2 => Matrix::Matrix2x2(Matrix2x2::from_rows(rows)),
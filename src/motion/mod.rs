@@ -0,0 +1,121 @@
+use crate::matrix::{Decomposition, Matrix};
+
+/// How a shape moves from its transform to an end transform, between a start
+/// time and an end time.
+///
+/// Both transforms are decomposed once, when the motion is made, so finding
+/// the transform partway through only blends the parts.  Motions are equal
+/// when they move between the same transforms at the same times, and only
+/// those are serialized.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(from = "MotionParts", into = "MotionParts")
+)]
+pub struct Motion {
+  start_transform: Matrix,
+  end_transform: Matrix,
+  start_time: f64,
+  end_time: f64,
+  start: Decomposition,
+  end: Decomposition,
+}
+
+impl Motion {
+  /// Create a motion from one transform to another, from time 0.0 to 1.0.
+  pub fn new(start_transform: Matrix, end_transform: Matrix) -> Self {
+    Motion {
+      start_transform,
+      end_transform,
+      start_time: 0.0,
+      end_time: 1.0,
+      start: start_transform.decompose(),
+      end: end_transform.decompose(),
+    }
+  }
+
+  /// Get the transform the motion starts from.
+  pub fn start_transform(&self) -> Matrix {
+    self.start_transform
+  }
+
+  /// Get the transform the motion ends at.
+  pub fn end_transform(&self) -> Matrix {
+    self.end_transform
+  }
+
+  /// Return a copy of the motion that happens between the given times.
+  pub fn with_times(&self, start_time: f64, end_time: f64) -> Self {
+    Motion {
+      start_time,
+      end_time,
+      ..*self
+    }
+  }
+
+  /// Get the time the motion starts at.
+  pub fn start_time(&self) -> f64 {
+    self.start_time
+  }
+
+  /// Get the time the motion ends at.
+  pub fn end_time(&self) -> f64 {
+    self.end_time
+  }
+
+  /// Compute how far through the motion the given time is, from 0.0 up to
+  /// the start time to 1.0 from the end time on.
+  pub fn fraction_at(&self, time: f64) -> f64 {
+    if self.end_time > self.start_time {
+      ((time - self.start_time) / (self.end_time - self.start_time)).clamp(0.0, 1.0)
+    } else if time < self.start_time {
+      0.0
+    } else {
+      1.0
+    }
+  }
+
+  /// Compute the transform at the given time.
+  pub fn transform_at(&self, time: f64) -> Matrix {
+    Matrix::Matrix4x4(self.start.blend(&self.end, self.fraction_at(time)))
+  }
+}
+
+impl PartialEq for Motion {
+  fn eq(&self, other: &Self) -> bool {
+    self.start_transform == other.start_transform
+      && self.end_transform == other.end_transform
+      && self.start_time == other.start_time
+      && self.end_time == other.end_time
+  }
+}
+
+/// The serialized form of a motion.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MotionParts {
+  start_transform: Matrix,
+  end_transform: Matrix,
+  start_time: f64,
+  end_time: f64,
+}
+
+#[cfg(feature = "serde")]
+impl From<MotionParts> for Motion {
+  fn from(parts: MotionParts) -> Self {
+    Motion::new(parts.start_transform, parts.end_transform).with_times(parts.start_time, parts.end_time)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl From<Motion> for MotionParts {
+  fn from(motion: Motion) -> Self {
+    MotionParts {
+      start_transform: motion.start_transform,
+      end_transform: motion.end_transform,
+      start_time: motion.start_time,
+      end_time: motion.end_time,
+    }
+  }
+}
@@ -7,11 +7,11 @@ use crate::ray::Ray;
 use crate::sphere::Sphere;
 use crate::test_shape::TestShape;
 use crate::vector::Vector;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// An enum for objects that can be intersected.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Object {
   Plane(Plane),
   Sphere(Sphere),
@@ -52,6 +52,33 @@ impl Object {
     }
   }
 
+  /// Return a clone of the object that moves to the given transform between
+  /// times 0.0 and 1.0.
+  pub fn with_motion(&self, end_transform: Matrix) -> Self {
+    self.with_motion_over(end_transform, 0.0, 1.0)
+  }
+
+  /// Return a clone of the object that moves to the given transform between
+  /// the given times.
+  pub fn with_motion_over(&self, end_transform: Matrix, start_time: f64, end_time: f64) -> Self {
+    match self {
+      Object::Plane(plane) => Object::Plane(plane.with_motion_over(end_transform, start_time, end_time)),
+      Object::Sphere(sphere) => Object::Sphere(sphere.with_motion_over(end_transform, start_time, end_time)),
+      Object::TestShape(test_shape) => {
+        Object::TestShape(test_shape.with_motion_over(end_transform, start_time, end_time))
+      },
+    }
+  }
+
+  /// Retrieve the transform of the object at the given time.
+  pub fn transform_at(&self, time: f64) -> Matrix {
+    match self {
+      Object::Plane(plane) => plane.transform_at(time),
+      Object::Sphere(sphere) => sphere.transform_at(time),
+      Object::TestShape(test_shape) => test_shape.transform_at(time),
+    }
+  }
+
   /// Retrieve the transform of the object.
   pub fn transform(&self) -> Matrix {
     match self {
@@ -88,8 +115,27 @@ impl Object {
     }
   }
 
+  /// Return a clone of the object that belongs to the given parent.
+  pub fn with_parent(&self, parent: Object) -> Self {
+    let parent = Some(Arc::new(parent));
+    match self {
+      Object::Plane(plane) => Object::Plane(Plane {
+        parent,
+        ..plane.clone()
+      }),
+      Object::Sphere(sphere) => Object::Sphere(Sphere {
+        parent,
+        ..sphere.clone()
+      }),
+      Object::TestShape(test_shape) => Object::TestShape(TestShape {
+        parent,
+        ..test_shape.clone()
+      }),
+    }
+  }
+
   /// Provide access to the parent of the object, if any.
-  pub fn parent(&self) -> Option<Rc<RefCell<Object>>> {
+  pub fn parent(&self) -> Option<Arc<Object>> {
     match self {
       Object::Plane(plane) => plane.parent.clone(),
       Object::Sphere(sphere) => sphere.parent.clone(),
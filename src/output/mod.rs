@@ -0,0 +1,218 @@
+use crate::color::Color;
+
+/// The curve applied to linear values before they are quantized.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TransferFunction {
+  /// Write values unchanged.
+  #[default]
+  Linear,
+  /// The piecewise sRGB curve expected by most displays and image viewers.
+  Srgb,
+  /// A pure power curve with the given exponent, e.g. 2.2.
+  Gamma(f64),
+}
+
+impl TransferFunction {
+  /// Encode a linear value in [0, 1].
+  pub fn encode(self, value: f64) -> f64 {
+    match self {
+      TransferFunction::Linear => value,
+      TransferFunction::Srgb => {
+        if value <= 0.0031308 {
+          value * 12.92
+        } else {
+          1.055 * value.powf(1.0 / 2.4) - 0.055
+        }
+      },
+      TransferFunction::Gamma(gamma) => value.powf(1.0 / gamma),
+    }
+  }
+
+  /// Decode an encoded value in [0, 1] back to linear.
+  pub fn decode(self, value: f64) -> f64 {
+    match self {
+      TransferFunction::Linear => value,
+      TransferFunction::Srgb => {
+        if value <= 0.04045 {
+          value / 12.92
+        } else {
+          ((value + 0.055) / 1.055).powf(2.4)
+        }
+      },
+      TransferFunction::Gamma(gamma) => value.powf(gamma),
+    }
+  }
+}
+
+/// How high dynamic range colors are compressed into [0, 1].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToneMap {
+  /// Leave colors alone, so anything brighter than 1.0 is clipped.
+  #[default]
+  Clamp,
+  /// Reinhard's operator, which maps luminance L to L / (1 + L).
+  Reinhard,
+  /// Reinhard's operator extended so that luminance at the white point maps
+  /// exactly to 1.0.
+  ReinhardExtended { white: f64 },
+  /// Narkowicz's fit of the ACES filmic curve.
+  Aces,
+}
+
+impl ToneMap {
+  /// Map a color into [0, 1].
+  pub fn map(self, color: Color) -> Color {
+    match self {
+      ToneMap::Clamp => color,
+      ToneMap::Reinhard => scale_luminance(color, |l| l / (1.0 + l)),
+      ToneMap::ReinhardExtended { white } => scale_luminance(color, |l| l * (1.0 + l / (white * white)) / (1.0 + l)),
+      ToneMap::Aces => Color(aces(color.0), aces(color.1), aces(color.2)),
+    }
+  }
+}
+
+/// Rescale a color so that its luminance becomes `f(luminance)`.
+fn scale_luminance(color: Color, f: impl Fn(f64) -> f64) -> Color {
+  let luminance = color.luminance();
+  if luminance <= 0.0 {
+    return Color::default();
+  }
+  color * (f(luminance) / luminance)
+}
+
+fn aces(x: f64) -> f64 {
+  let x = x.max(0.0);
+  (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// How much to brighten or darken colors before tone mapping.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Exposure {
+  /// Scale by two to the power of the given number of stops.
+  Stops(f64),
+  /// Scale so that the log-average luminance of the image lands on the given
+  /// key value; 0.18 is middle gray.
+  Auto { key: f64 },
+}
+
+impl Exposure {
+  /// The factor to multiply the colors of an image with the given log-average
+  /// luminance by; only automatic exposure depends on the image.
+  pub fn multiplier(self, log_average_luminance: f64) -> f64 {
+    match self {
+      Exposure::Stops(stops) => 2f64.powf(stops),
+      Exposure::Auto { key } if log_average_luminance > 0.0 => key / log_average_luminance,
+      Exposure::Auto { .. } => 1.0,
+    }
+  }
+}
+
+impl Default for Exposure {
+  fn default() -> Self {
+    Exposure::Stops(0.0)
+  }
+}
+
+/// The number of bits written for each channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SampleDepth {
+  #[default]
+  Eight,
+  Sixteen,
+}
+
+impl SampleDepth {
+  /// The largest value a channel can hold.
+  pub fn max_value(self) -> u16 {
+    match self {
+      SampleDepth::Eight => u8::MAX as u16,
+      SampleDepth::Sixteen => u16::MAX,
+    }
+  }
+}
+
+/// How colors are turned into integer samples by every exporter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OutputOptions {
+  pub exposure: Exposure,
+  pub tone_map: ToneMap,
+  pub transfer: TransferFunction,
+  pub depth: SampleDepth,
+  /// Whether exporters that support it should write an alpha channel.
+  pub alpha: bool,
+}
+
+impl OutputOptions {
+  /// Create new output options.
+  pub fn new(transfer: TransferFunction, depth: SampleDepth) -> Self {
+    OutputOptions {
+      transfer,
+      depth,
+      ..OutputOptions::default()
+    }
+  }
+
+  /// Return a copy of the options with the given exposure.
+  pub fn with_exposure(&self, exposure: Exposure) -> Self {
+    OutputOptions { exposure, ..*self }
+  }
+
+  /// Return a copy of the options with the given tone mapping operator.
+  pub fn with_tone_map(&self, tone_map: ToneMap) -> Self {
+    OutputOptions { tone_map, ..*self }
+  }
+
+  /// Return a copy of the options that writes an alpha channel, or not.
+  pub fn with_alpha(&self, alpha: bool) -> Self {
+    OutputOptions { alpha, ..*self }
+  }
+
+  /// Clamp, encode, and round a single channel.
+  pub fn quantize(&self, value: f64) -> u16 {
+    let value = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+    let max = self.depth.max_value() as f64;
+    (self.transfer.encode(value) * max).round() as u16
+  }
+
+  /// Clamp and round an alpha value, which is always stored linearly.
+  pub fn quantize_alpha(&self, alpha: f64) -> u16 {
+    let alpha = if alpha.is_nan() { 0.0 } else { alpha.clamp(0.0, 1.0) };
+    (alpha * self.depth.max_value() as f64).round() as u16
+  }
+}
+
+/// Output options with the exposure worked out for one image, ready to
+/// quantize its colors; `Canvas::quantizer` makes one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantizer {
+  pub options: OutputOptions,
+  /// The factor colors are multiplied by before tone mapping.
+  pub multiplier: f64,
+}
+
+impl Quantizer {
+  /// Resolve the exposure of the options for an image with the given
+  /// log-average luminance.
+  pub fn new(options: &OutputOptions, log_average_luminance: f64) -> Self {
+    Quantizer {
+      options: *options,
+      multiplier: options.exposure.multiplier(log_average_luminance),
+    }
+  }
+
+  /// Expose, tone map, and quantize each channel of a color.
+  pub fn quantize_color(&self, color: Color) -> [u16; 3] {
+    let color = self.options.tone_map.map(color * self.multiplier);
+    let options = &self.options;
+    [
+      options.quantize(color.0),
+      options.quantize(color.1),
+      options.quantize(color.2),
+    ]
+  }
+
+  /// Clamp and round an alpha value, which is always stored linearly.
+  pub fn quantize_alpha(&self, alpha: f64) -> u16 {
+    self.options.quantize_alpha(alpha)
+  }
+}
@@ -1,20 +1,25 @@
 use crate::intersection::Intersection;
 use crate::material::Material;
 use crate::matrix::Matrix;
+use crate::motion::Motion;
 use crate::object::Object;
 use crate::point::Point;
 use crate::ray::Ray;
 use crate::vector::Vector;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// A plane is a flat, two-dimensional surface that extends infinitely in all
 /// directions.
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plane {
   pub material: Material,
   pub transform: Matrix,
-  pub parent: Option<Rc<RefCell<Object>>>,
+  /// The object this plane belongs to, if any; serialized by value, so a shared
+  /// parent is copied for each child.
+  pub parent: Option<Arc<Object>>,
+  /// How the plane moves over the shutter interval, if it does.
+  pub motion: Option<Arc<Motion>>,
 }
 
 impl Plane {
@@ -24,27 +29,75 @@ impl Plane {
       transform,
       material,
       parent: None,
+      motion: None,
     }
   }
 
   /// Compute the normal vector at the given point on the plane.
   pub fn normal_at(&self, _point: Point) -> Vector {
-    Vector(0.0, 1.0, 0.0)
+    let world_normal = self.transform.inverse().transpose() * Vector(0.0, 1.0, 0.0);
+    world_normal.normalize()
   }
 
   /// Compute the intersections between the ray and the plane.
   pub fn intersect(&mut self, ray: Ray) -> Vec<Intersection> {
-    if ray.direction.1.abs() < 0.0001 {
+    let plane = self.at_time(ray.time);
+    let ray2 = ray.transform(plane.transform.inverse());
+    if ray2.direction.1.abs() < 0.0001 {
       return vec![];
     }
-    let t = -ray.origin.1 / ray.direction.1;
-    vec![Intersection::new(t, Object::Plane(self.clone()))]
+    let t = -ray2.origin.1 / ray2.direction.1;
+    vec![Intersection::new(t, Object::Plane(plane))]
   }
 
   /// Apply a transformation to the plane.
   pub fn with_transform(&self, transform: Matrix) -> Self {
     Plane {
       transform: self.transform * transform,
+      motion: self.motion.as_ref().map(|motion| {
+        Arc::new(
+          Motion::new(self.transform * transform, motion.end_transform() * transform)
+            .with_times(motion.start_time(), motion.end_time()),
+        )
+      }),
+      ..(*self).clone()
+    }
+  }
+
+  /// Return a clone of the plane that moves to the given transform between
+  /// times 0.0 and 1.0.
+  pub fn with_motion(&self, end_transform: Matrix) -> Self {
+    self.with_motion_over(end_transform, 0.0, 1.0)
+  }
+
+  /// Return a clone of the plane that moves to the given transform between
+  /// the given times.
+  pub fn with_motion_over(&self, end_transform: Matrix, start_time: f64, end_time: f64) -> Self {
+    Plane {
+      motion: Some(Arc::new(
+        Motion::new(self.transform, end_transform).with_times(start_time, end_time),
+      )),
+      ..(*self).clone()
+    }
+  }
+
+  /// Compute the transform at the given time; a moving plane stays at its
+  /// transform until its motion starts, and at its end transform after.
+  pub fn transform_at(&self, time: f64) -> Matrix {
+    self
+      .motion
+      .as_ref()
+      .map_or(self.transform, |motion| motion.transform_at(time))
+  }
+
+  /// Return a stationary clone of the plane as it is at the given time.
+  pub fn at_time(&self, time: f64) -> Self {
+    if self.motion.is_none() {
+      return self.clone();
+    }
+    Plane {
+      transform: self.transform_at(time),
+      motion: None,
       ..(*self).clone()
     }
   }
@@ -57,3 +110,9 @@ impl Plane {
     }
   }
 }
+
+impl Default for Plane {
+  fn default() -> Self {
+    Plane::new(Matrix::identity(), Material::default())
+  }
+}
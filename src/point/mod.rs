@@ -9,6 +9,7 @@ use std::ops::Sub;
 
 /// A three-dimensional point.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point(pub f64, pub f64, pub f64);
 
 impl Point {
@@ -1,10 +1,12 @@
 use crate::color::Color;
 use crate::material::Material;
 use crate::point::Point;
+use crate::trace::LightContribution;
 use crate::vector::Vector;
 
 /// A light source with no size, existing at a single point in space.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PointLight {
   pub position: Point,
   pub intensity: Color,
@@ -52,16 +54,36 @@ impl PointLight {
     }
   }
 
-  /// Light a material at a point using the eye and normal vectors.
-  pub fn light(self, material: Material, point: Point, eye: Vector, normal: Vector, in_shadow: bool) -> Color {
-    if in_shadow {
-      self.ambient_light(material)
+  /// Work out the ambient, diffuse and specular light this light adds to a
+  /// material at a point, using the eye and normal vectors.
+  pub fn contribution(
+    self,
+    material: Material,
+    point: Point,
+    eye: Vector,
+    normal: Vector,
+    in_shadow: bool,
+  ) -> LightContribution {
+    let (diffuse, specular) = if in_shadow {
+      (Color::new(0.0, 0.0, 0.0), Color::new(0.0, 0.0, 0.0))
     } else {
-      self.ambient_light(material)
-        + self.diffuse_light(material, point, normal)
-        + self.specular_light(material, point, eye, normal)
+      (
+        self.diffuse_light(material, point, normal),
+        self.specular_light(material, point, eye, normal),
+      )
+    };
+    LightContribution {
+      light: self,
+      ambient: self.ambient_light(material),
+      diffuse,
+      specular,
     }
   }
+
+  /// Light a material at a point using the eye and normal vectors.
+  pub fn light(self, material: Material, point: Point, eye: Vector, normal: Vector, in_shadow: bool) -> Color {
+    self.contribution(material, point, eye, normal, in_shadow).total()
+  }
 }
 
 impl From<(Point, Color)> for PointLight {
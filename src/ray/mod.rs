@@ -4,15 +4,28 @@ use crate::vector::Vector;
 
 /// A ray.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ray {
   pub origin: Point,
   pub direction: Vector,
+  /// The moment at which the ray is cast, somewhere between the camera's
+  /// shutter opening and closing, used to place moving objects.
+  pub time: f64,
 }
 
 impl Ray {
   /// Create a new ray.
   pub fn new(origin: Point, direction: Vector) -> Self {
-    Ray { origin, direction }
+    Ray {
+      origin,
+      direction,
+      time: 0.0,
+    }
+  }
+
+  /// Return a copy of the ray cast at the given time.
+  pub fn with_time(self, time: f64) -> Self {
+    Ray { time, ..self }
   }
 
   /// Compute the point along the ray at the given distance.
@@ -25,6 +38,7 @@ impl Ray {
     Ray {
       origin: matrix * self.origin,
       direction: matrix * self.direction,
+      time: self.time,
     }
   }
 }
@@ -0,0 +1,53 @@
+use std::f64::consts::PI;
+
+/// Compute the radical inverse of `i` in the given base, mirroring its digits
+/// about the radix point.
+pub fn radical_inverse(i: usize, base: usize) -> f64 {
+  let inverse_base = 1.0 / base as f64;
+  let mut i = i;
+  let mut factor = inverse_base;
+  let mut result = 0.0;
+  while i > 0 {
+    result += (i % base) as f64 * factor;
+    i /= base;
+    factor *= inverse_base;
+  }
+  result
+}
+
+/// Compute the `i`th of `n` points of the Hammersley set, which covers the
+/// unit square evenly without needing a random number generator.
+pub fn hammersley(i: usize, n: usize) -> (f64, f64) {
+  ((i as f64 + 0.5) / n as f64, radical_inverse(i, 2))
+}
+
+/// Map a point in the unit square onto the unit disk, keeping neighboring
+/// points close together.
+pub fn concentric_disk(u: f64, v: f64) -> (f64, f64) {
+  let a = 2.0 * u - 1.0;
+  let b = 2.0 * v - 1.0;
+  if a == 0.0 && b == 0.0 {
+    return (0.0, 0.0);
+  }
+  let (radius, theta) = if a.abs() > b.abs() {
+    (a, (PI / 4.0) * (b / a))
+  } else {
+    (b, (PI / 2.0) - (PI / 4.0) * (a / b))
+  };
+  (radius * theta.cos(), radius * theta.sin())
+}
+
+/// Map a point in the unit square onto a regular polygon with the given number
+/// of sides, inscribed in the unit circle with its first vertex on the x axis.
+pub fn polygon(u: f64, v: f64, sides: usize) -> (f64, f64) {
+  let sides_f = sides as f64;
+  let scaled = u * sides_f;
+  let sector = scaled.floor().min(sides_f - 1.0);
+  let u = scaled - sector;
+  let angle = (2.0 * PI) / sides_f;
+  let (x0, y0) = ((sector * angle).cos(), (sector * angle).sin());
+  let (x1, y1) = (((sector + 1.0) * angle).cos(), ((sector + 1.0) * angle).sin());
+  // Sample the triangle between the center and this side uniformly.
+  let a = u.sqrt();
+  (a * (1.0 - v) * x0 + a * v * x1, a * (1.0 - v) * y0 + a * v * y1)
+}
@@ -0,0 +1,496 @@
+use crate::camera::{Camera, Projection};
+use crate::color::Color;
+use crate::error::Result;
+use crate::material::Material;
+use crate::matrix::Matrix;
+use crate::object::Object;
+use crate::plane::Plane;
+use crate::point::Point;
+use crate::point_light::PointLight;
+use crate::sphere::Sphere;
+use crate::vector::Vector;
+use crate::world::{Background, World};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub mod writer;
+pub mod yaml;
+use yaml::{Node, Value};
+
+/// A world and the camera that looks at it, as described by a scene file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scene {
+  pub camera: Camera,
+  pub world: World,
+}
+
+impl Scene {
+  /// Create a new scene.
+  pub fn new(camera: Camera, world: World) -> Self {
+    Scene { camera, world }
+  }
+
+  /// Read a scene from a YAML file.
+  pub fn load(path: &Path) -> Result<Self> {
+    Scene::from_yaml(&fs::read_to_string(path)?)
+  }
+
+  /// Parse a scene from YAML.
+  ///
+  /// The document is a list of items such as `- add: sphere`, each followed
+  /// by the properties of the thing to add.  There must be one camera and at
+  /// least one light.
+  ///
+  /// Materials and transforms can be named with `- define: name` and a
+  /// `value`, and then used by name wherever a material or a transformation
+  /// is expected.  A material definition may `extend` an earlier one,
+  /// replacing some of its properties.
+  ///
+  /// A `- background:` item sets what rays that miss everything see: either a
+  /// color or `transparent`.
+  ///
+  /// The camera may choose a `projection` other than `perspective`, and may
+  /// set a lens (`aperture`, `focal-distance`, `aperture-blades`), a number
+  /// of `samples` per pixel, and when the shutter opens and closes.  A shape
+  /// with an `end-transform` moves to it between its `start-time` and
+  /// `end-time` (0 and 1 unless given), and is blurred over whatever part of
+  /// that motion the shutter is open for.  A shape's `parent` is written
+  /// out in full, as a sphere or plane with an `add` of its own.
+  pub fn from_yaml(source: &str) -> Result<Self> {
+    let document = yaml::parse(source)?;
+    let mut camera = None;
+    let mut world = World::empty();
+    let mut definitions = Definitions::default();
+    for item in document.as_sequence()? {
+      if let Some(name) = item.get("define") {
+        let value = definitions.define(item)?;
+        definitions.values.insert(name.as_str()?.to_string(), value);
+        continue;
+      }
+      if let Some(background) = item.get("background") {
+        check_keys(item, "background", &["background"])?;
+        world.background = parse_background(background)?;
+        continue;
+      }
+      let Some(kind) = item.get("add") else {
+        return Err(item.error("expected an `add`, `define` or `background` item"));
+      };
+      match kind.as_str()? {
+        "camera" => {
+          if camera.is_some() {
+            return Err(kind.error("the scene already has a camera"));
+          }
+          camera = Some(parse_camera(item, &definitions)?);
+        },
+        "light" => world.lights.push(parse_light(item)?),
+        "sphere" | "plane" => world.objects.push(definitions.object(item)?),
+        other => return Err(kind.error(format!("don't know how to add `{}`", other))),
+      }
+    }
+    let Some(camera) = camera else {
+      return Err(document.error("the scene has no camera"));
+    };
+    if world.lights.is_empty() {
+      return Err(document.error("the scene has no light"));
+    }
+    Ok(Scene { camera, world })
+  }
+
+  /// Get a copy of the scene's camera.
+  pub fn camera(&self) -> Camera {
+    self.camera.clone()
+  }
+}
+
+/// Check that an item only has the given properties, besides `add`.
+fn check_keys(item: &Node, kind: &str, keys: &[&str]) -> Result<()> {
+  for (key, node) in item.as_mapping()? {
+    if key != "add" && !keys.contains(&key.as_str()) {
+      return Err(node.error(format!("unknown {} property `{}`", kind, key)));
+    }
+  }
+  Ok(())
+}
+
+/// Get a property that must be present.
+fn required<'a>(item: &'a Node, kind: &str, key: &str) -> Result<&'a Node> {
+  item
+    .get(key)
+    .ok_or_else(|| item.error(format!("{} is missing `{}`", kind, key)))
+}
+
+/// Read a camera.
+///
+/// Perspective and fisheye cameras need a `field-of-view`, and orthographic
+/// ones a `view-size`.  The view is given either by `from`, `to` and `up`, or
+/// by a `transform`; the lens and shutter settings are optional.
+fn parse_camera(item: &Node, definitions: &Definitions) -> Result<Camera> {
+  let projection = match item.get("projection") {
+    None => Projection::Perspective,
+    Some(node) => match node.as_str()? {
+      "perspective" => Projection::Perspective,
+      "orthographic" => Projection::Orthographic,
+      "equirectangular" => Projection::Equirectangular,
+      "fisheye" => Projection::Fisheye,
+      other => return Err(node.error(format!("unknown projection `{}`", other))),
+    },
+  };
+  let mut keys = vec![
+    "width",
+    "height",
+    "projection",
+    "from",
+    "to",
+    "up",
+    "transform",
+    "aperture",
+    "focal-distance",
+    "aperture-blades",
+    "samples",
+    "shutter-open",
+    "shutter-close",
+  ];
+  match projection {
+    Projection::Perspective | Projection::Fisheye => keys.push("field-of-view"),
+    Projection::Orthographic => keys.push("view-size"),
+    Projection::Equirectangular => {},
+  }
+  check_keys(item, "camera", &keys)?;
+  let hsize = parse_pixels(required(item, "camera", "width")?)?;
+  let vsize = parse_pixels(required(item, "camera", "height")?)?;
+  let mut camera = match projection {
+    Projection::Perspective => Camera::new(hsize, vsize, required(item, "camera", "field-of-view")?.as_f64()?),
+    Projection::Orthographic => Camera::orthographic(hsize, vsize, required(item, "camera", "view-size")?.as_f64()?),
+    Projection::Equirectangular => Camera::equirectangular(hsize, vsize),
+    Projection::Fisheye => Camera::fisheye(hsize, vsize, required(item, "camera", "field-of-view")?.as_f64()?),
+  };
+  camera.transform = match item.get("transform") {
+    Some(node) => {
+      if let Some(key) = ["from", "to", "up"].into_iter().find(|key| item.get(key).is_some()) {
+        return Err(node.error(format!("a camera with a `transform` can't also have `{}`", key)));
+      }
+      definitions.transform(node)?
+    },
+    None => {
+      let from = parse_point(required(item, "camera", "from")?)?;
+      let to_node = required(item, "camera", "to")?;
+      let to = parse_point(to_node)?;
+      let up_node = required(item, "camera", "up")?;
+      let up = parse_vector(up_node)?;
+      if (to - from).magnitude() < 0.0001 {
+        return Err(to_node.error("the camera can't look at the point it is at; `to` must differ from `from`"));
+      }
+      let sideways = (to - from).normalize().cross(up.normalize()).magnitude();
+      if sideways < 0.0001 || sideways.is_nan() {
+        return Err(up_node.error("the camera's `up` can't be along the way it looks"));
+      }
+      Matrix::view_transform(from, to, up)
+    },
+  };
+  if let Some(node) = item.get("aperture") {
+    camera.aperture = node.as_f64()?;
+  }
+  if let Some(node) = item.get("focal-distance") {
+    camera.focal_distance = node.as_f64()?;
+  }
+  if let Some(node) = item.get("aperture-blades") {
+    camera.aperture_blades = node.as_usize()?;
+  }
+  if let Some(node) = item.get("samples") {
+    camera.samples = node.as_usize()?;
+  }
+  if let Some(node) = item.get("shutter-open") {
+    camera.shutter_open = node.as_f64()?;
+  }
+  if let Some(node) = item.get("shutter-close") {
+    camera.shutter_close = node.as_f64()?;
+  }
+  // One ray through a random point on the lens only shifts the image.
+  if let Some(node) = item
+    .get("aperture")
+    .filter(|_| camera.aperture > 0.0 && camera.samples < 2)
+  {
+    return Err(node.error("an open `aperture` needs more than one of `samples`"));
+  }
+  Ok(camera)
+}
+
+/// Read a size in pixels, which can't be zero.
+fn parse_pixels(node: &Node) -> Result<usize> {
+  match node.as_usize()? {
+    0 => Err(node.error("the camera must be at least one pixel wide and high")),
+    pixels => Ok(pixels),
+  }
+}
+
+fn parse_light(item: &Node) -> Result<PointLight> {
+  check_keys(item, "light", &["at", "intensity"])?;
+  Ok(PointLight::new(
+    parse_point(required(item, "light", "at")?)?,
+    parse_color(required(item, "light", "intensity")?)?,
+  ))
+}
+
+/// The values named by `define` items so far.
+///
+/// References are resolved as each definition is read, so a definition can
+/// only use names defined before it.
+#[derive(Debug, Default)]
+struct Definitions {
+  values: HashMap<String, Node>,
+}
+
+impl Definitions {
+  /// Resolve the value of a `define` item.
+  fn define(&self, item: &Node) -> Result<Node> {
+    check_keys(item, "define", &["define", "value", "extend"])?;
+    let value = required(item, "define", "value")?;
+    match (&value.value, item.get("extend")) {
+      (Value::Mapping(entries), Some(base)) => {
+        let mut merged = self.lookup(base)?.as_mapping()?.to_vec();
+        for (key, node) in entries {
+          merged.retain(|(k, _)| k != key);
+          merged.push((key.clone(), node.clone()));
+        }
+        // Check the result now, so mistakes are reported where they're made.
+        let merged = Node {
+          line: value.line,
+          value: Value::Mapping(merged),
+        };
+        parse_material(&merged)?;
+        Ok(merged)
+      },
+      (Value::Mapping(_), None) => {
+        parse_material(value)?;
+        Ok(value.clone())
+      },
+      (Value::Sequence(_), None) => {
+        let steps = self.steps(value)?;
+        parse_transform(&steps)?;
+        Ok(Node {
+          line: value.line,
+          value: Value::Sequence(steps),
+        })
+      },
+      (_, Some(base)) => Err(base.error("only materials can be extended")),
+      _ => Err(value.error("expected a material or a list of transformations")),
+    }
+  }
+
+  fn lookup(&self, name: &Node) -> Result<&Node> {
+    let key = name.as_str()?;
+    self
+      .values
+      .get(key)
+      .ok_or_else(|| name.error(format!("`{}` hasn't been defined", key)))
+  }
+
+  /// Read a material given either by name or by its properties.
+  fn material(&self, node: &Node) -> Result<Material> {
+    match node.value {
+      Value::Scalar(_) => parse_material(self.lookup(node)?),
+      _ => parse_material(node),
+    }
+  }
+
+  /// Read a list of transformations that can be undone, as every transform
+  /// used to draw something must be.
+  fn transform(&self, node: &Node) -> Result<Matrix> {
+    let matrix = parse_transform(&self.steps(node)?)?;
+    if !matrix.is_invertible() {
+      return Err(node.error("the transformation can't be undone; is something scaled by 0?"));
+    }
+    Ok(matrix)
+  }
+
+  /// Expand the names in a list of transformations into the steps they stand
+  /// for.
+  fn steps(&self, node: &Node) -> Result<Vec<Node>> {
+    let mut steps = vec![];
+    for step in node.as_sequence()? {
+      match step.value {
+        Value::Scalar(_) => steps.extend(self.lookup(step)?.as_sequence()?.iter().cloned()),
+        _ => steps.push(step.clone()),
+      }
+    }
+    Ok(steps)
+  }
+
+  /// Read a `sphere` or `plane` item, or the parent of one.
+  fn object(&self, item: &Node) -> Result<Object> {
+    let kind = required(item, "shape", "add")?;
+    match kind.as_str()? {
+      "sphere" => {
+        check_keys(
+          item,
+          "sphere",
+          &[
+            "transform",
+            "end-transform",
+            "start-time",
+            "end-time",
+            "material",
+            "parent",
+            "center",
+            "radius",
+          ],
+        )?;
+        let mut sphere = Sphere::unit();
+        if let Some(center) = item.get("center") {
+          sphere.center = parse_point(center)?;
+        }
+        if let Some(radius) = item.get("radius") {
+          sphere.radius = radius.as_f64()?;
+        }
+        self.shape(item, Object::Sphere(sphere))
+      },
+      "plane" => {
+        check_keys(
+          item,
+          "plane",
+          &[
+            "transform",
+            "end-transform",
+            "start-time",
+            "end-time",
+            "material",
+            "parent",
+          ],
+        )?;
+        self.shape(item, Object::Plane(Plane::default()))
+      },
+      other => Err(kind.error(format!("don't know how to add `{}`", other))),
+    }
+  }
+
+  /// Apply the properties shared by every kind of shape.
+  fn shape(&self, item: &Node, object: Object) -> Result<Object> {
+    let mut object = match item.get("transform") {
+      Some(node) => object.with_transform(self.transform(node)?),
+      None => object,
+    };
+    match item.get("end-transform") {
+      Some(node) => {
+        let start_time = item.get("start-time").map_or(Ok(0.0), Node::as_f64)?;
+        let end_time = item.get("end-time").map_or(Ok(1.0), Node::as_f64)?;
+        if end_time <= start_time || end_time.is_nan() || start_time.is_nan() {
+          return Err(node.error("a motion must end after it starts"));
+        }
+        object = object.with_motion_over(self.transform(node)?, start_time, end_time);
+      },
+      None => {
+        if let Some(key) = ["start-time", "end-time"]
+          .into_iter()
+          .find(|key| item.get(key).is_some())
+        {
+          return Err(item.error(format!("`{}` needs an `end-transform`", key)));
+        }
+      },
+    }
+    if let Some(node) = item.get("material") {
+      object = object.with_material(self.material(node)?);
+    }
+    if let Some(node) = item.get("parent") {
+      object = object.with_parent(self.object(node)?);
+    }
+    Ok(object)
+  }
+}
+
+fn parse_material(node: &Node) -> Result<Material> {
+  let mut material = Material::default();
+  for (key, value) in node.as_mapping()? {
+    match key.as_str() {
+      "color" => material.color = parse_color(value)?,
+      "ambient" => material.ambient = value.as_f64()?,
+      "diffuse" => material.diffuse = value.as_f64()?,
+      "specular" => material.specular = value.as_f64()?,
+      "shininess" => material.shininess = value.as_f64()?,
+      _ => return Err(value.error(format!("unknown material property `{}`", key))),
+    }
+  }
+  Ok(material)
+}
+
+/// Combine a list of transformations, applied in the order they are written.
+fn parse_transform(steps: &[Node]) -> Result<Matrix> {
+  steps.iter().try_fold(Matrix::identity(), |matrix, step| {
+    Ok(parse_transform_step(step)? * matrix)
+  })
+}
+
+fn parse_transform_step(node: &Node) -> Result<Matrix> {
+  let items = node.as_sequence()?;
+  let Some((name, args)) = items.split_first() else {
+    return Err(node.error("expected a transformation such as `[ translate, 1, 2, 3 ]`"));
+  };
+  let name = name.as_str()?;
+  if name == "matrix" {
+    return parse_matrix(node, args);
+  }
+  let args = args.iter().map(Node::as_f64).collect::<Result<Vec<_>>>()?;
+  let expected = match name {
+    "translate" | "scale" => 3,
+    "rotate-x" | "rotate-y" | "rotate-z" => 1,
+    "shear" => 6,
+    _ => return Err(node.error(format!("unknown transformation `{}`", name))),
+  };
+  if args.len() != expected {
+    return Err(node.error(format!("`{}` takes {} numbers, found {}", name, expected, args.len())));
+  }
+  Ok(match name {
+    "translate" => Matrix::translation(args[0], args[1], args[2]),
+    "scale" => Matrix::scaling(args[0], args[1], args[2]),
+    "rotate-x" => Matrix::rotation_x(args[0]),
+    "rotate-y" => Matrix::rotation_y(args[0]),
+    "rotate-z" => Matrix::rotation_z(args[0]),
+    _ => Matrix::shearing(args[0], args[1], args[2], args[3], args[4], args[5]),
+  })
+}
+
+/// Read the four rows of a `[ matrix, [ ... ], [ ... ], [ ... ], [ ... ] ]`
+/// transformation.
+fn parse_matrix(node: &Node, rows: &[Node]) -> Result<Matrix> {
+  if rows.len() != 4 {
+    return Err(node.error(format!("`matrix` takes 4 rows, found {}", rows.len())));
+  }
+  let rows = rows
+    .iter()
+    .map(|row| match row.as_sequence()? {
+      values @ [_, _, _, _] => values.iter().map(Node::as_f64).collect(),
+      values => Err(row.error(format!("expected four numbers, found {}", values.len()))),
+    })
+    .collect::<Result<Vec<Vec<f64>>>>()?;
+  Ok(Matrix::from_rows(rows))
+}
+
+fn parse_background(node: &Node) -> Result<Background> {
+  match node.value {
+    Value::Scalar(_) if node.as_str()? == "transparent" => Ok(Background::Transparent),
+    Value::Scalar(_) => Err(node.error("expected a color or `transparent`")),
+    _ => Ok(Background::Color(parse_color(node)?)),
+  }
+}
+
+fn parse_triple(node: &Node) -> Result<(f64, f64, f64)> {
+  match node.as_sequence()? {
+    [x, y, z] => Ok((x.as_f64()?, y.as_f64()?, z.as_f64()?)),
+    items => Err(node.error(format!("expected three numbers, found {}", items.len()))),
+  }
+}
+
+fn parse_point(node: &Node) -> Result<Point> {
+  let (x, y, z) = parse_triple(node)?;
+  Ok(Point(x, y, z))
+}
+
+fn parse_vector(node: &Node) -> Result<Vector> {
+  let (x, y, z) = parse_triple(node)?;
+  Ok(Vector(x, y, z))
+}
+
+fn parse_color(node: &Node) -> Result<Color> {
+  let (r, g, b) = parse_triple(node)?;
+  Ok(Color::new(r, g, b))
+}
@@ -0,0 +1,176 @@
+use super::Scene;
+use crate::camera::{Camera, CameraBuilder, Projection};
+use crate::error::{Error, Result};
+use crate::material::Material;
+use crate::matrix::Matrix;
+use crate::object::Object;
+use crate::point::Point;
+use crate::world::Background;
+use std::fs;
+use std::path::Path;
+
+impl Scene {
+  /// Write the scene in the format read by `from_yaml`.
+  ///
+  /// Numbers are written with as many digits as it takes to read them back
+  /// exactly, so loading the result gives an equal scene.  A shared parent is
+  /// written out again for each of its children.  Test shapes have no place
+  /// in a scene file, and are an error.
+  pub fn to_yaml(&self) -> Result<String> {
+    let mut items = vec![camera_yaml(&self.camera)];
+    match self.world.background {
+      background if background == Background::default() => {},
+      Background::Color(color) => items.push(format!("- background: {}\n", triple(color.0, color.1, color.2))),
+      Background::Transparent => items.push("- background: transparent\n".to_string()),
+    }
+    for light in &self.world.lights {
+      let (at, intensity) = (light.position, light.intensity);
+      items.push(format!(
+        "- add: light\n  at: {}\n  intensity: {}\n",
+        triple(at.0, at.1, at.2),
+        triple(intensity.0, intensity.1, intensity.2),
+      ));
+    }
+    for object in &self.world.objects {
+      items.push(object_yaml(object)?);
+    }
+    Ok(items.join("\n"))
+  }
+
+  /// Write the scene to a YAML file.
+  pub fn save(&self, path: &Path) -> Result<()> {
+    fs::write(path, self.to_yaml()?)?;
+    Ok(())
+  }
+}
+
+fn camera_yaml(camera: &Camera) -> String {
+  let mut yaml = format!("- add: camera\n  width: {}\n  height: {}\n", camera.hsize, camera.vsize);
+  match camera.projection {
+    Projection::Perspective => yaml += &format!("  field-of-view: {}\n", camera.field_of_view),
+    Projection::Orthographic => {
+      yaml += &format!("  projection: orthographic\n  view-size: {}\n", camera.half_view * 2.0)
+    },
+    Projection::Equirectangular => yaml += "  projection: equirectangular\n",
+    Projection::Fisheye => yaml += &format!("  projection: fisheye\n  field-of-view: {}\n", camera.field_of_view),
+  }
+  // A view is written as where the eye is and what it looks at when that
+  // gives back exactly the same transform, and as a matrix otherwise.
+  let view = CameraBuilder::from_camera(camera);
+  if Matrix::view_transform(view.position, view.target, view.up) == camera.transform {
+    let (from, to, up) = (view.position, view.target, view.up);
+    yaml += &format!(
+      "  from: {}\n  to: {}\n  up: {}\n",
+      triple(from.0, from.1, from.2),
+      triple(to.0, to.1, to.2),
+      triple(up.0, up.1, up.2),
+    );
+  } else {
+    yaml += &transform_yaml("transform", camera.transform);
+  }
+  let defaults = Camera::new(camera.hsize, camera.vsize, camera.field_of_view);
+  if camera.aperture != defaults.aperture {
+    yaml += &format!("  aperture: {}\n", camera.aperture);
+  }
+  if camera.focal_distance != defaults.focal_distance {
+    yaml += &format!("  focal-distance: {}\n", camera.focal_distance);
+  }
+  if camera.aperture_blades != defaults.aperture_blades {
+    yaml += &format!("  aperture-blades: {}\n", camera.aperture_blades);
+  }
+  if camera.samples != defaults.samples {
+    yaml += &format!("  samples: {}\n", camera.samples);
+  }
+  if camera.shutter_open != defaults.shutter_open {
+    yaml += &format!("  shutter-open: {}\n", camera.shutter_open);
+  }
+  if camera.shutter_close != defaults.shutter_close {
+    yaml += &format!("  shutter-close: {}\n", camera.shutter_close);
+  }
+  yaml
+}
+
+fn object_yaml(object: &Object) -> Result<String> {
+  let (mut yaml, transform, motion) = match object {
+    Object::Sphere(sphere) => {
+      let mut yaml = "- add: sphere\n".to_string();
+      if sphere.center != Point::default() {
+        let center = sphere.center;
+        yaml += &format!("  center: {}\n", triple(center.0, center.1, center.2));
+      }
+      if sphere.radius != 1.0 {
+        yaml += &format!("  radius: {}\n", sphere.radius);
+      }
+      (yaml, sphere.transform, sphere.motion.as_deref())
+    },
+    Object::Plane(plane) => ("- add: plane\n".to_string(), plane.transform, plane.motion.as_deref()),
+    Object::TestShape(_) => return Err(Error::Format("test shapes can't be saved to a scene file".to_string())),
+  };
+  if transform != Matrix::identity() {
+    yaml += &transform_yaml("transform", transform);
+  }
+  if let Some(motion) = motion {
+    yaml += &transform_yaml("end-transform", motion.end_transform());
+    if motion.start_time() != 0.0 {
+      yaml += &format!("  start-time: {}\n", motion.start_time());
+    }
+    if motion.end_time() != 1.0 {
+      yaml += &format!("  end-time: {}\n", motion.end_time());
+    }
+  }
+  yaml += &material_yaml(&object.material());
+  if let Some(parent) = object.parent() {
+    // The parent is written as an item of its own, moved in under the key.
+    yaml += "  parent:\n";
+    for (index, line) in object_yaml(&parent)?.lines().enumerate() {
+      match index {
+        0 => yaml += &format!("    {}\n", line.trim_start_matches("- ")),
+        _ => yaml += &format!("  {}\n", line),
+      }
+    }
+  }
+  Ok(yaml)
+}
+
+fn material_yaml(material: &Material) -> String {
+  let color = material.color;
+  format!(
+    "  material:\n    color: {}\n    ambient: {}\n    diffuse: {}\n    specular: {}\n    shininess: {}\n",
+    triple(color.0, color.1, color.2),
+    material.ambient,
+    material.diffuse,
+    material.specular,
+    material.shininess,
+  )
+}
+
+/// Write a transform as a list of steps, using `scale` and `translate` where
+/// the matrix is exactly made of those.
+fn transform_yaml(key: &str, matrix: Matrix) -> String {
+  let m = |row, col| matrix.get_value(row, col);
+  let off_diagonal_is_zero = (0..3).all(|row| (0..3).all(|col| row == col || m(row, col) == 0.0));
+  let bottom_is_identity = m(3, 0) == 0.0 && m(3, 1) == 0.0 && m(3, 2) == 0.0 && m(3, 3) == 1.0;
+  let mut steps = vec![];
+  if off_diagonal_is_zero && bottom_is_identity {
+    if m(0, 0) != 1.0 || m(1, 1) != 1.0 || m(2, 2) != 1.0 {
+      steps.push(format!("[ scale, {}, {}, {} ]", m(0, 0), m(1, 1), m(2, 2)));
+    }
+    if m(0, 3) != 0.0 || m(1, 3) != 0.0 || m(2, 3) != 0.0 {
+      steps.push(format!("[ translate, {}, {}, {} ]", m(0, 3), m(1, 3), m(2, 3)));
+    }
+  } else {
+    let rows: Vec<String> = (0..4)
+      .map(|row| format!("[ {}, {}, {}, {} ]", m(row, 0), m(row, 1), m(row, 2), m(row, 3)))
+      .collect();
+    steps.push(format!("[ matrix, {} ]", rows.join(", ")));
+  }
+  if steps.is_empty() {
+    return format!("  {}: []\n", key);
+  }
+  let steps: String = steps.iter().map(|step| format!("    - {}\n", step)).collect();
+  format!("  {}:\n{}", key, steps)
+}
+
+fn triple(x: f64, y: f64, z: f64) -> String {
+  format!("[ {}, {}, {} ]", x, y, z)
+}
@@ -0,0 +1,473 @@
+use crate::error::{Error, Result};
+
+/// A parsed YAML value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+  /// A plain or quoted string; numbers are left for the reader to convert.
+  Scalar(String),
+  /// A block (`- item`) or flow (`[a, b]`) sequence.
+  Sequence(Vec<Node>),
+  /// A block (`key: value`) or flow (`{ key: value }`) mapping, in the order
+  /// the keys were written.
+  Mapping(Vec<(String, Node)>),
+}
+
+/// A value along with the line on which it starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+  /// The 1-based line number.
+  pub line: usize,
+  pub value: Value,
+}
+
+impl Node {
+  /// Create a parse error pointing at this node.
+  pub fn error(&self, message: impl Into<String>) -> Error {
+    Error::Parse {
+      line: self.line,
+      message: message.into(),
+    }
+  }
+
+  /// Get the node as a string.
+  pub fn as_str(&self) -> Result<&str> {
+    match &self.value {
+      Value::Scalar(scalar) => Ok(scalar),
+      _ => Err(self.error("expected a single value")),
+    }
+  }
+
+  /// Get the node as a number.
+  pub fn as_f64(&self) -> Result<f64> {
+    let scalar = self.as_str()?;
+    scalar
+      .parse()
+      .map_err(|_| self.error(format!("expected a number, found `{}`", scalar)))
+  }
+
+  /// Get the node as a non-negative whole number.
+  pub fn as_usize(&self) -> Result<usize> {
+    let scalar = self.as_str()?;
+    scalar
+      .parse()
+      .map_err(|_| self.error(format!("expected a whole number, found `{}`", scalar)))
+  }
+
+  /// Get the items of a sequence.
+  pub fn as_sequence(&self) -> Result<&[Node]> {
+    match &self.value {
+      Value::Sequence(items) => Ok(items),
+      _ => Err(self.error("expected a list")),
+    }
+  }
+
+  /// Get the entries of a mapping.
+  pub fn as_mapping(&self) -> Result<&[(String, Node)]> {
+    match &self.value {
+      Value::Mapping(entries) => Ok(entries),
+      _ => Err(self.error("expected a mapping of keys to values")),
+    }
+  }
+
+  /// Look up a key, if the node is a mapping that has it.
+  pub fn get(&self, key: &str) -> Option<&Node> {
+    match &self.value {
+      Value::Mapping(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, node)| node),
+      _ => None,
+    }
+  }
+}
+
+/// How deeply blocks, or flow sequences and mappings, can be nested, so that
+/// a runaway document is reported rather than overflowing the stack.
+const MAX_DEPTH: usize = 100;
+
+/// Parse a YAML document.
+///
+/// Only the subset used by scene files is supported: block mappings and
+/// sequences, single-line flow sequences and mappings, plain and quoted
+/// scalars, and comments.  Anchors, tags and multi-line strings are not.
+pub fn parse(source: &str) -> Result<Node> {
+  let mut lines = vec![];
+  for (index, raw) in source.lines().enumerate() {
+    let number = index + 1;
+    let text = strip_comment(raw).trim_end();
+    let content = text.trim_start();
+    if content.is_empty() || content == "---" {
+      continue;
+    }
+    let indentation = &text[..text.len() - content.len()];
+    if indentation.contains('\t') {
+      return Err(Error::Parse {
+        line: number,
+        message: "tabs can't be used for indentation".to_string(),
+      });
+    }
+    lines.push(Line {
+      number,
+      indent: indentation.len(),
+      text: content,
+    });
+  }
+  let Some(first) = lines.first().copied() else {
+    return Ok(Node {
+      line: 1,
+      value: Value::Sequence(vec![]),
+    });
+  };
+  let mut parser = Parser {
+    lines,
+    position: 0,
+    depth: 0,
+  };
+  let node = parser.block(first.indent)?;
+  if let Some(line) = parser.lines.get(parser.position) {
+    return Err(parser.unexpected(line));
+  }
+  Ok(node)
+}
+
+/// A non-blank line with its comment and indentation removed.
+#[derive(Clone, Copy, Debug)]
+struct Line<'a> {
+  number: usize,
+  indent: usize,
+  text: &'a str,
+}
+
+struct Parser<'a> {
+  lines: Vec<Line<'a>>,
+  position: usize,
+  /// How many blocks enclose the one being parsed.
+  depth: usize,
+}
+
+impl<'a> Parser<'a> {
+  /// Parse the block starting at the current line, which is at `indent`.
+  fn block(&mut self, indent: usize) -> Result<Node> {
+    let line = self.lines[self.position];
+    if self.depth == MAX_DEPTH {
+      return Err(Error::Parse {
+        line: line.number,
+        message: format!("blocks can't be nested more than {} deep", MAX_DEPTH),
+      });
+    }
+    self.depth += 1;
+    let node = if is_sequence_item(line.text) {
+      self.sequence(indent)
+    } else if split_key(line.text).is_some() {
+      self.mapping(indent)
+    } else {
+      self.position += 1;
+      inline(line.text, line.number)
+    };
+    self.depth -= 1;
+    node
+  }
+
+  fn sequence(&mut self, indent: usize) -> Result<Node> {
+    let start = self.lines[self.position].number;
+    let mut items = vec![];
+    while let Some(line) = self.lines.get(self.position).copied() {
+      if line.indent < indent {
+        break;
+      }
+      if line.indent > indent || !is_sequence_item(line.text) {
+        return Err(self.unexpected(&line));
+      }
+      let rest = line.text[1..].trim_start();
+      if rest.is_empty() {
+        self.position += 1;
+        items.push(self.nested(indent, line.number)?);
+      } else {
+        // Treat the rest of the line as if it started a block of its own, so
+        // that `- key: value` can be followed by more keys lined up with it.
+        let indent = indent + line.text.len() - rest.len();
+        self.lines[self.position] = Line {
+          indent,
+          text: rest,
+          ..line
+        };
+        items.push(self.block(indent)?);
+      }
+    }
+    Ok(Node {
+      line: start,
+      value: Value::Sequence(items),
+    })
+  }
+
+  fn mapping(&mut self, indent: usize) -> Result<Node> {
+    let start = self.lines[self.position].number;
+    let mut entries: Vec<(String, Node)> = vec![];
+    while let Some(line) = self.lines.get(self.position).copied() {
+      if line.indent < indent {
+        break;
+      }
+      if line.indent > indent {
+        return Err(self.unexpected(&line));
+      }
+      let Some((key, rest)) = split_key(line.text) else {
+        return Err(Error::Parse {
+          line: line.number,
+          message: format!("expected `key: value`, found `{}`", line.text),
+        });
+      };
+      if entries.iter().any(|(k, _)| *k == key) {
+        return Err(Error::Parse {
+          line: line.number,
+          message: format!("duplicate key `{}`", key),
+        });
+      }
+      self.position += 1;
+      let value = if !rest.is_empty() {
+        inline(rest, line.number)?
+      } else {
+        match self.lines.get(self.position) {
+          // A sequence may line up with the key that holds it.
+          Some(next) if next.indent == indent && is_sequence_item(next.text) => self.sequence(indent)?,
+          _ => self.nested(indent, line.number)?,
+        }
+      };
+      entries.push((key, value));
+    }
+    Ok(Node {
+      line: start,
+      value: Value::Mapping(entries),
+    })
+  }
+
+  /// Parse the block indented under a line that ended without a value, or an
+  /// empty value if there isn't one.
+  fn nested(&mut self, indent: usize, number: usize) -> Result<Node> {
+    match self.lines.get(self.position).copied() {
+      Some(next) if next.indent > indent => self.block(next.indent),
+      _ => Ok(Node {
+        line: number,
+        value: Value::Scalar(String::new()),
+      }),
+    }
+  }
+
+  fn unexpected(&self, line: &Line) -> Error {
+    Error::Parse {
+      line: line.number,
+      message: format!("unexpected `{}`; check the indentation", line.text),
+    }
+  }
+}
+
+fn is_sequence_item(text: &str) -> bool {
+  text == "-" || text.starts_with("- ")
+}
+
+/// Split `key: value` into its key and the (possibly empty) rest of the line.
+fn split_key(text: &str) -> Option<(String, &str)> {
+  if text.starts_with(['[', '{', '"', '\'']) {
+    return None;
+  }
+  let bytes = text.as_bytes();
+  let colon = (0..bytes.len()).find(|&i| bytes[i] == b':' && (i + 1 == bytes.len() || bytes[i + 1] == b' '))?;
+  let key = text[..colon].trim();
+  if key.is_empty() {
+    return None;
+  }
+  Some((key.to_string(), text[colon + 1..].trim()))
+}
+
+/// Remove a comment, which starts with `#` at the beginning of the line or
+/// after whitespace, outside of quotes.
+fn strip_comment(line: &str) -> &str {
+  let mut quote = None;
+  let mut previous = ' ';
+  for (index, c) in line.char_indices() {
+    match quote {
+      Some(q) if c == q => quote = None,
+      Some(_) => {},
+      None if c == '"' || c == '\'' => quote = Some(c),
+      None if c == '#' && previous.is_whitespace() => return &line[..index],
+      None => {},
+    }
+    previous = c;
+  }
+  line
+}
+
+/// Parse a value written on a single line.
+fn inline(text: &str, line: usize) -> Result<Node> {
+  let mut flow = Flow {
+    chars: text.chars().collect(),
+    position: 0,
+    line,
+    depth: 0,
+  };
+  let node = flow.value(false)?;
+  flow.skip_whitespace();
+  if flow.position < flow.chars.len() {
+    let rest: String = flow.chars[flow.position..].iter().collect();
+    return Err(flow.error(format!("unexpected `{}`", rest)));
+  }
+  Ok(node)
+}
+
+/// A cursor over a single-line value.
+struct Flow {
+  chars: Vec<char>,
+  position: usize,
+  line: usize,
+  /// How many sequences and mappings enclose the current position.
+  depth: usize,
+}
+
+impl Flow {
+  fn value(&mut self, nested: bool) -> Result<Node> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('[') | Some('{') if self.depth == MAX_DEPTH => Err(self.error(format!(
+        "lists and mappings can't be nested more than {} deep",
+        MAX_DEPTH
+      ))),
+      Some('[') => self.nest(Flow::sequence),
+      Some('{') => self.nest(Flow::mapping),
+      Some('"') | Some('\'') => self.quoted(),
+      _ if nested => Ok(self.plain(&[',', ']', '}'])),
+      _ => Ok(self.plain(&[])),
+    }
+  }
+
+  /// Parse a sequence or mapping one level deeper.
+  fn nest(&mut self, parse: fn(&mut Self) -> Result<Node>) -> Result<Node> {
+    self.depth += 1;
+    let node = parse(self);
+    self.depth -= 1;
+    node
+  }
+
+  fn sequence(&mut self) -> Result<Node> {
+    self.position += 1;
+    let mut items = vec![];
+    loop {
+      self.skip_whitespace();
+      if self.eat(']') {
+        break;
+      }
+      items.push(self.value(true)?);
+      if self.separator(']')? {
+        break;
+      }
+    }
+    Ok(self.node(Value::Sequence(items)))
+  }
+
+  fn mapping(&mut self) -> Result<Node> {
+    self.position += 1;
+    let mut entries = vec![];
+    loop {
+      self.skip_whitespace();
+      if self.eat('}') {
+        break;
+      }
+      let key = match self.peek() {
+        Some('"') | Some('\'') => self.quoted()?,
+        _ => self.plain(&[':', ',', '}']),
+      };
+      self.expect(':')?;
+      entries.push((key.as_str()?.to_string(), self.value(true)?));
+      if self.separator('}')? {
+        break;
+      }
+    }
+    Ok(self.node(Value::Mapping(entries)))
+  }
+
+  fn quoted(&mut self) -> Result<Node> {
+    let quote = self.chars[self.position];
+    self.position += 1;
+    let mut text = String::new();
+    while let Some(c) = self.peek() {
+      self.position += 1;
+      if c == quote {
+        return Ok(self.node(Value::Scalar(text)));
+      }
+      if c == '\\' && quote == '"' {
+        if let Some(escaped) = self.peek() {
+          self.position += 1;
+          text.push(match escaped {
+            'n' => '\n',
+            't' => '\t',
+            other => other,
+          });
+          continue;
+        }
+      }
+      text.push(c);
+    }
+    Err(self.error("unterminated string"))
+  }
+
+  fn plain(&mut self, stops: &[char]) -> Node {
+    let start = self.position;
+    while self.peek().is_some_and(|c| !stops.contains(&c)) {
+      self.position += 1;
+    }
+    let text: String = self.chars[start..self.position].iter().collect();
+    self.node(Value::Scalar(text.trim().to_string()))
+  }
+
+  fn expect(&mut self, c: char) -> Result<()> {
+    self.skip_whitespace();
+    if self.eat(c) {
+      return Ok(());
+    }
+    Err(self.unexpected(&format!("`{}`", c)))
+  }
+
+  /// Consume the comma between items, returning true at the closing bracket.
+  fn separator(&mut self, close: char) -> Result<bool> {
+    self.skip_whitespace();
+    if self.eat(close) {
+      return Ok(true);
+    }
+    if self.eat(',') {
+      return Ok(false);
+    }
+    Err(self.unexpected(&format!("`,` or `{}`", close)))
+  }
+
+  fn unexpected(&self, expected: &str) -> Error {
+    match self.peek() {
+      Some(found) => self.error(format!("expected {}, found `{}`", expected, found)),
+      None => self.error(format!("expected {} before the end of the line", expected)),
+    }
+  }
+
+  fn eat(&mut self, c: char) -> bool {
+    if self.peek() == Some(c) {
+      self.position += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.position).copied()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.peek().is_some_and(char::is_whitespace) {
+      self.position += 1;
+    }
+  }
+
+  fn node(&self, value: Value) -> Node {
+    Node { line: self.line, value }
+  }
+
+  fn error(&self, message: impl Into<String>) -> Error {
+    Error::Parse {
+      line: self.line,
+      message: message.into(),
+    }
+  }
+}
@@ -1,21 +1,26 @@
 use crate::intersection::Intersection;
 use crate::material::Material;
 use crate::matrix::Matrix;
+use crate::motion::Motion;
 use crate::object::Object;
 use crate::point::Point;
 use crate::ray::Ray;
 use crate::vector::Vector;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// A sphere.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sphere {
   pub center: Point,
   pub radius: f64,
   pub transform: Matrix,
   pub material: Material,
-  pub parent: Option<Rc<RefCell<Object>>>,
+  /// The object this sphere belongs to, if any; serialized by value, so a shared
+  /// parent is copied for each child.
+  pub parent: Option<Arc<Object>>,
+  /// How the sphere moves over the shutter interval, if it does.
+  pub motion: Option<Arc<Motion>>,
 }
 
 impl Sphere {
@@ -27,6 +32,7 @@ impl Sphere {
       transform: Matrix::identity(),
       material: Material::default(),
       parent: None,
+      motion: None,
     }
   }
 
@@ -38,6 +44,7 @@ impl Sphere {
       transform,
       material,
       parent: None,
+      motion: None,
     }
   }
 
@@ -49,6 +56,7 @@ impl Sphere {
       transform: Matrix::identity(),
       material: Material::glass(),
       parent: None,
+      motion: None,
     }
   }
 
@@ -63,7 +71,8 @@ impl Sphere {
 
   /// Compute the intersections between the ray and the sphere.
   pub fn intersect(&self, ray: Ray) -> Vec<Intersection> {
-    let ray2 = ray.transform(self.transform.inverse());
+    let sphere = self.at_time(ray.time);
+    let ray2 = ray.transform(sphere.transform.inverse());
     let sphere_to_ray = ray2.origin - self.center;
     let a = ray2.direction.dot(ray2.direction);
     let b = 2.0 * ray2.direction.dot(sphere_to_ray);
@@ -75,8 +84,8 @@ impl Sphere {
       let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
       let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
       vec![
-        Intersection::new(t1, Object::Sphere(self.clone())),
-        Intersection::new(t2, Object::Sphere(self.clone())),
+        Intersection::new(t1, Object::Sphere(sphere.clone())),
+        Intersection::new(t2, Object::Sphere(sphere)),
       ]
     }
   }
@@ -85,6 +94,50 @@ impl Sphere {
   pub fn with_transform(&self, transform: Matrix) -> Self {
     Sphere {
       transform: self.transform * transform,
+      motion: self.motion.as_ref().map(|motion| {
+        Arc::new(
+          Motion::new(self.transform * transform, motion.end_transform() * transform)
+            .with_times(motion.start_time(), motion.end_time()),
+        )
+      }),
+      ..(*self).clone()
+    }
+  }
+
+  /// Return a clone of the sphere that moves to the given transform between
+  /// times 0.0 and 1.0.
+  pub fn with_motion(&self, end_transform: Matrix) -> Self {
+    self.with_motion_over(end_transform, 0.0, 1.0)
+  }
+
+  /// Return a clone of the sphere that moves to the given transform between
+  /// the given times.
+  pub fn with_motion_over(&self, end_transform: Matrix, start_time: f64, end_time: f64) -> Self {
+    Sphere {
+      motion: Some(Arc::new(
+        Motion::new(self.transform, end_transform).with_times(start_time, end_time),
+      )),
+      ..(*self).clone()
+    }
+  }
+
+  /// Compute the transform at the given time; a moving sphere stays at its
+  /// transform until its motion starts, and at its end transform after.
+  pub fn transform_at(&self, time: f64) -> Matrix {
+    self
+      .motion
+      .as_ref()
+      .map_or(self.transform, |motion| motion.transform_at(time))
+  }
+
+  /// Return a stationary clone of the sphere as it is at the given time.
+  pub fn at_time(&self, time: f64) -> Self {
+    if self.motion.is_none() {
+      return self.clone();
+    }
+    Sphere {
+      transform: self.transform_at(time),
+      motion: None,
       ..(*self).clone()
     }
   }
@@ -1,20 +1,25 @@
 use crate::intersection::Intersection;
 use crate::material::Material;
 use crate::matrix::Matrix;
+use crate::motion::Motion;
 use crate::object::Object;
 use crate::point::Point;
 use crate::ray::Ray;
 use crate::vector::Vector;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// The TestShape struct represents a test shape.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestShape {
   pub transform: Matrix,
   pub material: Material,
   pub saved_ray: Option<Ray>,
-  pub parent: Option<Rc<RefCell<Object>>>,
+  /// The object this shape belongs to, if any; serialized by value, so a shared
+  /// parent is copied for each child.
+  pub parent: Option<Arc<Object>>,
+  /// How the shape moves over the shutter interval, if it does.
+  pub motion: Option<Arc<Motion>>,
 }
 
 impl TestShape {
@@ -25,12 +30,13 @@ impl TestShape {
       material: Material::default(),
       saved_ray: None,
       parent: None,
+      motion: None,
     }
   }
 
   /// Compute the intersections between the ray and the sphere.
   pub fn intersect(&mut self, ray: Ray) -> Vec<Intersection> {
-    let ray2 = ray.transform(self.transform.inverse());
+    let ray2 = ray.transform(self.transform_at(ray.time).inverse());
     self.saved_ray = Some(ray2);
     vec![]
   }
@@ -47,10 +53,42 @@ impl TestShape {
   pub fn with_transform(&self, transform: Matrix) -> Self {
     TestShape {
       transform: self.transform * transform,
+      motion: self.motion.as_ref().map(|motion| {
+        Arc::new(
+          Motion::new(self.transform * transform, motion.end_transform() * transform)
+            .with_times(motion.start_time(), motion.end_time()),
+        )
+      }),
       ..(*self).clone()
     }
   }
 
+  /// Return a clone of the test shape that moves to the given transform between
+  /// times 0.0 and 1.0.
+  pub fn with_motion(&self, end_transform: Matrix) -> Self {
+    self.with_motion_over(end_transform, 0.0, 1.0)
+  }
+
+  /// Return a clone of the test shape that moves to the given transform between
+  /// the given times.
+  pub fn with_motion_over(&self, end_transform: Matrix, start_time: f64, end_time: f64) -> Self {
+    TestShape {
+      motion: Some(Arc::new(
+        Motion::new(self.transform, end_transform).with_times(start_time, end_time),
+      )),
+      ..(*self).clone()
+    }
+  }
+
+  /// Compute the transform at the given time; a moving test shape stays at its
+  /// transform until its motion starts, and at its end transform after.
+  pub fn transform_at(&self, time: f64) -> Matrix {
+    self
+      .motion
+      .as_ref()
+      .map_or(self.transform, |motion| motion.transform_at(time))
+  }
+
   /// Return a clone of the test shape with a new material.
   pub fn with_material(&self, material: Material) -> Self {
     TestShape {
@@ -67,6 +105,7 @@ impl Default for TestShape {
       material: Material::default(),
       saved_ray: None,
       parent: None,
+      motion: None,
     }
   }
 }
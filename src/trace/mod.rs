@@ -0,0 +1,68 @@
+use crate::color::Color;
+use crate::comps::Comps;
+use crate::intersection::Intersection;
+use crate::point_light::PointLight;
+use crate::ray::Ray;
+
+/// Everything involved in computing the color of one pixel.
+#[derive(Clone, Debug, Default)]
+pub struct PixelTrace {
+  pub x: usize,
+  pub y: usize,
+  /// Whether the pixel is in the camera's view; if not, no rays are traced.
+  pub in_view: bool,
+  /// One trace for each sample taken.
+  pub samples: Vec<RayTrace>,
+  /// The final color of the pixel, premultiplied by the coverage.
+  pub color: Color,
+  pub alpha: f64,
+}
+
+/// A ray cast into the world, and what it saw.
+#[derive(Clone, Debug, Default)]
+pub struct RayTrace {
+  pub ray: Ray,
+  /// Every intersection along the ray, nearest first.
+  pub intersections: Vec<Intersection>,
+  /// The shading at the hit, or `None` if the ray saw the background.
+  pub hit: Option<HitTrace>,
+  pub color: Color,
+  pub alpha: f64,
+}
+
+/// How the surface at a hit was shaded.
+#[derive(Clone, Debug, Default)]
+pub struct HitTrace {
+  pub comps: Comps,
+  pub shadow: ShadowTrace,
+  /// What each light in the world contributed, in order.
+  pub lights: Vec<LightContribution>,
+}
+
+/// The ray cast from a hit towards the first light, to see if it's in shadow.
+#[derive(Clone, Debug, Default)]
+pub struct ShadowTrace {
+  pub ray: Ray,
+  pub intersections: Vec<Intersection>,
+  /// The distance from the hit to the light.
+  pub distance: f64,
+  pub in_shadow: bool,
+}
+
+/// The light one point light added at a hit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LightContribution {
+  pub light: PointLight,
+  pub ambient: Color,
+  /// Zero where the hit is in shadow.
+  pub diffuse: Color,
+  /// Zero where the hit is in shadow.
+  pub specular: Color,
+}
+
+impl LightContribution {
+  /// The total light added.
+  pub fn total(&self) -> Color {
+    self.ambient + self.diffuse + self.specular
+  }
+}
@@ -7,6 +7,7 @@ use std::ops::Sub;
 
 /// A three-dimensional vector.
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector(pub f64, pub f64, pub f64);
 
 impl Vector {
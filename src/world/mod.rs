@@ -2,6 +2,7 @@ use crate::camera::Camera;
 use crate::canvas::Canvas;
 use crate::color::Color;
 use crate::comps::Comps;
+use crate::error::Result;
 use crate::hit::Hit;
 use crate::intersection::Intersection;
 use crate::material::Material;
@@ -12,14 +13,44 @@ use crate::point::Point;
 use crate::point_light::PointLight;
 use crate::ray::Ray;
 use crate::sphere::Sphere;
+use crate::trace::{HitTrace, LightContribution, RayTrace, ShadowTrace};
+
+/// What a ray sees when it misses every object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Background {
+  /// A solid color.
+  Color(Color),
+  /// Nothing at all, leaving the pixel uncovered.
+  Transparent,
+}
+
+impl Background {
+  /// The color a ray that misses everything sees, and its alpha.
+  pub fn color_and_alpha(self) -> (Color, f64) {
+    match self {
+      Background::Color(color) => (color, 1.0),
+      Background::Transparent => (Color::new(0.0, 0.0, 0.0), 0.0),
+    }
+  }
+}
+
+impl Default for Background {
+  fn default() -> Self {
+    Background::Color(Color::new(0.0, 0.0, 0.0))
+  }
+}
 
 /// The world struct.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct World {
   /// The objects in the world.
   pub objects: Vec<Object>,
   /// The lights in the world.
   pub lights: Vec<PointLight>,
+  /// What rays that miss every object see.
+  pub background: Background,
 }
 
 impl World {
@@ -29,7 +60,19 @@ impl World {
 
   /// Create a new world.
   pub fn new(objects: Vec<Object>, lights: Vec<PointLight>) -> Self {
-    World { objects, lights }
+    World {
+      objects,
+      lights,
+      background: Background::default(),
+    }
+  }
+
+  /// Return a copy of the world with the given background.
+  pub fn with_background(&self, background: Background) -> Self {
+    World {
+      background,
+      ..self.clone()
+    }
   }
 
   /// Calculate the intersections between the world and the given ray as
@@ -39,10 +82,21 @@ impl World {
     for object in &mut self.objects {
       intersections.append(&mut object.intersect(ray));
     }
-    intersections.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+    intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
     intersections
   }
 
+  /// Find the hit for the given ray, along with the position in the list of
+  /// the object that was hit.
+  pub fn hit_with_index(&mut self, ray: Ray) -> Option<(usize, Intersection)> {
+    self
+      .objects
+      .iter_mut()
+      .enumerate()
+      .filter_map(|(index, object)| object.intersect(ray).hit().map(|hit| (index, hit)))
+      .min_by(|a, b| a.1.t.total_cmp(&b.1.t))
+  }
+
   /// Prepare computations.
   pub fn prepare_computations(&self, intersection: &Intersection, ray: Ray) -> crate::comps::Comps {
     Comps::prepare(intersection, ray)
@@ -50,32 +104,86 @@ impl World {
 
   /// Calculate the color at the intersection encapsulated by comps.
   pub fn shade_hit(&mut self, comps: &Comps) -> Color {
-    let in_shadow = self.is_shadowed(comps.over_point);
-    // Iterate over the lights in the world, calculating the color at the
-    // intersection for each light.
-    self.lights.iter().fold(Color::new(0.0, 0.0, 0.0), |acc, light| {
-      acc
-        + comps
-          .object
-          .material()
-          .lighting(*light, comps.point, comps.eyev, comps.normalv, in_shadow)
-    })
+    let in_shadow = self.is_shadowed_at(comps.over_point, comps.time);
+    total_light(&self.light_contributions(comps, in_shadow))
+  }
+
+  /// Calculate what each light in the world adds at the intersection
+  /// encapsulated by comps.
+  fn light_contributions(&self, comps: &Comps, in_shadow: bool) -> Vec<LightContribution> {
+    let material = comps.object.material();
+    self
+      .lights
+      .iter()
+      .map(|light| light.contribution(material, comps.point, comps.eyev, comps.normalv, in_shadow))
+      .collect()
   }
 
   /// Calculate the color at the ray.
   pub fn color_at(&mut self, ray: Ray) -> Color {
+    self.color_and_alpha_at(ray).0
+  }
+
+  /// Calculate the color at the ray, and whether it hit anything opaque: the
+  /// alpha is 1 unless the ray saw a transparent background.
+  pub fn color_and_alpha_at(&mut self, ray: Ray) -> (Color, f64) {
     let intersections = self.intersect(ray);
     // Find the hit, if any.
     let hit = intersections.hit();
-    // If there was no hit, return black.
+    // If there was no hit, return the background.
     if hit.is_none() {
-      return Color::new(0.0, 0.0, 0.0);
+      return self.background.color_and_alpha();
     }
     // Otherwise, calculate the color at the hit.
     let hit = hit.unwrap();
     let comps = self.prepare_computations(&hit, ray);
 
-    self.shade_hit(&comps)
+    (self.shade_hit(&comps), 1.0)
+  }
+
+  /// Trace the ray as `color_and_alpha_at` does, recording every
+  /// intersection, the shadow ray, and what each light contributed.
+  pub fn trace_ray(&mut self, ray: Ray) -> RayTrace {
+    let intersections = self.intersect(ray);
+    let Some(hit) = intersections.hit() else {
+      let (color, alpha) = self.background.color_and_alpha();
+      return RayTrace {
+        ray,
+        intersections,
+        hit: None,
+        color,
+        alpha,
+      };
+    };
+    let comps = self.prepare_computations(&hit, ray);
+    let shadow = self.trace_shadow(comps.over_point, comps.time);
+    let lights = self.light_contributions(&comps, shadow.in_shadow);
+    let color = total_light(&lights);
+    RayTrace {
+      ray,
+      intersections,
+      hit: Some(HitTrace { comps, shadow, lights }),
+      color,
+      alpha: 1.0,
+    }
+  }
+
+  /// Trace the shadow ray from the given point towards the first light.
+  fn trace_shadow(&mut self, point: Point, time: f64) -> ShadowTrace {
+    let Some(light) = self.lights.first() else {
+      return ShadowTrace::default();
+    };
+    let v = light.position - point;
+    let distance = v.magnitude();
+    let ray = Ray::new(point, v.normalize()).with_time(time);
+    let intersections = self.intersect(ray);
+    let in_shadow = intersections.hit().is_some_and(|hit| hit.t < distance);
+    ShadowTrace {
+      ray,
+      intersections,
+      distance,
+      in_shadow,
+    }
   }
 
   /// Render the world.
@@ -84,16 +192,21 @@ impl World {
   }
 
   /// Render the world as a PNG.
-  pub fn render_png(&mut self, camera: &Camera, filename: &str) {
+  pub fn render_png(&mut self, camera: &Camera, filename: &str) -> Result<()> {
     camera.render_png(self, filename)
   }
 
   /// Determine if the given point is in shadow.
   pub fn is_shadowed(&mut self, point: Point) -> bool {
+    self.is_shadowed_at(point, 0.0)
+  }
+
+  /// Determine if the given point is in shadow at the given time.
+  pub fn is_shadowed_at(&mut self, point: Point, time: f64) -> bool {
     let v = self.lights[0].position - point;
     let distance = v.magnitude();
     let direction = v.normalize();
-    let r = Ray::new(point, direction);
+    let r = Ray::new(point, direction).with_time(time);
     let intersections = self.intersect(r);
     let hit = intersections.hit();
     if hit.is_none() {
@@ -104,6 +217,13 @@ impl World {
   }
 }
 
+/// Add up the light from every light at a hit.
+fn total_light(lights: &[LightContribution]) -> Color {
+  lights
+    .iter()
+    .fold(Color::new(0.0, 0.0, 0.0), |acc, light| acc + light.total())
+}
+
 impl Default for World {
   fn default() -> Self {
     World {
@@ -119,6 +239,7 @@ impl Default for World {
             ..Material::default()
           },
           parent: None,
+          motion: None,
         }),
         Object::Sphere(Sphere {
           center: Point::default(),
@@ -137,9 +258,11 @@ impl Default for World {
             ..Material::default()
           },
           parent: None,
+          motion: None,
         }),
       ],
       lights: vec![PointLight::new((-10.0, 10.0, -10.0).into(), (1.0, 1.0, 1.0).into())],
+      background: Background::default(),
     }
   }
 }
@@ -0,0 +1,158 @@
+#![allow(clippy::too_many_arguments)]
+use assert_approx_eq::assert_approx_eq;
+use cucumber::{given, then, when, World};
+use std::f64::consts::PI;
+use std::path::PathBuf;
+use sunhouse::aov::{Aov, AovBuffers};
+use sunhouse::camera::Camera;
+use sunhouse::canvas::exr::ExrOptions;
+use sunhouse::color::Color;
+use sunhouse::material::Material;
+use sunhouse::matrix::Matrix;
+use sunhouse::object::Object;
+use sunhouse::point::Point;
+use sunhouse::point_light::PointLight;
+use sunhouse::vector::Vector;
+use sunhouse::world::World as RenderWorld;
+
+// `AovWorld` is your shared, likely mutable state.
+// Cucumber constructs it via `Default::default()` for each scenario.
+#[derive(Debug, Default, World)]
+pub struct AovWorld {
+  w: RenderWorld,
+  c: Camera,
+  buffers: AovBuffers,
+  directory: PathBuf,
+}
+
+fn aov_named(name: &str) -> Aov {
+  *Aov::all()
+    .iter()
+    .find(|aov| aov.name() == name)
+    .unwrap_or_else(|| panic!("Unknown output variable: {}", name))
+}
+
+#[given(regex = r"^w ← default_world\(\)$")]
+fn set_default_world(world: &mut AovWorld) {
+  world.w = RenderWorld::default();
+}
+
+fn two_spheres(right_material: Material) -> RenderWorld {
+  let left = Object::sphere().with_transform(Matrix::translation(-1.5, 0.0, 0.0));
+  let mut right = Object::sphere().with_transform(Matrix::translation(1.5, 0.0, 0.0));
+  if let Object::Sphere(ref mut sphere) = right {
+    sphere.material = right_material;
+  }
+  RenderWorld::new(
+    vec![left, right],
+    vec![PointLight::new(Point(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0))],
+  )
+}
+
+#[given(regex = r"^w ← two spheres side by side sharing a material$")]
+fn set_two_spheres_sharing(world: &mut AovWorld) {
+  world.w = two_spheres(Material::default());
+}
+
+#[given(regex = r"^w ← two spheres side by side with different materials$")]
+fn set_two_spheres_different(world: &mut AovWorld) {
+  world.w = two_spheres(Material::glass());
+}
+
+#[given(regex = r"^w ← a sphere floating above a floor$")]
+fn set_sphere_above_floor(world: &mut AovWorld) {
+  world.w = RenderWorld::new(
+    vec![
+      Object::plane(),
+      Object::sphere().with_transform(Matrix::translation(0.0, 2.0, 0.0)),
+    ],
+    vec![PointLight::new(Point(0.0, 10.0, 0.0), Color::new(1.0, 1.0, 1.0))],
+  );
+}
+
+#[given(
+  regex = r"^c ← camera\((\d+), (\d+), π/2\) looking from point\((-?\d+), (-?\d+), (-?\d+)\) to point\((-?\d+), (-?\d+), (-?\d+)\)$"
+)]
+fn set_camera(world: &mut AovWorld, hsize: usize, vsize: usize, fx: f64, fy: f64, fz: f64, tx: f64, ty: f64, tz: f64) {
+  world.c = Camera::new(hsize, vsize, PI / 2.0);
+  world.c.transform = Matrix::view_transform(Point(fx, fy, fz), Point(tx, ty, tz), Vector(0.0, 1.0, 0.0));
+}
+
+#[when(regex = r"^buffers ← render_aovs\(c, w\) with all variables$")]
+fn render_all(world: &mut AovWorld) {
+  world.buffers = world.c.render_aovs(&mut world.w, &Aov::all());
+}
+
+#[when(regex = r"^buffers ← render_aovs\(c, w\) with (\w+)$")]
+fn render_one(world: &mut AovWorld, name: String) {
+  world.buffers = world.c.render_aovs(&mut world.w, &[aov_named(&name)]);
+}
+
+#[then(regex = r"^(\w+) at \((\d+), (\d+)\) = color\((-?\d+\.?\d*), (-?\d+\.?\d*), (-?\d+\.?\d*)\)$")]
+fn check_aov(world: &mut AovWorld, name: String, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let color = world.buffers.get(aov_named(&name)).unwrap().get_color_at(x, y);
+  assert_approx_eq!(color.0, r, 1e-5);
+  assert_approx_eq!(color.1, g, 1e-5);
+  assert_approx_eq!(color.2, b, 1e-5);
+}
+
+#[then(regex = r"^depth at \((\d+), (\d+)\) is infinite$")]
+fn check_depth_infinite(world: &mut AovWorld, x: usize, y: usize) {
+  let color = world.buffers.get(Aov::Depth).unwrap().get_color_at(x, y);
+  assert_eq!(color.0, f64::INFINITY);
+}
+
+#[then(regex = r"^the color of buffers = render\(c, w\)$")]
+fn check_color(world: &mut AovWorld) {
+  assert_eq!(world.buffers.color, world.c.render(&mut world.w));
+}
+
+#[then(regex = r"^buffers has a (\w+) buffer$")]
+fn check_has_buffer(world: &mut AovWorld, name: String) {
+  assert!(world.buffers.get(aov_named(&name)).is_some());
+}
+
+#[then(regex = r"^buffers has no (\w+) buffer$")]
+fn check_has_no_buffer(world: &mut AovWorld, name: String) {
+  assert!(world.buffers.get(aov_named(&name)).is_none());
+}
+
+#[then(regex = r#"^the EXR channels of buffers are "(.*)"$"#)]
+fn check_exr_channels(world: &mut AovWorld, names: String) {
+  let channels = world.buffers.exr_channels();
+  let actual: Vec<&str> = channels.iter().map(|c| c.name.as_str()).collect();
+  assert_eq!(actual.join(", "), names);
+}
+
+#[then(regex = r"^the EXR channel (\S+) of buffers at \((\d+), (\d+)\) = (-?\d+\.?\d*)$")]
+fn check_exr_channel_value(world: &mut AovWorld, name: String, x: usize, y: usize, value: f64) {
+  let channels = world.buffers.exr_channels();
+  let channel = channels.iter().find(|c| c.name == name).unwrap();
+  assert_approx_eq!(channel.values[y * world.buffers.color.width + x], value, 1e-5);
+}
+
+#[when(regex = r"^buffers are saved as EXR files$")]
+fn save_exr_files(world: &mut AovWorld) {
+  world.directory = std::env::temp_dir().join(format!("sunhouse-aov-{}", std::process::id()));
+  std::fs::create_dir_all(&world.directory).unwrap();
+  world
+    .buffers
+    .save_exr_files(&world.directory, &ExrOptions::default())
+    .unwrap();
+}
+
+#[then(regex = r#"^the EXR files "(.*)" were written$"#)]
+fn check_exr_files(world: &mut AovWorld, names: String) {
+  let mut written: Vec<String> = std::fs::read_dir(&world.directory)
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+    .collect();
+  written.sort();
+  std::fs::remove_dir_all(&world.directory).unwrap();
+  assert_eq!(written.join(", "), names);
+}
+
+// This runs before everything else, so you can setup things here.
+fn main() {
+  futures::executor::block_on(AovWorld::run("tests/features/aov.feature"));
+}
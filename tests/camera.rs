@@ -1,13 +1,28 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::{given, then, when, World};
+use std::sync::Mutex;
+use std::time::Duration;
 use sunhouse::camera::Camera;
+use sunhouse::camera::CameraBuilder;
+use sunhouse::camera::CameraTrack;
+use sunhouse::camera::Interpolation;
+use sunhouse::camera::Keyframe;
+use sunhouse::camera::Progress;
+use sunhouse::camera::ProgressiveRender;
+use sunhouse::camera::Projection;
+use sunhouse::camera::RenderJob;
+use sunhouse::camera::RenderOutcome;
 use sunhouse::canvas::Canvas;
+use sunhouse::color::Color;
 use sunhouse::matrix::Matrix;
 use sunhouse::point::Point;
 use sunhouse::ray::Ray;
+use sunhouse::trace::PixelTrace;
 use sunhouse::vector::Vector;
+use sunhouse::world::Background;
 use sunhouse::world::World as RenderWorld;
+use sunhouse::Error;
 
 // `TestWorld` is your shared, likely mutable state.
 // Cucumber constructs it via `Default::default()` for each scenario.
@@ -23,6 +38,15 @@ pub struct TestWorld {
   pub to: Point,
   pub up: Vector,
   pub image: Canvas,
+  pub track: CameraTrack,
+  pub cameras: Vec<Camera>,
+  pub job: Option<RenderJob>,
+  pub outcome: RenderOutcome,
+  pub progress: Progress,
+  pub reports: Vec<Progress>,
+  pub progressive: Option<ProgressiveRender>,
+  pub passes: usize,
+  pub trace: PixelTrace,
 }
 
 #[given(regex = r#"^hsize ← (\d+)$"#)]
@@ -51,6 +75,156 @@ fn camera_is(world: &mut TestWorld, hsize: usize, vsize: usize) {
   world.c = Camera::new(hsize, vsize, std::f64::consts::PI / 2.0);
 }
 
+#[given(regex = r#"^c ← orthographic_camera\((\d+), (\d+), (\d+(?:\.\d+)?)\)$"#)]
+fn orthographic_camera_is(world: &mut TestWorld, hsize: usize, vsize: usize, view_size: f64) {
+  world.c = Camera::orthographic(hsize, vsize, view_size);
+}
+
+#[given(regex = r#"^c ← equirectangular_camera\((\d+), (\d+)\)$"#)]
+fn equirectangular_camera_is(world: &mut TestWorld, hsize: usize, vsize: usize) {
+  world.c = Camera::equirectangular(hsize, vsize);
+}
+
+#[given(regex = r#"^c ← fisheye_camera\((\d+), (\d+), (\d+)°\)$"#)]
+fn fisheye_camera_is(world: &mut TestWorld, hsize: usize, vsize: usize, degrees: f64) {
+  world.c = Camera::fisheye(hsize, vsize, degrees.to_radians());
+}
+
+#[then(regex = r#"^pixel \((\d+), (\d+)\) of c is (in|out of) view$"#)]
+fn pixel_is_in_view(world: &mut TestWorld, x: usize, y: usize, which: String) {
+  assert_eq!(world.c.is_in_view(x, y), which == "in");
+}
+
+#[given(regex = r#"^c\.(aperture|focal_distance|shutter_open|shutter_close) ← (\d+(?:\.\d+)?)$"#)]
+fn camera_lens_property_is(world: &mut TestWorld, property: String, value: f64) {
+  match property.as_str() {
+    "aperture" => world.c.aperture = value,
+    "focal_distance" => world.c.focal_distance = value,
+    "shutter_open" => world.c.shutter_open = value,
+    "shutter_close" => world.c.shutter_close = value,
+    _ => panic!("Unknown property: {}", property),
+  }
+}
+
+#[given(regex = r#"^c\.aperture_blades ← (\d+)$"#)]
+fn camera_aperture_blades_is(world: &mut TestWorld, blades: usize) {
+  world.c.aperture_blades = blades;
+}
+
+#[given(regex = r#"^c\.samples ← (\d+)$"#)]
+fn camera_samples_are(world: &mut TestWorld, samples: usize) {
+  world.c.samples = samples;
+}
+
+#[then(regex = r#"^c\.(aperture|shutter_open|shutter_close) = (\d+\.\d+)$"#)]
+fn camera_aperture_is(world: &mut TestWorld, property: String, value: f64) {
+  match property.as_str() {
+    "aperture" => assert_eq!(world.c.aperture, value),
+    "shutter_open" => assert_eq!(world.c.shutter_open, value),
+    "shutter_close" => assert_eq!(world.c.shutter_close, value),
+    _ => panic!("Unknown property: {}", property),
+  }
+}
+
+#[then(regex = r#"^time_for_sample\(c, (\d+)\) = (\d+\.\d+)$"#)]
+fn time_for_sample_is(world: &mut TestWorld, i: usize, time: f64) {
+  assert_approx_eq!(world.c.time_for_sample(i), time);
+}
+
+#[then(regex = r#"^every sample time of c falls within the shutter interval$"#)]
+fn sample_times_within_shutter(world: &mut TestWorld) {
+  for i in 0..64 {
+    let time = world.c.time_for_sample(i);
+    assert!(time >= world.c.shutter_open && time < world.c.shutter_close);
+  }
+}
+
+#[given(regex = r#"^every object in w moves by translation\((-?\d+), (-?\d+), (-?\d+)\)$"#)]
+fn objects_move_by(world: &mut TestWorld, x: f64, y: f64, z: f64) {
+  for object in world.w.objects.iter_mut() {
+    *object = object.with_motion(Matrix::translation(x, y, z) * object.transform());
+  }
+}
+
+#[then(regex = r#"^pixel_at\(image, (\d+), (\d+)\) is dimmer than color\((-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)\)$"#)]
+fn pixel_at_is_dimmer(world: &mut TestWorld, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let color = world.image.get_color_at(x, y);
+  assert!(color.0 < r - 1e-3 && color.1 < g - 1e-3 && color.2 < b - 1e-3);
+}
+
+#[then(
+  regex = r#"^pixel_at\(image, (\d+), (\d+)\) is brighter than color\((-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)\)$"#
+)]
+fn pixel_at_is_brighter(world: &mut TestWorld, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let color = world.image.get_color_at(x, y);
+  assert!(color.0 > r + 1e-3 && color.1 > g + 1e-3 && color.2 > b + 1e-3);
+}
+
+#[then(regex = r#"^pixel_at\(image, (\d+), (\d+)\) is not black$"#)]
+fn pixel_at_is_not_black(world: &mut TestWorld, x: usize, y: usize) {
+  let color = world.image.get_color_at(x, y);
+  assert!(color.0 + color.1 + color.2 > 0.0);
+}
+
+#[then(regex = r#"^c\.samples = (\d+)$"#)]
+fn camera_samples_is(world: &mut TestWorld, samples: usize) {
+  assert_eq!(world.c.samples, samples);
+}
+
+#[when(regex = r#"^r ← ray_for_lens_sample\(c, (\d+), (\d+), (\d+\.\d+), (\d+\.\d+)\)$"#)]
+fn ray_for_lens_sample_is(world: &mut TestWorld, x: usize, y: usize, u: f64, v: f64) {
+  world.r = world.c.ray_for_lens_sample(x, y, u, v);
+}
+
+#[then(
+  regex = r#"^ray_for_lens_sample\(c, (\d+), (\d+), (\d+\.\d+), (\d+\.\d+)\) = ray_for_pixel\(c, (\d+), (\d+)\)$"#
+)]
+fn ray_for_lens_sample_is_pinhole(world: &mut TestWorld, x: usize, y: usize, u: f64, v: f64, px: usize, py: usize) {
+  assert_eq!(world.c.ray_for_lens_sample(x, y, u, v), world.c.ray_for_pixel(px, py));
+}
+
+#[then(regex = r#"^ray_for_sample\(c, (\d+), (\d+), (\d+)\) = ray_for_pixel\(c, (\d+), (\d+)\)$"#)]
+fn ray_for_sample_is_pinhole(world: &mut TestWorld, x: usize, y: usize, i: usize, px: usize, py: usize) {
+  assert_eq!(world.c.ray_for_sample(x, y, i), world.c.ray_for_pixel(px, py));
+}
+
+#[then(regex = r#"^every lens ray for pixel \((\d+), (\d+)\) of c converges on the focal plane$"#)]
+fn lens_rays_converge(world: &mut TestWorld, x: usize, y: usize) {
+  let view = world.c.transform;
+  let pinhole = world.c.ray_for_pixel(x, y);
+  let camera_direction = view * pinhole.direction;
+  let focus = pinhole.position(world.c.focal_distance / -camera_direction.2);
+  for i in 0..16 {
+    let (u, v) = ((i % 4) as f64 / 4.0 + 0.1, (i / 4) as f64 / 4.0 + 0.1);
+    let r = world.c.ray_for_lens_sample(x, y, u, v);
+    let t = (focus - r.origin).magnitude();
+    let reached = r.position(t);
+    assert_approx_eq!(reached.0, focus.0, 1e-5);
+    assert_approx_eq!(reached.1, focus.1, 1e-5);
+    assert_approx_eq!(reached.2, focus.2, 1e-5);
+  }
+}
+
+#[then(regex = r#"^every lens point of c lies within the aperture$"#)]
+fn lens_points_within_aperture(world: &mut TestWorld) {
+  let aperture = world.c.aperture;
+  for i in 0..=10 {
+    for j in 0..=10 {
+      let (x, y) = world.c.lens_point(i as f64 / 10.0, j as f64 / 10.0);
+      if world.c.aperture_blades == 4 {
+        assert!(x.abs() + y.abs() <= aperture + 1e-9);
+      } else {
+        assert!(x.hypot(y) <= aperture + 1e-9);
+      }
+    }
+  }
+}
+
+#[then(regex = r#"^c\.projection = orthographic$"#)]
+fn camera_projection_is_orthographic(world: &mut TestWorld) {
+  assert_eq!(world.c.projection, Projection::Orthographic);
+}
+
 #[then(regex = r#"^c\.hsize = (\d+)$"#)]
 fn camera_hsize_is(world: &mut TestWorld, hsize: usize) {
   assert_eq!(world.c.hsize, hsize);
@@ -102,6 +276,17 @@ fn camera_transform_is_props(world: &mut TestWorld) {
   world.c.transform = Matrix::rotation_y(std::f64::consts::PI / 4.0) * Matrix::translation(0.0, -2.0, 5.0);
 }
 
+#[then(regex = r#"^c\.transform = rotation_y\(π/4\) \* translation\(0, -2, 5\)$"#)]
+fn camera_transform_is_moved(world: &mut TestWorld) {
+  let expected = Matrix::rotation_y(std::f64::consts::PI / 4.0) * Matrix::translation(0.0, -2.0, 5.0);
+  assert_eq!(world.c.transform, expected);
+}
+
+#[when(regex = r#"^c ← with_resolution\(c, (\d+), (\d+)\)$"#)]
+fn camera_with_resolution(world: &mut TestWorld, hsize: usize, vsize: usize) {
+  world.c = world.c.with_resolution(hsize, vsize);
+}
+
 #[then(regex = r#"^r\.direction = vector\((\d+\.\d+), √2/2, -√2/2\)$"#)]
 fn ray_direction_is_props_x(world: &mut TestWorld, x: f64) {
   assert_approx_eq!(world.r.direction.0, x, 1e-5);
@@ -147,6 +332,18 @@ fn image_is(world: &mut TestWorld) {
   world.image = world.w.render(&world.c);
 }
 
+#[when(regex = r#"^image ← render_parallel\(c, w, (\d+)\)$"#)]
+fn image_is_parallel(world: &mut TestWorld, threads: usize) {
+  world.image = world.c.render_parallel(&world.w, threads);
+}
+
+#[then(regex = r#"^image matches render\(c, w\)$"#)]
+fn image_matches_render(world: &mut TestWorld) {
+  let expected = world.w.render(&world.c);
+  assert_eq!(world.image.pixels, expected.pixels);
+  assert_eq!(world.image.alpha, expected.alpha);
+}
+
 #[then(regex = r#"^pixel_at\(image, (\d+), (\d+)\) = color\((-?\d+\.\d+), (-?\d+\.\d+), (-?\d+\.\d+)\)$"#)]
 fn pixel_at_is(world: &mut TestWorld, x: usize, y: usize, r: f64, g: f64, b: f64) {
   assert_approx_eq!(world.image.get_color_at(x, y).0, r, 1e-5);
@@ -154,6 +351,346 @@ fn pixel_at_is(world: &mut TestWorld, x: usize, y: usize, r: f64, g: f64, b: f64
   assert_approx_eq!(world.image.get_color_at(x, y).2, b, 1e-5);
 }
 
+#[when(regex = r#"^c ← build\(camera_builder\((\d+), (\d+), π/2\)\)$"#)]
+fn camera_is_built(world: &mut TestWorld, hsize: usize, vsize: usize) {
+  world.c = Camera::builder()
+    .resolution(hsize, vsize)
+    .field_of_view(std::f64::consts::PI / 2.0)
+    .build();
+}
+
+#[when(regex = r#"^c ← build\(camera_builder\((\d+), (\d+), π/2\) from from to to with up\)$"#)]
+fn camera_is_built_from_to(world: &mut TestWorld, hsize: usize, vsize: usize) {
+  world.c = CameraBuilder::new()
+    .resolution(hsize, vsize)
+    .field_of_view(std::f64::consts::PI / 2.0)
+    .position(world.from)
+    .target(world.to)
+    .up(world.up)
+    .build();
+}
+
+#[when(regex = r#"^c ← build\(camera_builder_from\(c\)\)$"#)]
+fn camera_is_rebuilt(world: &mut TestWorld) {
+  world.c = CameraBuilder::from_camera(&world.c).build();
+}
+
+#[then(regex = r#"^c\.transform ≈ view_transform\(from, to, up\)$"#)]
+fn camera_transform_is_near_view_transform(world: &mut TestWorld) {
+  let expected = Matrix::view_transform(world.from, world.to, world.up);
+  for row in 0..4 {
+    for col in 0..4 {
+      assert_approx_eq!(
+        world.c.transform.get_value(row, col),
+        expected.get_value(row, col),
+        1e-5
+      );
+    }
+  }
+}
+
+#[then(regex = r#"^c\.transform = view_transform\(from, to, up\)$"#)]
+fn camera_transform_is_view_transform(world: &mut TestWorld) {
+  assert_eq!(
+    world.c.transform,
+    Matrix::view_transform(world.from, world.to, world.up)
+  );
+}
+
+#[given(regex = r#"^track ← (linear|catmull_rom) camera track of (\d+)x(\d+)$"#)]
+fn track_is(world: &mut TestWorld, interpolation: String, hsize: usize, vsize: usize) {
+  let interpolation = match interpolation.as_str() {
+    "linear" => Interpolation::Linear,
+    _ => Interpolation::CatmullRom,
+  };
+  world.track = CameraTrack::new(CameraBuilder::new().resolution(hsize, vsize), interpolation);
+}
+
+fn parse_point(s: &str) -> Point {
+  let values: Vec<f64> = s
+    .trim_start_matches("point(")
+    .trim_end_matches(')')
+    .split(", ")
+    .map(|v| v.parse().unwrap())
+    .collect();
+  Point(values[0], values[1], values[2])
+}
+
+#[given(regex = r#"^a keyframe at time (\d+) from (point\(.*\)) to (point\(.*\)) seeing (\d+)°$"#)]
+fn track_has_keyframe(world: &mut TestWorld, time: f64, from: String, to: String, degrees: f64) {
+  let keyframe = Keyframe::new(time, parse_point(&from), parse_point(&to), degrees.to_radians());
+  world.track = world.track.with_keyframe(keyframe);
+}
+
+fn assert_point_eq(actual: Point, expected: Point) {
+  assert_approx_eq!(actual.0, expected.0, 1e-9);
+  assert_approx_eq!(actual.1, expected.1, 1e-9);
+  assert_approx_eq!(actual.2, expected.2, 1e-9);
+}
+
+fn assert_camera_is(camera: &Camera, from: &str, to: &str, degrees: f64) {
+  let view = Matrix::view_transform(parse_point(from), parse_point(to), Vector(0.0, 1.0, 0.0));
+  assert_approx_eq!(camera.field_of_view, degrees.to_radians(), 1e-9);
+  for row in 0..4 {
+    for col in 0..4 {
+      assert_approx_eq!(camera.transform.get_value(row, col), view.get_value(row, col), 1e-9);
+    }
+  }
+}
+
+#[then(
+  regex = r#"^keyframe_at\(track, (\d+(?:\.\d+)?)\) is from (point\(.*\)) to (point\(.*\)) seeing (\d+(?:\.\d+)?)°$"#
+)]
+fn keyframe_at_is(world: &mut TestWorld, time: f64, from: String, to: String, degrees: f64) {
+  let keyframe = world.track.keyframe_at(time).unwrap();
+  assert_eq!(keyframe.time, time);
+  assert_point_eq(keyframe.position, parse_point(&from));
+  assert_point_eq(keyframe.target, parse_point(&to));
+  assert_approx_eq!(keyframe.field_of_view, degrees.to_radians(), 1e-9);
+}
+
+#[then(regex = r#"^camera_at\(track, (\d+)\) is from (point\(.*\)) to (point\(.*\)) seeing (\d+)°$"#)]
+fn camera_at_is(world: &mut TestWorld, time: f64, from: String, to: String, degrees: f64) {
+  assert_camera_is(&world.track.camera_at(time).unwrap(), &from, &to, degrees);
+}
+
+#[then(regex = r#"^keyframe_at\(track, (\d+)\) is nothing$"#)]
+fn keyframe_at_is_nothing(world: &mut TestWorld, time: f64) {
+  assert_eq!(world.track.keyframe_at(time), None);
+}
+
+#[then(regex = r#"^camera_at\(track, (\d+)\) is nothing$"#)]
+fn camera_at_is_nothing(world: &mut TestWorld, time: f64) {
+  assert_eq!(world.track.camera_at(time), None);
+}
+
+#[when(regex = r#"^cameras ← cameras\(track, (\d+)\)$"#)]
+fn cameras_are(world: &mut TestWorld, frames: usize) {
+  world.cameras = world.track.cameras(frames);
+}
+
+#[then(regex = r#"^cameras\.count = (\d+)$"#)]
+fn cameras_count_is(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.cameras.len(), count);
+}
+
+#[then(regex = r#"^cameras\[(\d+)\] is from (point\(.*\)) to (point\(.*\)) seeing (\d+)°$"#)]
+fn cameras_index_is(world: &mut TestWorld, idx: usize, from: String, to: String, degrees: f64) {
+  assert_camera_is(&world.cameras[idx], &from, &to, degrees);
+}
+
+#[then(regex = r"^render_png\(c, w\) into a nonexistent directory fails with an I/O error$")]
+fn render_png_fails(world: &mut TestWorld) {
+  let path = std::env::temp_dir()
+    .join("sunhouse-missing-directory")
+    .join("render.png");
+  let result = world.c.render_png(&mut world.w, path.to_str().unwrap());
+  assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[given(regex = r"^w\.background ← transparent$")]
+fn set_background_transparent(world: &mut TestWorld) {
+  world.w = world.w.with_background(Background::Transparent);
+}
+
+#[then(regex = r"^alpha_at\(image, (\d+), (\d+)\) = (\d+\.?\d*)$")]
+fn check_alpha_at(world: &mut TestWorld, x: usize, y: usize, alpha: f64) {
+  assert_approx_eq!(world.image.get_alpha_at(x, y), alpha);
+}
+
+#[when(regex = r"^job ← render_job\(c, w\) with tiles of (\d+) on (\d+) threads?$")]
+fn job_is(world: &mut TestWorld, tile_size: usize, threads: usize) {
+  let job = RenderJob::new(world.c.clone(), world.w.clone());
+  world.job = Some(job.with_tile_size(tile_size).with_threads(threads));
+}
+
+#[when(regex = r"^job is cancelled$")]
+fn job_is_cancelled(world: &mut TestWorld) {
+  world.job.as_ref().unwrap().cancel_token().cancel();
+}
+
+#[then(regex = r"^job has (\d+) tiles$")]
+fn job_tile_count(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.job.as_ref().unwrap().tiles().len(), count);
+}
+
+#[then(regex = r"^tile (\d+) of job is at \((\d+), (\d+)\) with size (\d+)x(\d+)$")]
+fn job_tile_is(world: &mut TestWorld, index: usize, x: usize, y: usize, width: usize, height: usize) {
+  let tile = world.job.as_ref().unwrap().tiles()[index];
+  assert_eq!((tile.x, tile.y, tile.width, tile.height), (x, y, width, height));
+}
+
+#[when(regex = r"^image ← run\(job\)(?:, cancelling after (\d+) tiles)?$")]
+fn run_job(world: &mut TestWorld, cancel_after: String) {
+  let job = world.job.as_ref().unwrap();
+  let cancel = job.cancel_token();
+  let cancel_after = cancel_after.parse().unwrap_or(usize::MAX);
+  let reports = Mutex::new(vec![]);
+  world.outcome = job.run(|progress| {
+    reports.lock().unwrap().push(progress);
+    if progress.tiles_done >= cancel_after {
+      cancel.cancel();
+    }
+  });
+  world.reports = reports.into_inner().unwrap();
+  world.image = world.outcome.canvas.clone();
+}
+
+#[then(regex = r"^outcome\.tiles_done = (\d+)$")]
+fn outcome_tiles_done(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.outcome.tiles_done, count);
+}
+
+#[then(regex = r"^outcome is (not )?cancelled$")]
+fn outcome_is_cancelled(world: &mut TestWorld, not: String) {
+  assert_eq!(world.outcome.cancelled, not.is_empty());
+}
+
+#[then(regex = r"^(\d+) progress reports were made, the last at fraction 1$")]
+fn progress_reports(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.reports.len(), count);
+  assert_eq!(world.reports.last().unwrap().fraction(), 1.0);
+}
+
+#[then(regex = r"^the first (\d+) tiles of image match render\(c, w\)$")]
+fn first_tiles_match(world: &mut TestWorld, count: usize) {
+  let expected = world.w.render(&world.c);
+  for tile in &world.job.as_ref().unwrap().tiles()[..count] {
+    for y in tile.y..tile.y + tile.height {
+      for x in tile.x..tile.x + tile.width {
+        assert_eq!(world.image.get_color_at(x, y), expected.get_color_at(x, y));
+        assert_eq!(world.image.get_alpha_at(x, y), expected.get_alpha_at(x, y));
+      }
+    }
+  }
+}
+
+#[then(regex = r"^the rest of image is black and uncovered$")]
+fn rest_is_black(world: &mut TestWorld) {
+  for tile in &world.job.as_ref().unwrap().tiles()[world.outcome.tiles_done..] {
+    for y in tile.y..tile.y + tile.height {
+      for x in tile.x..tile.x + tile.width {
+        assert_eq!(world.image.get_color_at(x, y), Color::default());
+        assert_eq!(world.image.get_alpha_at(x, y), 0.0);
+      }
+    }
+  }
+}
+
+#[given(regex = r"^progress ← (\d+) of (\d+) tiles done after (\d+) seconds$")]
+fn progress_is(world: &mut TestWorld, tiles_done: usize, tiles_total: usize, seconds: u64) {
+  world.progress = Progress {
+    tiles_done,
+    tiles_total,
+    elapsed: Duration::from_secs(seconds),
+    ..Progress::default()
+  };
+}
+
+#[then(regex = r"^fraction\(progress\) = (\d+\.\d+)$")]
+fn progress_fraction(world: &mut TestWorld, fraction: f64) {
+  assert_approx_eq!(world.progress.fraction(), fraction);
+}
+
+#[then(regex = r"^eta\(progress\) = (\d+) seconds$")]
+fn progress_eta(world: &mut TestWorld, seconds: u64) {
+  assert_eq!(world.progress.eta(), Some(Duration::from_secs(seconds)));
+}
+
+#[when(regex = r"^progressive ← progressive_render\(c, w\) with blocks of (\d+)$")]
+fn progressive_is(world: &mut TestWorld, block_size: usize) {
+  let progressive = ProgressiveRender::new(world.c.clone(), world.w.clone());
+  world.progressive = Some(progressive.with_block_size(block_size));
+}
+
+#[when(regex = r"^progressive is refined (\d+) times?$")]
+fn refine_progressive(world: &mut TestWorld, passes: usize) {
+  let progressive = world.progressive.as_mut().unwrap();
+  for _ in 0..passes {
+    assert!(progressive.refine());
+  }
+  world.image = progressive.canvas().clone();
+}
+
+#[when(regex = r"^progressive is refined until done$")]
+fn refine_progressive_until_done(world: &mut TestWorld) {
+  let progressive = world.progressive.as_mut().unwrap();
+  world.passes = 0;
+  while progressive.refine() {
+    world.passes += 1;
+  }
+  assert!(progressive.is_done());
+  world.image = progressive.canvas().clone();
+}
+
+#[then(regex = r"^progressive was refined (\d+) times$")]
+fn progressive_passes(world: &mut TestWorld, passes: usize) {
+  assert_eq!(world.passes, passes);
+}
+
+#[then(regex = r"^samples_taken\(progressive\) = (\d+)$")]
+fn progressive_samples_taken(world: &mut TestWorld, samples: usize) {
+  assert_eq!(world.progressive.as_ref().unwrap().samples_taken(), samples);
+}
+
+#[then(regex = r"^every (\d+)x\d+ block of image has the color of its corner in render\(c, w\)$")]
+fn blocks_have_corner_color(world: &mut TestWorld, size: usize) {
+  let expected = world.w.render(&world.c);
+  for y in 0..world.image.height {
+    for x in 0..world.image.width {
+      let corner = (x - x % size, y - y % size);
+      assert_eq!(
+        world.image.get_color_at(x, y),
+        expected.get_color_at(corner.0, corner.1)
+      );
+    }
+  }
+}
+
+#[when(regex = r"^image ← render_region\(c, w, (\d+), (\d+), (\d+), (\d+)\)$")]
+fn image_is_region(world: &mut TestWorld, x: usize, y: usize, width: usize, height: usize) {
+  world.image = world.c.render_region(&mut world.w, x, y, width, height);
+}
+
+#[then(regex = r"^image is (\d+)x(\d+)$")]
+fn image_size(world: &mut TestWorld, width: usize, height: usize) {
+  assert_eq!((world.image.width, world.image.height), (width, height));
+}
+
+#[then(regex = r"^image matches render\(c, w\) from \((\d+), (\d+)\)$")]
+fn image_matches_render_from(world: &mut TestWorld, x0: usize, y0: usize) {
+  let expected = world.w.render(&world.c);
+  for y in 0..world.image.height {
+    for x in 0..world.image.width {
+      assert_eq!(world.image.get_color_at(x, y), expected.get_color_at(x0 + x, y0 + y));
+      assert_eq!(world.image.get_alpha_at(x, y), expected.get_alpha_at(x0 + x, y0 + y));
+    }
+  }
+}
+
+#[when(regex = r"^trace ← trace_pixel\(c, w, (\d+), (\d+)\)$")]
+fn trace_pixel(world: &mut TestWorld, x: usize, y: usize) {
+  world.trace = world.c.trace_pixel(&mut world.w, x, y);
+}
+
+#[then(regex = r"^trace\.samples\.count = (\d+)$")]
+fn trace_sample_count(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.trace.samples.len(), count);
+}
+
+#[then(regex = r"^trace\.samples\[(\d+)\]\.hit is the object (\d+) of w$")]
+fn trace_sample_hit(world: &mut TestWorld, sample: usize, object: usize) {
+  let hit = world.trace.samples[sample].hit.as_ref().unwrap();
+  assert_eq!(hit.comps.object, world.w.objects[object]);
+}
+
+#[then(regex = r"^trace\.color = pixel_at\(render\(c, w\), (\d+), (\d+)\)$")]
+fn trace_color_is_pixel(world: &mut TestWorld, x: usize, y: usize) {
+  let expected = world.w.render(&world.c);
+  assert_eq!(world.trace.color, expected.get_color_at(x, y));
+  assert_eq!(world.trace.alpha, expected.get_alpha_at(x, y));
+}
+
 // This runs before everything else, so you can setup things here.
 fn main() {
   futures::executor::block_on(TestWorld::run("tests/features/camera.feature"));
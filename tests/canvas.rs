@@ -1,8 +1,21 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
+use assert_approx_eq::assert_approx_eq;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, when, World};
+use flate2::read::ZlibDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use sunhouse::canvas::bloom::Bloom;
+use sunhouse::canvas::exr::{f32_to_half, ExrChannel, ExrCompression, ExrOptions, ExrPixelType};
+use sunhouse::canvas::hdr::{color_to_rgbe, rgbe_to_color};
+use sunhouse::canvas::kernel::Kernel;
+use sunhouse::canvas::ops::ResizeFilter;
+use sunhouse::canvas::terminal::{ansi256, TerminalColors};
 use sunhouse::canvas::Canvas;
 use sunhouse::color::Color;
+use sunhouse::output::{Exposure, OutputOptions, SampleDepth, ToneMap, TransferFunction};
+use sunhouse::Error;
 
 // `CanvasWorld` is your shared, likely mutable state.
 // Cucumber constructs it via `Default::default()` for each scenario.
@@ -14,6 +27,141 @@ pub struct CanvasWorld {
   c3: Color,
   red: Color,
   ppm: String,
+  p6: Vec<u8>,
+  png: Vec<u8>,
+  png_path: PathBuf,
+  d: Canvas,
+  hdr: Vec<u8>,
+  exr: Vec<u8>,
+  file: ExrFile,
+}
+
+/// Just enough of an OpenEXR reader to check what the writer produces.
+#[derive(Debug, Default)]
+pub struct ExrFile {
+  attributes: HashMap<String, Vec<u8>>,
+  channels: Vec<(String, i32)>,
+  width: usize,
+  height: usize,
+  values: HashMap<String, Vec<f32>>,
+}
+
+fn half_to_f32(half: u16) -> f32 {
+  let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+  let exponent = ((half >> 10) & 0x1f) as i32;
+  let mantissa = (half & 0x3ff) as f32;
+  match exponent {
+    0 => sign * mantissa * 2f32.powi(-24),
+    31 if mantissa == 0.0 => sign * f32::INFINITY,
+    31 => f32::NAN,
+    _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+  }
+}
+
+fn read_i32(bytes: &[u8], at: usize) -> i32 {
+  i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_string(bytes: &[u8], at: &mut usize) -> String {
+  let end = *at + bytes[*at..].iter().position(|b| *b == 0).unwrap();
+  let string = String::from_utf8(bytes[*at..end].to_vec()).unwrap();
+  *at = end + 1;
+  string
+}
+
+fn rle_decompress(data: &[u8]) -> Vec<u8> {
+  let mut out = vec![];
+  let mut i = 0;
+  while i < data.len() {
+    let count = data[i] as i8;
+    if count < 0 {
+      let count = (-count) as usize;
+      out.extend_from_slice(&data[i + 1..i + 1 + count]);
+      i += 1 + count;
+    } else {
+      out.extend(std::iter::repeat_n(data[i + 1], count as usize + 1));
+      i += 2;
+    }
+  }
+  out
+}
+
+fn unpredict(mut data: Vec<u8>) -> Vec<u8> {
+  for i in 1..data.len() {
+    data[i] = data[i - 1].wrapping_add(data[i]).wrapping_sub(128);
+  }
+  let half = data.len().div_ceil(2);
+  let mut out = Vec::with_capacity(data.len());
+  for i in 0..half {
+    out.push(data[i]);
+    if half + i < data.len() {
+      out.push(data[half + i]);
+    }
+  }
+  out
+}
+
+fn read_exr(bytes: &[u8]) -> ExrFile {
+  assert_eq!(bytes[0..4], [0x76, 0x2f, 0x31, 0x01]);
+  let mut file = ExrFile::default();
+  let mut at = 8;
+  while bytes[at] != 0 {
+    let name = read_string(bytes, &mut at);
+    let _kind = read_string(bytes, &mut at);
+    let size = read_i32(bytes, at) as usize;
+    file.attributes.insert(name, bytes[at + 4..at + 4 + size].to_vec());
+    at += 4 + size;
+  }
+  at += 1;
+  let chlist = &file.attributes["channels"];
+  let mut c = 0;
+  while chlist[c] != 0 {
+    let name = read_string(chlist, &mut c);
+    file.channels.push((name, read_i32(chlist, c)));
+    c += 16;
+  }
+  let window = &file.attributes["dataWindow"];
+  file.width = (read_i32(window, 8) + 1) as usize;
+  file.height = (read_i32(window, 12) + 1) as usize;
+  let compression = file.attributes["compression"][0];
+  let lines_per_block = if compression == 3 { 16 } else { 1 };
+  let sample_size = |kind: i32| if kind == 1 { 2 } else { 4 };
+  let line_size: usize = file.channels.iter().map(|(_, k)| sample_size(*k) * file.width).sum();
+  for (name, _) in &file.channels {
+    file.values.insert(name.clone(), vec![]);
+  }
+  for block in 0..file.height.div_ceil(lines_per_block) {
+    let offset = u64::from_le_bytes(bytes[at + block * 8..at + block * 8 + 8].try_into().unwrap()) as usize;
+    let first_line = read_i32(bytes, offset) as usize;
+    let size = read_i32(bytes, offset + 4) as usize;
+    let lines = lines_per_block.min(file.height - first_line);
+    let data = &bytes[offset + 8..offset + 8 + size];
+    let raw = if size == lines * line_size {
+      data.to_vec()
+    } else if compression == 1 {
+      unpredict(rle_decompress(data))
+    } else {
+      let mut inflated = vec![];
+      ZlibDecoder::new(data).read_to_end(&mut inflated).unwrap();
+      unpredict(inflated)
+    };
+    assert_eq!(raw.len(), lines * line_size);
+    let mut r = 0;
+    for _ in 0..lines {
+      for (name, kind) in &file.channels {
+        let values = file.values.get_mut(name).unwrap();
+        for _ in 0..file.width {
+          if *kind == 1 {
+            values.push(half_to_f32(u16::from_le_bytes([raw[r], raw[r + 1]])));
+          } else {
+            values.push(f32::from_le_bytes(raw[r..r + 4].try_into().unwrap()));
+          }
+          r += sample_size(*kind);
+        }
+      }
+    }
+  }
+  file
 }
 
 #[given(regex = r"^c ← canvas\((\d+), (\d+)\)$")]
@@ -133,6 +281,822 @@ fn check_ppm_newline(world: &mut CanvasWorld) {
   assert!(world.ppm.ends_with('\n'));
 }
 
+fn output_options(transfer: &str, bits: usize) -> OutputOptions {
+  let transfer = match transfer {
+    "linear" => TransferFunction::Linear,
+    "srgb" => TransferFunction::Srgb,
+    "gamma 2.2" => TransferFunction::Gamma(2.2),
+    _ => panic!("Unknown transfer function: {}", transfer),
+  };
+  let depth = match bits {
+    8 => SampleDepth::Eight,
+    16 => SampleDepth::Sixteen,
+    _ => panic!("Unsupported bit depth: {}", bits),
+  };
+  OutputOptions::new(transfer, depth)
+}
+
+#[then(regex = r"^quantize\((-?\d+\.?\d*), (linear|srgb|gamma 2\.2), (8|16)\) = (\d+)$")]
+fn check_quantize(_world: &mut CanvasWorld, value: f64, transfer: String, bits: usize, sample: u16) {
+  assert_eq!(output_options(&transfer, bits).quantize(value), sample);
+}
+
+#[when(regex = r"^ppm ← canvas_to_ppm\(c, (linear|srgb|gamma 2\.2), (8|16)\)$")]
+fn canvas_to_ppm_with(world: &mut CanvasWorld, transfer: String, bits: usize) {
+  world.ppm = world.c.to_ppm_with(&output_options(&transfer, bits));
+}
+
+#[then(regex = r"^png_data\(c, (linear|srgb|gamma 2\.2), (8|16)\) = \[(.*)\]$")]
+fn check_png_data(world: &mut CanvasWorld, transfer: String, bits: usize, bytes: String) {
+  let expected: Vec<u8> = bytes.split(", ").map(|b| b.parse().unwrap()).collect();
+  assert_eq!(world.c.to_png_data(&output_options(&transfer, bits)), expected);
+}
+
+fn tone_map(operator: &str) -> ToneMap {
+  match operator.split_once(' ') {
+    None if operator == "clamp" => ToneMap::Clamp,
+    None if operator == "reinhard" => ToneMap::Reinhard,
+    None if operator == "aces" => ToneMap::Aces,
+    Some(("reinhard_extended", white)) => ToneMap::ReinhardExtended {
+      white: white.parse().unwrap(),
+    },
+    _ => panic!("Unknown tone mapping operator: {}", operator),
+  }
+}
+
+#[then(regex = r"^tone_map\(([a-z_]+(?: \d+)?), (\d+\.\d+)\) = (\d+\.\d+)$")]
+fn check_tone_map(_world: &mut CanvasWorld, operator: String, value: f64, mapped: f64) {
+  let color = tone_map(&operator).map(Color(value, value, value));
+  assert_approx_eq!(color.0, mapped, 1e-5);
+  assert_approx_eq!(color.1, mapped, 1e-5);
+  assert_approx_eq!(color.2, mapped, 1e-5);
+}
+
+#[then(
+  regex = r"^tone_map\(([a-z_]+), color\((\d+), (\d+), (\d+)\)\) is proportional to color\((\d+), (\d+), (\d+)\)$"
+)]
+fn check_tone_map_hue(_world: &mut CanvasWorld, operator: String, r: f64, g: f64, b: f64, er: f64, eg: f64, eb: f64) {
+  let color = tone_map(&operator).map(Color(r, g, b));
+  let scale = color.0 / er;
+  assert!(scale > 0.0 && scale < 1.0);
+  assert_approx_eq!(color.1, eg * scale, 1e-9);
+  assert_approx_eq!(color.2, eb * scale, 1e-9);
+}
+
+#[then(regex = r"^png_data\(c, (linear|srgb), (8|16), (.*)\) = \[(.*)\]$")]
+fn check_png_data_mapped(world: &mut CanvasWorld, transfer: String, bits: usize, mapping: String, bytes: String) {
+  let options = output_options(&transfer, bits);
+  let options = if let Some(stops) = mapping.strip_prefix("exposure ") {
+    options.with_exposure(Exposure::Stops(stops.parse().unwrap()))
+  } else if let Some(key) = mapping.strip_prefix("auto exposure ") {
+    options.with_exposure(Exposure::Auto {
+      key: key.parse().unwrap(),
+    })
+  } else if mapping == "alpha" {
+    options.with_alpha(true)
+  } else {
+    options.with_tone_map(tone_map(&mapping))
+  };
+  let expected: Vec<u8> = bytes.split(", ").map(|b| b.parse().unwrap()).collect();
+  assert_eq!(world.c.to_png_data(&options), expected);
+}
+
+#[when(regex = r"^every pixel of c is set to color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$")]
+fn set_every_pixel(world: &mut CanvasWorld, r: f64, g: f64, b: f64) {
+  for y in 0..world.c.height {
+    for x in 0..world.c.width {
+      world.c.set_color_at(x, y, Color(r, g, b));
+    }
+  }
+}
+
+#[then(regex = r"^log_average_luminance\(c\) = (\d+\.\d+)$")]
+fn check_log_average_luminance(world: &mut CanvasWorld, luminance: f64) {
+  assert_approx_eq!(world.c.log_average_luminance(), luminance, 1e-3);
+}
+
+fn parse_bytes(bytes: &str) -> Vec<u8> {
+  bytes.split(", ").map(|b| b.parse().unwrap()).collect()
+}
+
+#[then(regex = r"^color_to_rgbe\(color\(([^,]+), ([^,]+), ([^)]+)\)\) = \[(.*)\]$")]
+fn check_color_to_rgbe(_world: &mut CanvasWorld, r: f64, g: f64, b: f64, bytes: String) {
+  assert_eq!(color_to_rgbe(Color(r, g, b)).to_vec(), parse_bytes(&bytes));
+}
+
+#[then(regex = r"^rgbe_to_color\(\[(.*)\]\) = color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$")]
+fn check_rgbe_to_color(_world: &mut CanvasWorld, bytes: String, r: f64, g: f64, b: f64) {
+  let bytes = parse_bytes(&bytes);
+  assert_eq!(rgbe_to_color([bytes[0], bytes[1], bytes[2], bytes[3]]), Color(r, g, b));
+}
+
+#[given(regex = r"^c ← gradient canvas\((\d+), (\d+)\) up to (\d+)$")]
+fn set_gradient_canvas(world: &mut CanvasWorld, width: usize, height: usize, max: f64) {
+  world.c = Canvas::new(width, height);
+  for y in 0..height {
+    for x in 0..width {
+      let u = x as f64 / width as f64;
+      let v = (y + 1) as f64 / height as f64;
+      world.c.set_color_at(x, y, Color(u * max, v, (1.0 - u) * v * 0.01));
+    }
+  }
+}
+
+#[when(regex = r"^hdr ← canvas_to_hdr\(c\)$")]
+fn canvas_to_hdr(world: &mut CanvasWorld) {
+  world.hdr = world.c.to_hdr();
+}
+
+#[when(regex = r"^d ← canvas_from_hdr\(hdr\)$")]
+fn canvas_from_hdr(world: &mut CanvasWorld) {
+  world.d = Canvas::from_hdr(&world.hdr).unwrap();
+}
+
+#[then(regex = r"^canvas_from_hdr\(hdr\) fails$")]
+fn canvas_from_hdr_fails(world: &mut CanvasWorld) {
+  assert!(Canvas::from_hdr(&world.hdr).is_err());
+}
+
+#[then(regex = r"^d matches c within RGBE precision$")]
+fn check_hdr_round_trip(world: &mut CanvasWorld) {
+  assert_eq!(world.d.width, world.c.width);
+  assert_eq!(world.d.height, world.c.height);
+  for (expected, actual) in world.c.pixels.iter().zip(world.d.pixels.iter()) {
+    // The shared exponent keeps each channel within 1/128 of the brightest.
+    let tolerance = expected.0.max(expected.1).max(expected.2) / 128.0;
+    assert_approx_eq!(actual.0, expected.0, tolerance);
+    assert_approx_eq!(actual.1, expected.1, tolerance);
+    assert_approx_eq!(actual.2, expected.2, tolerance);
+  }
+}
+
+#[then(regex = r"^hdr is smaller than (\d+) flat pixels$")]
+fn check_hdr_is_compressed(world: &mut CanvasWorld, pixels: usize) {
+  assert!(world.hdr.len() < pixels * 4);
+}
+
+#[given(expr = "hdr ← the HDR header")]
+fn set_hdr_header(world: &mut CanvasWorld, step: &Step) {
+  let header = step.docstring.as_ref().expect("No docstring found");
+  let mut lines: Vec<&str> = header.lines().skip(1).collect();
+  if lines.last() == Some(&"") {
+    lines.pop();
+  }
+  world.hdr = lines
+    .iter()
+    .flat_map(|line| format!("{}\n", line).into_bytes())
+    .collect();
+}
+
+#[given(regex = r"^hdr continues with the bytes \[(.*)\]$")]
+fn append_hdr_bytes(world: &mut CanvasWorld, bytes: String) {
+  world.hdr.extend(parse_bytes(&bytes));
+}
+
+#[then(regex = r"^d\.(width|height) = (\d+)$")]
+fn check_d_property(world: &mut CanvasWorld, property: String, value: usize) {
+  match property.as_str() {
+    "width" => assert_eq!(world.d.width, value),
+    "height" => assert_eq!(world.d.height, value),
+    _ => panic!("Unknown property: {}", property),
+  }
+}
+
+#[then(regex = r"^pixel_at\(d, (\d+), (\d+)\) = color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$")]
+fn check_d_pixel_at(world: &mut CanvasWorld, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  assert_eq!(world.d.get_color_at(x, y), Color(r, g, b));
+}
+
+#[then(regex = r"^f32_to_half\((-?\d+.?\d*)\) = (\d+)$")]
+fn check_f32_to_half(_world: &mut CanvasWorld, value: f32, bits: u16) {
+  assert_eq!(f32_to_half(value), bits);
+}
+
+fn exr_options(pixel_type: &str, compression: &str, channels: &str) -> ExrOptions {
+  let pixel_type = match pixel_type {
+    "half" => ExrPixelType::Half,
+    "float" => ExrPixelType::Float,
+    _ => panic!("Unknown pixel type: {}", pixel_type),
+  };
+  let compression = match compression {
+    "none" => ExrCompression::None,
+    "rle" => ExrCompression::Rle,
+    "zips" => ExrCompression::Zips,
+    "zip" => ExrCompression::Zip,
+    _ => panic!("Unknown compression: {}", compression),
+  };
+  ExrOptions::new(pixel_type, compression, channels == "rgba")
+}
+
+#[when(regex = r"^exr ← canvas_to_exr\(c, (half|float), (\w+), (rgba?)\)$")]
+fn canvas_to_exr(world: &mut CanvasWorld, pixel_type: String, compression: String, channels: String) {
+  world.exr = world
+    .c
+    .to_exr(&exr_options(&pixel_type, &compression, &channels))
+    .unwrap();
+}
+
+#[when(regex = r"^exr ← canvas_to_exr\(c, (half|float), (\w+), (rgba?)\) with depth and normals$")]
+fn canvas_to_exr_with_channels(world: &mut CanvasWorld, pixel_type: String, compression: String, channels: String) {
+  let count = world.c.width * world.c.height;
+  let extra = [
+    ExrChannel::new("Z", ExrPixelType::Float, (0..count).map(|i| i as f64 + 0.25).collect()),
+    ExrChannel::new("N.X", ExrPixelType::Half, vec![0.0; count]),
+    ExrChannel::new("N.Y", ExrPixelType::Half, vec![1.0; count]),
+    ExrChannel::new("N.Z", ExrPixelType::Half, vec![-0.5; count]),
+  ];
+  world.exr = world
+    .c
+    .to_exr_with_channels(&exr_options(&pixel_type, &compression, &channels), &extra)
+    .unwrap();
+}
+
+#[then(regex = r"^canvas_to_exr\(c, (half|float), (\w+), (rgba?)\) fails$")]
+fn canvas_to_exr_fails(world: &mut CanvasWorld, pixel_type: String, compression: String, channels: String) {
+  let result = world.c.to_exr(&exr_options(&pixel_type, &compression, &channels));
+  assert!(matches!(result, Err(Error::Format(_))), "{:?}", result);
+}
+
+#[then(regex = r"^canvas_to_exr\(c, (half|float), (\w+), (rgba?)\) with extra channels (.+) fails$")]
+fn canvas_to_exr_with_named_channels_fails(
+  world: &mut CanvasWorld,
+  pixel_type: String,
+  compression: String,
+  channels: String,
+  names: String,
+) {
+  let count = world.c.width * world.c.height;
+  let extra: Vec<_> = names
+    .split(", ")
+    .map(|name| ExrChannel::new(name, ExrPixelType::Float, vec![1.0; count]))
+    .collect();
+  let result = world
+    .c
+    .to_exr_with_channels(&exr_options(&pixel_type, &compression, &channels), &extra);
+  assert!(matches!(result, Err(Error::Format(_))), "{:?}", result);
+}
+
+#[then(regex = r"^canvas_to_exr\(c, (half|float), (\w+), (rgba?)\) with a (\d+)-value depth channel fails$")]
+fn canvas_to_exr_with_channel_fails(
+  world: &mut CanvasWorld,
+  pixel_type: String,
+  compression: String,
+  channels: String,
+  count: usize,
+) {
+  let extra = [ExrChannel::new("Z", ExrPixelType::Float, vec![1.0; count])];
+  let result = world
+    .c
+    .to_exr_with_channels(&exr_options(&pixel_type, &compression, &channels), &extra);
+  assert!(matches!(result, Err(Error::Format(_))), "{:?}", result);
+}
+
+#[then(regex = r"^the first bytes of exr are \[(.*)\]$")]
+fn check_exr_start(world: &mut CanvasWorld, bytes: String) {
+  let bytes = parse_bytes(&bytes);
+  assert_eq!(world.exr[..bytes.len()], bytes[..]);
+}
+
+#[when(regex = r"^file ← read_exr\(exr\)$")]
+fn set_file(world: &mut CanvasWorld) {
+  world.file = read_exr(&world.exr);
+}
+
+#[then(regex = r#"^file\.channels = "(.*)"$"#)]
+fn check_file_channels(world: &mut CanvasWorld, channels: String) {
+  let actual: Vec<String> = world
+    .file
+    .channels
+    .iter()
+    .map(|(name, kind)| format!("{}:{}", name, if *kind == 1 { "half" } else { "float" }))
+    .collect();
+  assert_eq!(actual.join(", "), channels);
+}
+
+#[then(regex = r"^file\.(compression|lineOrder) = (\d+)$")]
+fn check_file_byte_attribute(world: &mut CanvasWorld, name: String, value: u8) {
+  assert_eq!(world.file.attributes[&name], vec![value]);
+}
+
+#[then(regex = r"^file\.(dataWindow|displayWindow) = \[(.*)\]$")]
+fn check_file_window(world: &mut CanvasWorld, name: String, values: String) {
+  let window = &world.file.attributes[&name];
+  let expected: Vec<i32> = values.split(", ").map(|v| v.parse().unwrap()).collect();
+  let actual: Vec<i32> = (0..4).map(|i| read_i32(window, i * 4)).collect();
+  assert_eq!(actual, expected);
+}
+
+#[then(regex = r#"^file has the attributes "(.*)"$"#)]
+fn check_file_attributes(world: &mut CanvasWorld, names: String) {
+  let mut actual: Vec<&String> = world.file.attributes.keys().collect();
+  actual.sort();
+  assert_eq!(actual, names.split(", ").collect::<Vec<_>>());
+}
+
+#[then(regex = r"^the pixels of file match c within (half|float) precision$")]
+fn check_file_pixels(world: &mut CanvasWorld, pixel_type: String) {
+  let precision = if pixel_type == "half" { 1e-3 } else { 1e-6 };
+  assert_eq!((world.file.width, world.file.height), (world.c.width, world.c.height));
+  for (i, expected) in world.c.pixels.iter().enumerate() {
+    for (name, value) in [("R", expected.0), ("G", expected.1), ("B", expected.2)] {
+      let actual = world.file.values[name][i] as f64;
+      assert_approx_eq!(actual, value, (value.abs() * precision).max(1e-6));
+    }
+  }
+}
+
+#[then(regex = r"^exr is smaller than the uncompressed EXR$")]
+fn check_exr_is_compressed(world: &mut CanvasWorld) {
+  let uncompressed = world.c.to_exr(&ExrOptions::default()).unwrap();
+  assert!(world.exr.len() < uncompressed.len() / 4);
+}
+
+#[then(regex = r"^channel (\S+) of file at \((\d+), (\d+)\) = (-?\d+.?\d*)$")]
+fn check_file_channel_value(world: &mut CanvasWorld, name: String, x: usize, y: usize, value: f32) {
+  assert_eq!(world.file.values[&name][y * world.file.width + x], value);
+}
+
+#[then(expr = r"lines 4-7 of ppm are")]
+fn check_ppm_wrapped_body(world: &mut CanvasWorld, step: &Step) {
+  let lines: Vec<&str> = world.ppm.lines().collect();
+  let expected_lines: Vec<&str> = step
+    .docstring
+    .as_ref()
+    .expect("No docstring found")
+    .lines()
+    .skip(1)
+    .collect();
+  assert_eq!(lines[3..7], expected_lines[..4]);
+}
+
+#[when(regex = r"^p6 ← canvas_to_p6\(c\)$")]
+fn canvas_to_p6(world: &mut CanvasWorld) {
+  world.p6 = world.c.to_p6();
+}
+
+#[when(regex = r"^p6 ← canvas_to_p6\(c, (linear|srgb|gamma 2\.2), (8|16)\)$")]
+fn canvas_to_p6_with(world: &mut CanvasWorld, transfer: String, bits: usize) {
+  world.p6 = world.c.to_p6_with(&output_options(&transfer, bits));
+}
+
+#[then(regex = r#"^p6 starts with the header "(.*)"$"#)]
+fn check_p6_header(world: &mut CanvasWorld, header: String) {
+  let header = header.replace("\\n", "\n");
+  assert!(world.p6.starts_with(header.as_bytes()));
+  world.p6.drain(..header.len());
+}
+
+#[then(regex = r"^p6 continues with the bytes \[(.*)\]$")]
+fn check_p6_bytes(world: &mut CanvasWorld, bytes: String) {
+  assert_eq!(world.p6, parse_bytes(&bytes));
+}
+
+#[then(regex = r"^p6 continues with (\d+) bytes$")]
+fn check_p6_length(world: &mut CanvasWorld, length: usize) {
+  assert_eq!(world.p6.len(), length);
+}
+
+#[given(expr = "ppm ← the PPM file")]
+fn set_ppm_file(world: &mut CanvasWorld, step: &Step) {
+  let ppm = step.docstring.as_ref().expect("No docstring found");
+  world.ppm = ppm.lines().skip(1).map(|line| format!("{}\n", line)).collect();
+}
+
+#[when(regex = r"^d ← canvas_from_ppm\(ppm\)$")]
+fn canvas_from_ppm(world: &mut CanvasWorld) {
+  world.d = Canvas::from_ppm(world.ppm.as_bytes()).unwrap();
+}
+
+#[when(regex = r"^d ← canvas_from_p6\(p6\)$")]
+fn canvas_from_p6(world: &mut CanvasWorld) {
+  world.d = Canvas::from_ppm(&world.p6).unwrap();
+}
+
+#[then(regex = r"^canvas_from_ppm\(ppm\) fails$")]
+fn canvas_from_ppm_fails(world: &mut CanvasWorld) {
+  assert!(Canvas::from_ppm(world.ppm.as_bytes()).is_err());
+}
+
+#[then(regex = r"^d matches c within (8|16)-bit precision$")]
+fn check_ppm_round_trip(world: &mut CanvasWorld, bits: i32) {
+  assert_eq!((world.d.width, world.d.height), (world.c.width, world.c.height));
+  let tolerance = 0.5 / (2f64.powi(bits) - 1.0) + 1e-9;
+  for (expected, actual) in world.c.pixels.iter().zip(world.d.pixels.iter()) {
+    assert_approx_eq!(actual.0, expected.0, tolerance);
+    assert_approx_eq!(actual.1, expected.1, tolerance);
+    assert_approx_eq!(actual.2, expected.2, tolerance);
+  }
+}
+
+fn missing_directory() -> PathBuf {
+  std::env::temp_dir().join("sunhouse-missing-directory")
+}
+
+#[then(regex = r"^saving c as (\w+) into a nonexistent directory fails with an I/O error$")]
+fn check_save_fails(world: &mut CanvasWorld, format: String) {
+  let path = missing_directory().join(format!("canvas.{}", format));
+  let result = match format.as_str() {
+    "ppm" => world.c.save_ppm(&path),
+    "p6" => world.c.save_p6(&path),
+    "png" => world.c.to_png(&path),
+    "hdr" => world.c.save_hdr(&path),
+    "exr" => world.c.save_exr(&path, &ExrOptions::default()),
+    _ => panic!("Unknown format: {}", format),
+  };
+  assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[then(regex = r"^loading a missing (\w+) file fails with an I/O error$")]
+fn check_load_fails(_world: &mut CanvasWorld, format: String) {
+  let path = missing_directory().join(format!("canvas.{}", format));
+  let result = match format.as_str() {
+    "ppm" => Canvas::load_ppm(&path),
+    "hdr" => Canvas::load_hdr(&path),
+    _ => panic!("Unknown format: {}", format),
+  };
+  assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[then(regex = r"^canvas_from_ppm\(ppm\) fails with a format error$")]
+fn check_ppm_format_error(world: &mut CanvasWorld) {
+  let error = Canvas::from_ppm(world.ppm.as_bytes()).unwrap_err();
+  assert!(matches!(error, Error::Format(_)));
+  assert!(error.to_string().starts_with("Format error: Missing PPM sample"));
+}
+
+fn encode_png(color_type: png::ColorType, bits: u8, palette: Option<Vec<u8>>, samples: Vec<u8>) -> Vec<u8> {
+  let mut bytes = vec![];
+  let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+  encoder.set_color(color_type);
+  encoder.set_depth(png::BitDepth::from_u8(bits).unwrap());
+  if let Some(palette) = palette {
+    encoder.set_palette(palette);
+  }
+  let mut writer = encoder.write_header().unwrap();
+  writer.write_image_data(&samples).unwrap();
+  writer.finish().unwrap();
+  bytes
+}
+
+#[given(regex = r"^png ← a 2x1 (rgb|rgba|grayscale|grayscale alpha) PNG with (\d+)-bit samples \[(.*)\]$")]
+fn set_png(world: &mut CanvasWorld, color_type: String, bits: u8, samples: String) {
+  let color_type = match color_type.as_str() {
+    "rgb" => png::ColorType::Rgb,
+    "rgba" => png::ColorType::Rgba,
+    "grayscale" => png::ColorType::Grayscale,
+    "grayscale alpha" => png::ColorType::GrayscaleAlpha,
+    _ => panic!("Unknown color type: {}", color_type),
+  };
+  world.png = encode_png(color_type, bits, None, parse_bytes(&samples));
+}
+
+#[given(regex = r"^png ← a 2x1 palette PNG with palette \[(.*)\] and (\d+)-bit samples \[(.*)\]$")]
+fn set_palette_png(world: &mut CanvasWorld, palette: String, bits: u8, samples: String) {
+  world.png = encode_png(
+    png::ColorType::Indexed,
+    bits,
+    Some(parse_bytes(&palette)),
+    parse_bytes(&samples),
+  );
+}
+
+#[when(regex = r"^d ← canvas_from_png\(png\)$")]
+fn canvas_from_png(world: &mut CanvasWorld) {
+  world.d = Canvas::from_png(&world.png).unwrap();
+}
+
+#[when(regex = r"^d ← canvas_from_png\(png, srgb\)$")]
+fn canvas_from_png_srgb(world: &mut CanvasWorld) {
+  world.d = Canvas::from_png_with(&world.png, TransferFunction::Srgb).unwrap();
+}
+
+#[when(regex = r"^d ← canvas_from_ppm\(ppm, srgb\)$")]
+fn canvas_from_ppm_srgb(world: &mut CanvasWorld) {
+  world.d = Canvas::from_ppm_with(world.ppm.as_bytes(), TransferFunction::Srgb).unwrap();
+}
+
+#[then(regex = r"^pixel_at\((c|d), (\d+), (\d+)\) ≈ color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$")]
+fn check_pixel_near(world: &mut CanvasWorld, canvas: String, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let canvas = if canvas == "c" { &world.c } else { &world.d };
+  let color = canvas.get_color_at(x, y);
+  assert_approx_eq!(color.0, r, 1e-5);
+  assert_approx_eq!(color.1, g, 1e-5);
+  assert_approx_eq!(color.2, b, 1e-5);
+}
+
+#[when(regex = r"^c is saved as a (8|16)-bit PNG file$")]
+fn save_png(world: &mut CanvasWorld, bits: usize) {
+  world.png_path = std::env::temp_dir().join(format!("sunhouse-canvas-{}-{}.png", bits, std::process::id()));
+  world
+    .c
+    .to_png_with(&world.png_path, &output_options("linear", bits))
+    .unwrap();
+}
+
+#[when(regex = r"^d ← the saved PNG file$")]
+fn load_saved_png(world: &mut CanvasWorld) {
+  world.d = Canvas::load_png(&world.png_path).unwrap();
+  std::fs::remove_file(&world.png_path).unwrap();
+}
+
+#[then(regex = r"^canvas_from_png\(ppm\) fails with a PNG error$")]
+fn check_png_error(world: &mut CanvasWorld) {
+  let result = Canvas::from_png(world.ppm.as_bytes());
+  assert!(matches!(result, Err(Error::PngDecoding(_))));
+}
+
+#[then(regex = r"^every pixel of c has alpha (\d+\.?\d*)$")]
+fn check_every_alpha(world: &mut CanvasWorld, alpha: f64) {
+  assert!(world.c.alpha.iter().all(|a| *a == alpha));
+  assert_eq!(world.c.alpha.len(), world.c.width * world.c.height);
+}
+
+#[when(regex = r"^set_alpha\(c, (\d+), (\d+), (\d+\.?\d*)\)$")]
+fn set_alpha(world: &mut CanvasWorld, x: usize, y: usize, alpha: f64) {
+  world.c.set_alpha_at(x, y, alpha);
+}
+
+#[then(regex = r"^alpha_at\((c|d), (\d+), (\d+)\) = (\d+\.?\d*)$")]
+fn check_alpha_at(world: &mut CanvasWorld, canvas: String, x: usize, y: usize, alpha: f64) {
+  let canvas = if canvas == "c" { &world.c } else { &world.d };
+  assert_approx_eq!(canvas.get_alpha_at(x, y), alpha, 1e-9);
+}
+
+#[given(regex = r"^d ← canvas\((\d+), (\d+)\)$")]
+fn set_d(world: &mut CanvasWorld, width: usize, height: usize) {
+  world.d = Canvas::new(width, height);
+}
+
+#[given(regex = r"^d ← a copy of c$")]
+fn set_d_to_copy(world: &mut CanvasWorld) {
+  world.d = world.c.clone();
+}
+
+#[then(regex = r"^compare\(c, d\)\.(mean_absolute_error|rmse|psnr|max_error) = (\d+\.?\d*)$")]
+fn check_comparison(world: &mut CanvasWorld, metric: String, value: f64) {
+  let diff = world.c.compare(&world.d).unwrap();
+  let actual = match metric.as_str() {
+    "mean_absolute_error" => diff.mean_absolute_error,
+    "rmse" => world.c.rmse(&world.d).unwrap(),
+    "psnr" => world.c.psnr(&world.d).unwrap(),
+    "max_error" => world.c.max_error(&world.d).unwrap(),
+    _ => panic!("Unknown metric: {}", metric),
+  };
+  assert_approx_eq!(actual, value, 1e-5);
+}
+
+#[then(regex = r"^compare\(c, d\)\.psnr is infinite$")]
+fn check_psnr_infinite(world: &mut CanvasWorld) {
+  assert_eq!(world.c.compare(&world.d).unwrap().psnr, f64::INFINITY);
+}
+
+#[then(regex = r"^compare\(c, d\), abs_diff\(c, d\) and diff_visualization\(c, d\) are nothing$")]
+fn check_comparison_fails(world: &mut CanvasWorld) {
+  assert_eq!(world.c.compare(&world.d), None);
+  assert_eq!(world.c.rmse(&world.d), None);
+  assert!(world.c.abs_diff(&world.d).is_none());
+  assert!(world.c.diff_visualization(&world.d).is_none());
+}
+
+#[then(
+  regex = r"^pixel_at\((abs_diff|diff_visualization)\(c, d\), (\d+), (\d+)\) = color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$"
+)]
+fn check_diff_pixel(world: &mut CanvasWorld, function: String, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let diff = match function.as_str() {
+    "abs_diff" => world.c.abs_diff(&world.d),
+    _ => world.c.diff_visualization(&world.d),
+  }
+  .unwrap();
+  assert_eq!(diff.get_color_at(x, y), Color(r, g, b));
+}
+
+#[then(regex = r#"^canvas_to_ansi\(c, (\d+), (truecolor|256)\) = "(.*)"$"#)]
+fn canvas_to_ansi_is(world: &mut CanvasWorld, columns: usize, colors: String, expected: String) {
+  let colors = match colors.as_str() {
+    "truecolor" => TerminalColors::TrueColor,
+    _ => TerminalColors::Ansi256,
+  };
+  let expected = expected.replace("\\e", "\x1b").replace("\\n", "\n");
+  assert_eq!(world.c.to_ansi(columns, colors), expected);
+}
+
+#[then(regex = r"^ansi256\((\d+), (\d+), (\d+)\) = (\d+)$")]
+fn ansi256_is(_world: &mut CanvasWorld, r: u8, g: u8, b: u8, index: u8) {
+  assert_eq!(ansi256([r, g, b]), index);
+}
+
+/// Assert that two canvases are the same size and their pixels and alpha are
+/// within `epsilon` of each other.
+fn assert_canvases_near(a: &Canvas, b: &Canvas, epsilon: f64) {
+  assert_eq!((a.width, a.height), (b.width, b.height));
+  for (i, (p, q)) in a.pixels.iter().zip(b.pixels.iter()).enumerate() {
+    assert!(
+      (p.0 - q.0).abs() < epsilon && (p.1 - q.1).abs() < epsilon && (p.2 - q.2).abs() < epsilon,
+      "pixel {}: {:?} != {:?}",
+      i,
+      p,
+      q
+    );
+  }
+  for (p, q) in a.alpha.iter().zip(b.alpha.iter()) {
+    assert_approx_eq!(p, q, epsilon);
+  }
+}
+
+fn resize_filter(name: &str) -> ResizeFilter {
+  match name {
+    "nearest" => ResizeFilter::Nearest,
+    "bilinear" => ResizeFilter::Bilinear,
+    "lanczos3" => ResizeFilter::Lanczos3,
+    _ => panic!("Unknown filter: {}", name),
+  }
+}
+
+fn parse_values(text: &str) -> Vec<f64> {
+  text.split(',').map(|value| value.trim().parse().unwrap()).collect()
+}
+
+#[given(regex = r"^c ← numbered canvas\((\d+), (\d+)\)$")]
+fn set_numbered_canvas(world: &mut CanvasWorld, width: usize, height: usize) {
+  world.c = Canvas::new(width, height);
+  for y in 0..height {
+    for x in 0..width {
+      world.c.set_color_at(x, y, Color(x as f64, y as f64, 0.0));
+    }
+  }
+}
+
+#[given(regex = r"^c ← gray canvas\((\d+), (\d+)\) with values \[(.*)\]$")]
+fn set_gray_canvas(world: &mut CanvasWorld, width: usize, height: usize, values: String) {
+  world.c = Canvas::new(width, height);
+  for (i, value) in parse_values(&values).into_iter().enumerate() {
+    world.c.set_color_at(i % width, i / width, Color(value, value, value));
+  }
+}
+
+#[when(regex = r"^d ← crop\(c, (\d+), (\d+), (\d+), (\d+)\)$")]
+fn crop(world: &mut CanvasWorld, x: usize, y: usize, width: usize, height: usize) {
+  world.d = world.c.crop(x, y, width, height);
+}
+
+#[when(regex = r"^d ← (flip_horizontal|flip_vertical|rotate_90|rotate_180|rotate_270)\(c\)$")]
+fn flip_or_rotate(world: &mut CanvasWorld, operation: String) {
+  world.d = match operation.as_str() {
+    "flip_horizontal" => world.c.flip_horizontal(),
+    "flip_vertical" => world.c.flip_vertical(),
+    "rotate_90" => world.c.rotate_90(),
+    "rotate_180" => world.c.rotate_180(),
+    _ => world.c.rotate_270(),
+  };
+}
+
+#[when(regex = r"^d ← rotate_90\(rotate_90\(rotate_90\(rotate_90\(c\)\)\)\)$")]
+fn rotate_four_times(world: &mut CanvasWorld) {
+  world.d = world.c.rotate_90().rotate_90().rotate_90().rotate_90();
+}
+
+#[then(regex = r"^d = c$")]
+fn d_is_c(world: &mut CanvasWorld) {
+  assert_eq!(world.d, world.c);
+}
+
+#[when(regex = r"^d ← resize\(c, (\d+), (\d+), (nearest|bilinear|lanczos3)\)$")]
+fn resize(world: &mut CanvasWorld, width: usize, height: usize, filter: String) {
+  world.d = world.c.resize(width, height, resize_filter(&filter));
+}
+
+#[then(regex = r"^row (\d+) of d is gray \[(.*)\]$")]
+fn row_is_gray(world: &mut CanvasWorld, y: usize, values: String) {
+  let values = parse_values(&values);
+  assert_eq!(world.d.width, values.len());
+  for (x, value) in values.into_iter().enumerate() {
+    let color = world.d.get_color_at(x, y);
+    assert_approx_eq!(color.0, value, 1e-9);
+    assert_approx_eq!(color.1, value, 1e-9);
+    assert_approx_eq!(color.2, value, 1e-9);
+  }
+}
+
+#[then(regex = r"^every pixel of d ≈ color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\)$")]
+fn every_pixel_of_d_near(world: &mut CanvasWorld, r: f64, g: f64, b: f64) {
+  let mut expected = Canvas::new(world.d.width, world.d.height);
+  expected.pixels.iter_mut().for_each(|pixel| *pixel = Color(r, g, b));
+  expected.alpha = world.d.alpha.clone();
+  assert_canvases_near(&world.d, &expected, 1e-9);
+}
+
+#[then(regex = r"^every pixel of d has alpha ≈ (\d+\.?\d*)$")]
+fn every_alpha_of_d_near(world: &mut CanvasWorld, alpha: f64) {
+  for a in &world.d.alpha {
+    assert_approx_eq!(a, alpha, 1e-9);
+  }
+}
+
+#[then(regex = r"^d ≈ c$")]
+fn d_is_near_c(world: &mut CanvasWorld) {
+  assert_canvases_near(&world.d, &world.c, 1e-9);
+}
+
+#[when(
+  regex = r"^every pixel of (c|d) is set to color\((-?\d+.?\d*), (-?\d+.?\d*), (-?\d+.?\d*)\) with alpha (\d+\.?\d*)$"
+)]
+fn set_every_pixel_with_alpha(world: &mut CanvasWorld, canvas: String, r: f64, g: f64, b: f64, alpha: f64) {
+  let canvas = if canvas == "c" { &mut world.c } else { &mut world.d };
+  canvas.pixels.iter_mut().for_each(|pixel| *pixel = Color(r, g, b));
+  canvas.alpha.iter_mut().for_each(|a| *a = alpha);
+}
+
+#[when(regex = r"^d is (composited over|blitted onto) c at \((-?\d+), (-?\d+)\)$")]
+fn combine(world: &mut CanvasWorld, how: String, x: isize, y: isize) {
+  if how == "composited over" {
+    world.c.composite(&world.d, x, y);
+  } else {
+    world.c.blit(&world.d, x, y);
+  }
+}
+
+#[when(regex = r"^d ← convolve\(c, (.+)\)$")]
+fn convolve(world: &mut CanvasWorld, kernel: String) {
+  let kernel = if let Some(radius) = kernel.strip_prefix("box_blur(") {
+    Kernel::box_blur(radius.trim_end_matches(')').parse().unwrap())
+  } else if kernel == "sharpen" {
+    Kernel::sharpen()
+  } else if let Some(rows) = kernel.strip_prefix("kernel [[") {
+    let rows = rows.trim_end_matches("]]").split("], [").map(parse_values).collect();
+    Kernel::from_rows(rows)
+  } else {
+    panic!("Unknown kernel: {}", kernel);
+  };
+  world.d = world.c.convolve(&kernel);
+}
+
+#[then(regex = r"^the weights of gaussian\((\d+\.?\d*)\) sum to 1$")]
+fn gaussian_sums_to_one(_world: &mut CanvasWorld, sigma: f64) {
+  assert_approx_eq!(Kernel::gaussian(sigma).weights.iter().sum::<f64>(), 1.0, 1e-12);
+}
+
+#[then(regex = r"^gaussian\((\d+\.?\d*)\) is (\d+)x(\d+)$")]
+fn gaussian_size(_world: &mut CanvasWorld, sigma: f64, width: usize, height: usize) {
+  let kernel = Kernel::gaussian(sigma);
+  assert_eq!((kernel.width, kernel.height), (width, height));
+}
+
+#[then(regex = r"^gaussian\((\d+\.?\d*)\) = transpose\(gaussian\((\d+\.?\d*)\)\)$")]
+fn gaussian_is_symmetric(_world: &mut CanvasWorld, sigma: f64, _same: f64) {
+  let kernel = Kernel::gaussian(sigma);
+  assert_eq!(kernel.transpose(), kernel);
+}
+
+#[when(regex = r"^d ← gaussian_blur\(c, (\d+\.?\d*)\)$")]
+fn gaussian_blur(world: &mut CanvasWorld, sigma: f64) {
+  world.d = world.c.gaussian_blur(sigma);
+}
+
+#[then(regex = r"^d ≈ convolve\(c, gaussian\((\d+\.?\d*)\)\)$")]
+fn d_is_near_gaussian(world: &mut CanvasWorld, sigma: f64) {
+  assert_canvases_near(&world.d, &world.c.convolve(&Kernel::gaussian(sigma)), 1e-9);
+}
+
+/// The sum of every channel of every pixel.
+fn total_light(canvas: &Canvas) -> f64 {
+  canvas.pixels.iter().map(|color| color.0 + color.1 + color.2).sum()
+}
+
+#[when(regex = r"^d ← bright_pass\(c, (\d+\.?\d*)\)$")]
+fn bright_pass(world: &mut CanvasWorld, threshold: f64) {
+  world.d = world.c.bright_pass(threshold);
+}
+
+#[when(regex = r"^d ← bloom\(c\) with threshold (\d+\.?\d*) and intensity (\d+\.?\d*)$")]
+fn bloom(world: &mut CanvasWorld, threshold: f64, intensity: f64) {
+  let bloom = Bloom::default().with_threshold(threshold).with_intensity(intensity);
+  world.d = world.c.bloom(&bloom);
+}
+
+#[then(regex = r"^pixel_at\(d, (\d+), (\d+)\) is at least color\((\d+\.?\d*), (\d+\.?\d*), (\d+\.?\d*)\)$")]
+fn pixel_is_at_least(world: &mut CanvasWorld, x: usize, y: usize, r: f64, g: f64, b: f64) {
+  let color = world.d.get_color_at(x, y);
+  assert!(color.0 >= r && color.1 >= g && color.2 >= b, "{:?}", color);
+}
+
+#[then(regex = r"^pixel_at\(d, (\d+), (\d+)\) glows red$")]
+fn pixel_glows_red(world: &mut CanvasWorld, x: usize, y: usize) {
+  let color = world.d.get_color_at(x, y);
+  assert!(color.0 > 0.0 && color.1 == 0.0 && color.2 == 0.0, "{:?}", color);
+}
+
+#[then(regex = r"^the light d adds to c is within (\d+)% of (\d+\.?\d*) × the light in bright_pass\(c, (\d+\.?\d*)\)$")]
+fn glow_carries_light(world: &mut CanvasWorld, percent: f64, intensity: f64, threshold: f64) {
+  let added = total_light(&world.d) - total_light(&world.c);
+  let expected = intensity * total_light(&world.c.bright_pass(threshold));
+  assert!(
+    (added - expected).abs() <= expected * percent / 100.0,
+    "added {}, expected {}",
+    added,
+    expected
+  );
+}
+
 // This runs before everything else, so you can setup things here.
 fn main() {
   futures::executor::block_on(CanvasWorld::run("tests/features/canvas.feature"));
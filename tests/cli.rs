@@ -0,0 +1,146 @@
+#![allow(clippy::too_many_arguments)]
+use cucumber::gherkin::Step;
+use cucumber::{given, then, when, World};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use sunhouse::canvas::Canvas;
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+// `CliWorld` is your shared, likely mutable state.
+// Cucumber constructs it via `Default::default()` for each scenario.
+#[derive(Debug, World)]
+pub struct CliWorld {
+  dir: PathBuf,
+  output: Option<Output>,
+}
+
+impl Default for CliWorld {
+  /// Give each scenario a fresh directory to run the binary in.
+  fn default() -> Self {
+    let dir = std::env::temp_dir().join(format!(
+      "sunhouse-cli-{}-{}",
+      std::process::id(),
+      NEXT_DIR.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    CliWorld { dir, output: None }
+  }
+}
+
+impl Drop for CliWorld {
+  /// Clean up the scenario's directory and everything written into it.
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.dir);
+  }
+}
+
+impl CliWorld {
+  fn output(&self) -> &Output {
+    self.output.as_ref().expect("sunhouse hasn't been run")
+  }
+
+  fn stderr(&self) -> String {
+    String::from_utf8_lossy(&self.output().stderr).into_owned()
+  }
+}
+
+#[given(regex = r"^a scene file (\S+) containing$")]
+fn scene_file(world: &mut CliWorld, name: String, step: &Step) {
+  let docstring = step.docstring.as_ref().expect("No docstring found");
+  // The docstring starts with the newline after its opening quotes.
+  let contents = docstring.strip_prefix('\n').unwrap_or(docstring);
+  std::fs::write(world.dir.join(name), contents).unwrap();
+}
+
+#[when(regex = r#"^sunhouse is run with "(.*)"$"#)]
+fn run_sunhouse(world: &mut CliWorld, args: String) {
+  let output = Command::new(env!("CARGO_BIN_EXE_sunhouse"))
+    .args(args.split_whitespace())
+    .current_dir(&world.dir)
+    .output()
+    .unwrap();
+  world.output = Some(output);
+}
+
+#[then(regex = r"^the command succeeds$")]
+fn command_succeeds(world: &mut CliWorld) {
+  assert!(world.output().status.success(), "sunhouse failed: {}", world.stderr());
+}
+
+#[then(regex = r"^the command fails$")]
+fn command_fails(world: &mut CliWorld) {
+  assert!(!world.output().status.success());
+}
+
+#[then(regex = r#"^stderr contains "(.*)"$"#)]
+fn stderr_contains(world: &mut CliWorld, text: String) {
+  let stderr = world.stderr();
+  assert!(stderr.contains(&text), "{:?} not found in {:?}", text, stderr);
+}
+
+#[then(regex = r"^(\S+) is a (\d+)×(\d+) (PNG|PPM)$")]
+fn image_has_size(world: &mut CliWorld, name: String, width: usize, height: usize, format: String) {
+  let path = world.dir.join(name);
+  let canvas = match format.as_str() {
+    "PNG" => Canvas::load_png(&path).unwrap(),
+    _ => Canvas::load_ppm(&path).unwrap(),
+  };
+  assert_eq!((canvas.width, canvas.height), (width, height));
+}
+
+#[then(regex = r"^the top left pixel of (\S+) is transparent$")]
+fn top_left_is_transparent(world: &mut CliWorld, name: String) {
+  let canvas = Canvas::load_png(&world.dir.join(name)).unwrap();
+  assert_eq!(canvas.get_alpha_at(0, 0), 0.0);
+}
+
+#[then(regex = r"^(\S+) has the EXR channels (.*)$")]
+fn exr_has_channels(world: &mut CliWorld, name: String, expected: String) {
+  let bytes = std::fs::read(world.dir.join(name)).unwrap();
+  let tag = b"channels\0chlist\0";
+  let start = bytes
+    .windows(tag.len())
+    .position(|w| w == tag)
+    .expect("No channel list")
+    + tag.len()
+    + 4;
+  // Each channel is a name followed by 16 bytes of layout.
+  let mut names = vec![];
+  let mut at = start;
+  while bytes[at] != 0 {
+    let end = at + bytes[at..].iter().position(|b| *b == 0).unwrap();
+    names.push(String::from_utf8_lossy(&bytes[at..end]).into_owned());
+    at = end + 1 + 16;
+  }
+  assert_eq!(names.join(", "), expected);
+}
+
+#[then(regex = r"^(\S+) differs from (\S+)$")]
+fn images_differ(world: &mut CliWorld, a: String, b: String) {
+  let a = Canvas::load_png(&world.dir.join(a)).unwrap();
+  let b = Canvas::load_png(&world.dir.join(b)).unwrap();
+  assert!(a.max_error(&b).unwrap() > 0.0);
+}
+
+#[then(regex = r"^stdout is a (truecolor|256-color) preview (\d+) characters wide and (\d+) lines high$")]
+fn stdout_is_preview(world: &mut CliWorld, colors: String, width: usize, height: usize) {
+  let stdout = String::from_utf8_lossy(&world.output().stdout).into_owned();
+  let escape = if colors == "truecolor" {
+    "\x1b[38;2;"
+  } else {
+    "\x1b[38;5;"
+  };
+  let lines: Vec<&str> = stdout.lines().collect();
+  assert_eq!(lines.len(), height, "{:?}", stdout);
+  for line in lines {
+    assert_eq!(line.matches('▀').count(), width, "{:?}", line);
+    assert_eq!(line.matches(escape).count(), width, "{:?}", line);
+  }
+}
+
+// This runs before everything else, so you can setup things here.
+fn main() {
+  futures::executor::block_on(CliWorld::run("tests/features/cli.feature"));
+}
@@ -0,0 +1,69 @@
+Feature: Arbitrary Output Variables
+
+Scenario: Rendering output variables for a hit
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then depth at (5, 5) = color(4, 4, 4)
+    And normal at (5, 5) = color(0, 0, -1)
+    And albedo at (5, 5) = color(0.8, 1, 0.6)
+    And object_id at (5, 5) = color(1, 1, 1)
+    And material_id at (5, 5) = color(1, 1, 1)
+    And shadow at (5, 5) = color(0, 0, 0)
+    And position at (5, 5) = color(0, 0, -1)
+
+Scenario: Rendering output variables for a miss
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then depth at (0, 0) is infinite
+    And normal at (0, 0) = color(0, 0, 0)
+    And albedo at (0, 0) = color(0, 0, 0)
+    And object_id at (0, 0) = color(0, 0, 0)
+    And material_id at (0, 0) = color(0, 0, 0)
+    And position at (0, 0) = color(0, 0, 0)
+
+Scenario: The color buffer matches a normal render
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with depth
+  Then the color of buffers = render(c, w)
+    And buffers has a depth buffer
+    And buffers has no normal buffer
+
+Scenario: Objects that share a material share a material ID
+  Given w ← two spheres side by side sharing a material
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then object_id at (3, 5) = color(1, 1, 1)
+    And object_id at (7, 5) = color(2, 2, 2)
+    And material_id at (3, 5) = color(1, 1, 1)
+    And material_id at (7, 5) = color(1, 1, 1)
+
+Scenario: Objects with different materials have different material IDs
+  Given w ← two spheres side by side with different materials
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then material_id at (3, 5) = color(1, 1, 1)
+    And material_id at (7, 5) = color(2, 2, 2)
+
+Scenario: The shadow mask marks surfaces in shadow
+  Given w ← a sphere floating above a floor
+    And c ← camera(11, 11, π/2) looking from point(0, 1, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then shadow at (5, 5) = color(1, 1, 1)
+    And shadow at (5, 10) = color(0, 0, 0)
+
+Scenario: Output variables are exported as named EXR channels
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with all variables
+  Then the EXR channels of buffers are "Z, N.X, N.Y, N.Z, albedo.R, albedo.G, albedo.B, objectId, materialId, shadow, P.X, P.Y, P.Z"
+    And the EXR channel Z of buffers at (5, 5) = 4
+
+Scenario: Output variables are exported to separate files
+  Given w ← default_world()
+    And c ← camera(5, 5, π/2) looking from point(0, 0, -5) to point(0, 0, 0)
+  When buffers ← render_aovs(c, w) with depth
+    And buffers are saved as EXR files
+  Then the EXR files "color.exr, depth.exr" were written
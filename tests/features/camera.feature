@@ -46,3 +46,494 @@ Scenario: Rendering a world with a camera
     And c.transform ← view_transform(from, to, up)
   When image ← render(c, w)
   Then pixel_at(image, 5, 5) = color(0.38066, 0.47583, 0.2855)
+
+Scenario: Rendering a world with a transparent background
+  Given w ← default_world()
+    And w.background ← transparent
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 5, 5) = color(0.38066, 0.47583, 0.2855)
+    And alpha_at(image, 5, 5) = 1
+    And pixel_at(image, 0, 0) = color(0.0, 0.0, 0.0)
+    And alpha_at(image, 0, 0) = 0
+
+Scenario: Rendering on several threads matches rendering on one
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render_parallel(c, w, 3)
+  Then pixel_at(image, 5, 5) = color(0.38066, 0.47583, 0.2855)
+    And image matches render(c, w)
+
+Scenario: A render job splits the canvas into tiles
+  Given c ← camera(20, 10, π/2)
+    And w ← default_world()
+  When job ← render_job(c, w) with tiles of 8 on 1 thread
+  Then job has 6 tiles
+    And tile 5 of job is at (16, 8) with size 4x2
+
+Scenario: A finished render job matches rendering in one go
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When job ← render_job(c, w) with tiles of 4 on 3 threads
+    And image ← run(job)
+  Then outcome.tiles_done = 9
+    And outcome is not cancelled
+    And 9 progress reports were made, the last at fraction 1
+    And image matches render(c, w)
+
+Scenario: Cancelling a render job keeps the tiles rendered so far
+  Given w ← default_world()
+    And c ← camera(20, 20, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When job ← render_job(c, w) with tiles of 5 on 1 thread
+    And image ← run(job), cancelling after 3 tiles
+  Then outcome.tiles_done = 3
+    And outcome is cancelled
+    And the first 3 tiles of image match render(c, w)
+    And the rest of image is black and uncovered
+
+Scenario: Cancelling a render job after its last tile doesn't cancel it
+  Given w ← default_world()
+    And c ← camera(20, 20, π/2)
+  When job ← render_job(c, w) with tiles of 5 on 1 thread
+    And image ← run(job), cancelling after 16 tiles
+  Then outcome.tiles_done = 16
+    And outcome is not cancelled
+
+Scenario: A render job cancelled before it starts renders nothing
+  Given w ← default_world()
+    And c ← camera(20, 20, π/2)
+  When job ← render_job(c, w) with tiles of 5 on 2 threads
+    And job is cancelled
+    And image ← run(job)
+  Then outcome.tiles_done = 0
+    And outcome is cancelled
+    And the rest of image is black and uncovered
+
+Scenario: Estimating the time left in a render job
+  Given progress ← 1 of 4 tiles done after 2 seconds
+  Then fraction(progress) = 0.25
+    And eta(progress) = 6 seconds
+
+Scenario: The first pass of a progressive render shows coarse blocks
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When progressive ← progressive_render(c, w) with blocks of 4
+    And progressive is refined 1 time
+  Then samples_taken(progressive) = 0
+    And every 4x4 block of image has the color of its corner in render(c, w)
+
+Scenario: A progressive render converges on the full render
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+    And c.aperture ← 0.1
+    And c.samples ← 4
+  When progressive ← progressive_render(c, w) with blocks of 4
+    And progressive is refined until done
+  Then progressive was refined 6 times
+    And samples_taken(progressive) = 4
+    And image matches render(c, w)
+
+Scenario: Rendering a region keeps the full-frame projection
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render_region(c, w, 3, 4, 5, 2)
+  Then image is 5x2
+    And image matches render(c, w) from (3, 4)
+
+Scenario: A region is clipped to the frame
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+  When image ← render_region(c, w, 8, 9, 10, 10)
+  Then image is 3x2
+    And image matches render(c, w) from (8, 9)
+
+Scenario: Tracing a pixel gives the color it renders
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When trace ← trace_pixel(c, w, 5, 5)
+  Then trace.samples.count = 1
+    And trace.samples[0].hit is the object 0 of w
+    And trace.color = pixel_at(render(c, w), 5, 5)
+
+Scenario: Tracing a pixel through a lens traces every sample
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+    And c.aperture ← 0.1
+    And c.samples ← 4
+  When trace ← trace_pixel(c, w, 4, 5)
+  Then trace.samples.count = 4
+    And trace.color = pixel_at(render(c, w), 4, 5)
+
+Scenario: Tracing a pixel out of view traces nothing
+  Given w ← default_world()
+    And c ← fisheye_camera(11, 11, 180°)
+  When trace ← trace_pixel(c, w, 0, 0)
+  Then trace.samples.count = 0
+    And trace.color = pixel_at(render(c, w), 0, 0)
+
+Scenario: Pixels outside a fisheye image circle are uncovered
+  Given w ← default_world()
+    And c ← fisheye_camera(11, 11, 180°)
+  When image ← render(c, w)
+  Then alpha_at(image, 0, 0) = 0
+    And alpha_at(image, 5, 5) = 1
+
+Scenario: Rendering a PNG into a nonexistent directory fails
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+  Then render_png(c, w) into a nonexistent directory fails with an I/O error
+
+Scenario: Constructing an orthographic camera
+  Given c ← orthographic_camera(200, 100, 4)
+  Then c.hsize = 200
+    And c.vsize = 100
+    And c.pixel_size = 0.02
+    And c.projection = orthographic
+
+Scenario: An orthographic ray through the center of the canvas
+  Given c ← orthographic_camera(201, 101, 2.01)
+  When r ← ray_for_pixel(c, 100, 50)
+  Then r.origin = point(0, 0, 0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: An orthographic ray through the top-left corner of the canvas
+  Given c ← orthographic_camera(200, 100, 4)
+  When r ← ray_for_pixel(c, 0, 0)
+  Then r.origin = point(1.99, 0.99, 0.0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: An orthographic ray through the bottom-right corner of the canvas
+  Given c ← orthographic_camera(200, 100, 4)
+  When r ← ray_for_pixel(c, 199, 99)
+  Then r.origin = point(-1.99, -0.99, 0.0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: Constructing an orthographic ray when the camera is transformed
+  Given c ← orthographic_camera(201, 101, 2.01)
+  When c.transform ← rotation_y(π/4) * translation(0, -2, 5)
+    And r ← ray_for_pixel(c, 100, 50)
+  Then r.origin = point(0, 2, -5)
+    And r.direction = vector(√2/2, 0, -√2/2)
+
+Scenario: An equirectangular ray through the center of the canvas
+  Given c ← equirectangular_camera(201, 101)
+  When r ← ray_for_pixel(c, 100, 50)
+  Then r.origin = point(0, 0, 0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: An equirectangular ray through a corner of the canvas
+  Given c ← equirectangular_camera(4, 2)
+  When r ← ray_for_pixel(c, 0, 0)
+  Then r.origin = point(0, 0, 0)
+    And r.direction = vector(0.5, 0.70711, 0.5)
+
+Scenario: An equirectangular ray at the left edge of the seam
+  Given c ← equirectangular_camera(360, 181)
+  When r ← ray_for_pixel(c, 0, 90)
+  Then r.direction = vector(0.00873, 0.0, 0.99996)
+
+Scenario: An equirectangular ray at the right edge of the seam
+  Given c ← equirectangular_camera(360, 181)
+  When r ← ray_for_pixel(c, 359, 90)
+  Then r.direction = vector(-0.00873, 0.0, 0.99996)
+
+Scenario: A fisheye ray through the center of the canvas
+  Given c ← fisheye_camera(101, 101, 180°)
+  When r ← ray_for_pixel(c, 50, 50)
+  Then r.origin = point(0, 0, 0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: A 180° fisheye ray near the edge of the image circle
+  Given c ← fisheye_camera(101, 101, 180°)
+  When r ← ray_for_pixel(c, 0, 50)
+  Then r.direction = vector(0.99988, 0.0, -0.01555)
+
+Scenario: A 180° fisheye ray near the top of the image circle
+  Given c ← fisheye_camera(101, 101, 180°)
+  When r ← ray_for_pixel(c, 50, 0)
+  Then r.direction = vector(0.0, 0.99988, -0.01555)
+
+Scenario: A 360° fisheye ray near the edge of the image circle looks backward
+  Given c ← fisheye_camera(101, 101, 360°)
+  When r ← ray_for_pixel(c, 100, 50)
+  Then r.direction = vector(-0.0311, 0.0, 0.99952)
+
+Scenario: The corners of a fisheye canvas are out of view
+  Given c ← fisheye_camera(101, 101, 180°)
+  Then pixel (50, 50) of c is in view
+    And pixel (0, 50) of c is in view
+    And pixel (0, 0) of c is out of view
+
+Scenario: Rendering with a fisheye camera leaves the corners black
+  Given w ← default_world()
+    And c ← fisheye_camera(11, 11, 180°)
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 0, 0) = color(0.0, 0.0, 0.0)
+    And pixel_at(image, 5, 5) = color(0.38066, 0.47583, 0.2855)
+
+Scenario: A camera is a pinhole by default
+  Given c ← camera(201, 101, π/2)
+  Then c.aperture = 0.0
+    And c.samples = 1
+
+Scenario: A zero aperture reproduces the pinhole ray exactly
+  Given c ← camera(201, 101, π/2)
+  When c.transform ← rotation_y(π/4) * translation(0, -2, 5)
+  Then ray_for_lens_sample(c, 0, 0, 0.25, 0.75) = ray_for_pixel(c, 0, 0)
+    And ray_for_lens_sample(c, 100, 50, 0.9, 0.1) = ray_for_pixel(c, 100, 50)
+
+Scenario: A ray through the center of the lens matches the pinhole ray
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+    And c.focal_distance ← 10
+  When r ← ray_for_lens_sample(c, 100, 50, 0.5, 0.5)
+  Then r.origin = point(0, 0, 0)
+    And r.direction = vector(0, 0, -1)
+
+Scenario: A single sample looks through the center of the lens
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+    And c.focal_distance ← 10
+    And c.samples ← 1
+  When c.transform ← rotation_y(π/4) * translation(0, -2, 5)
+  Then ray_for_sample(c, 0, 0, 0) = ray_for_pixel(c, 0, 0)
+    And ray_for_sample(c, 100, 50, 0) = ray_for_pixel(c, 100, 50)
+
+Scenario: A ray through the edge of the lens is aimed at the focal plane
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+    And c.focal_distance ← 10
+  When r ← ray_for_lens_sample(c, 100, 50, 1.0, 0.5)
+  Then r.origin = point(0.5, 0.0, 0.0)
+    And r.direction = vector(-0.04994, 0.0, -0.99875)
+
+Scenario: Every ray through the lens meets the pinhole ray on the focal plane
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+    And c.focal_distance ← 10
+  When c.transform ← rotation_y(π/4) * translation(0, -2, 5)
+  Then every lens ray for pixel (0, 0) of c converges on the focal plane
+
+Scenario: A circular aperture keeps lens points within its radius
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+  Then every lens point of c lies within the aperture
+
+Scenario: A polygonal aperture keeps lens points within its blades
+  Given c ← camera(201, 101, π/2)
+    And c.aperture ← 0.5
+    And c.aperture_blades ← 4
+  Then every lens point of c lies within the aperture
+
+Scenario: The shutter is instantaneous by default
+  Given c ← camera(201, 101, π/2)
+  Then c.shutter_open = 0.0
+    And c.shutter_close = 0.0
+
+Scenario: Sample times fall within the shutter interval
+  Given c ← camera(201, 101, π/2)
+    And c.shutter_open ← 0.25
+    And c.shutter_close ← 0.75
+  Then time_for_sample(c, 0) = 0.25
+    And every sample time of c falls within the shutter interval
+
+Scenario: Rendering a still world with an open shutter matches an instant render
+  Given w ← default_world()
+    And c ← camera(11, 11, π/2)
+    And c.shutter_close ← 1.0
+    And c.samples ← 8
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 5, 5) = color(0.38066, 0.47583, 0.2855)
+
+Scenario: Rendering a moving world blurs it
+  Given w ← default_world()
+    And every object in w moves by translation(2, 0, 0)
+    And c ← camera(11, 11, π/2)
+    And c.shutter_close ← 1.0
+    And c.samples ← 16
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 5, 5) is dimmer than color(0.38066, 0.47583, 0.2855)
+    And pixel_at(image, 5, 5) is not black
+    And pixel_at(image, 5, 5) = color(0.29804, 0.37255, 0.22353)
+
+Scenario: A short shutter blurs a moving world less than a long one
+  Given w ← default_world()
+    And every object in w moves by translation(2, 0, 0)
+    And c ← camera(11, 11, π/2)
+    And c.shutter_close ← 0.01
+    And c.samples ← 16
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 5, 5) is brighter than color(0.29804, 0.37255, 0.22353)
+
+Scenario: A shutter that opens after the motion ends sees the shapes where they stopped
+  Given w ← default_world()
+    And every object in w moves by translation(2, 0, 0)
+    And c ← camera(11, 11, π/2)
+    And c.shutter_open ← 1.0
+    And c.shutter_close ← 2.0
+    And c.samples ← 16
+    And from ← point(0, 0, -5)
+    And to ← point(0, 0, 0)
+    And up ← vector(0, 1, 0)
+    And c.transform ← view_transform(from, to, up)
+  When image ← render(c, w)
+  Then pixel_at(image, 5, 5) = color(0.0, 0.0, 0.0)
+    And pixel_at(image, 8, 5) is not black
+
+Scenario: Resizing a camera keeps its projection, view and lens
+  Given c ← orthographic_camera(200, 100, 4)
+    And c.aperture ← 0.5
+    And c.samples ← 8
+  When c.transform ← rotation_y(π/4) * translation(0, -2, 5)
+    And c ← with_resolution(c, 100, 100)
+  Then c.projection = orthographic
+    And c.hsize = 100
+    And c.vsize = 100
+    And c.pixel_size = 0.04
+    And c.aperture = 0.5
+    And c.samples = 8
+    And c.transform = rotation_y(π/4) * translation(0, -2, 5)
+
+Scenario: Building a camera from a position and a target
+  Given from ← point(1, 3, 2)
+    And to ← point(4, -2, 8)
+    And up ← vector(1, 1, 0)
+  When c ← build(camera_builder(160, 120, π/2) from from to to with up)
+  Then c.hsize = 160
+    And c.vsize = 120
+    And c.field_of_view = π/2
+    And c.transform = view_transform(from, to, up)
+
+Scenario: Recovering a camera builder from a camera
+  Given from ← point(1, 3, 2)
+    And to ← point(4, -2, 8)
+    And up ← vector(1, 1, 0)
+    And c ← camera(160, 120, π/2)
+    And c.transform ← view_transform(from, to, up)
+  When c ← build(camera_builder_from(c))
+  Then c.hsize = 160
+    And c.vsize = 120
+    And c.field_of_view = π/2
+    And c.transform ≈ view_transform(from, to, up)
+
+Scenario: A camera builder looks down -z from the origin by default
+  When c ← build(camera_builder(160, 120, π/2))
+  Then c.transform = identity_matrix
+
+Scenario: A linear track reproduces its keyframes
+  Given track ← linear camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And a keyframe at time 2 from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+  Then keyframe_at(track, 0) is from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And keyframe_at(track, 2) is from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+    And camera_at(track, 2) is from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+
+Scenario: A linear track interpolates halfway between keyframes
+  Given track ← linear camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And a keyframe at time 2 from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+  Then keyframe_at(track, 1) is from point(5, 0, -5) to point(0, 1, 0) seeing 75°
+    And camera_at(track, 1) is from point(5, 0, -5) to point(0, 1, 0) seeing 75°
+
+Scenario: A track holds its first and last keyframes outside its span
+  Given track ← linear camera track of 160x120
+    And a keyframe at time 1 from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And a keyframe at time 2 from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+  Then keyframe_at(track, 0) is from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And keyframe_at(track, 3) is from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+
+Scenario: A Catmull-Rom track reproduces its keyframes
+  Given track ← catmull_rom camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, 0) to point(0, 0, 10) seeing 60°
+    And a keyframe at time 1 from point(1, 1, 0) to point(0, 0, 10) seeing 70°
+    And a keyframe at time 2 from point(2, 0, 0) to point(0, 0, 10) seeing 80°
+    And a keyframe at time 3 from point(3, 3, 0) to point(0, 0, 10) seeing 60°
+  Then keyframe_at(track, 0) is from point(0, 0, 0) to point(0, 0, 10) seeing 60°
+    And keyframe_at(track, 1) is from point(1, 1, 0) to point(0, 0, 10) seeing 70°
+    And keyframe_at(track, 3) is from point(3, 3, 0) to point(0, 0, 10) seeing 60°
+
+Scenario: A Catmull-Rom track curves between keyframes
+  Given track ← catmull_rom camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, 0) to point(0, 0, 10) seeing 60°
+    And a keyframe at time 1 from point(1, 1, 0) to point(0, 0, 10) seeing 70°
+    And a keyframe at time 2 from point(2, 0, 0) to point(0, 0, 10) seeing 80°
+    And a keyframe at time 3 from point(3, 3, 0) to point(0, 0, 10) seeing 60°
+  Then keyframe_at(track, 1.5) is from point(1.5, 0.375, 0) to point(0, 0, 10) seeing 76.875°
+
+Scenario: A Catmull-Rom track keeps a steady speed across unevenly spaced keyframes
+  Given track ← catmull_rom camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, 0) to point(0, 0, 10) seeing 60°
+    And a keyframe at time 1 from point(1, 0, 0) to point(0, 0, 10) seeing 60°
+    And a keyframe at time 3 from point(3, 0, 0) to point(0, 0, 10) seeing 60°
+    And a keyframe at time 4 from point(4, 0, 0) to point(0, 0, 10) seeing 60°
+  Then keyframe_at(track, 1.5) is from point(1.5, 0, 0) to point(0, 0, 10) seeing 60°
+    And keyframe_at(track, 2.5) is from point(2.5, 0, 0) to point(0, 0, 10) seeing 60°
+
+Scenario: A track with no keyframes has no camera
+  Given track ← linear camera track of 160x120
+  When cameras ← cameras(track, 3)
+  Then keyframe_at(track, 1) is nothing
+    And camera_at(track, 1) is nothing
+    And cameras.count = 0
+
+Scenario: A track produces a camera for every frame
+  Given track ← linear camera track of 160x120
+    And a keyframe at time 0 from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And a keyframe at time 2 from point(10, 0, -5) to point(0, 2, 0) seeing 90°
+  When cameras ← cameras(track, 5)
+  Then cameras.count = 5
+    And cameras[0] is from point(0, 0, -5) to point(0, 0, 0) seeing 60°
+    And cameras[2] is from point(5, 0, -5) to point(0, 1, 0) seeing 75°
+    And cameras[4] is from point(10, 0, -5) to point(0, 2, 0) seeing 90°
@@ -6,6 +6,16 @@ Scenario: Creating a canvas
   And c.height = 20
   And every pixel of c is color(0, 0, 0)
 
+Scenario: A new canvas is fully covered
+  Given c ← canvas(3, 2)
+  Then every pixel of c has alpha 1
+
+Scenario: Writing alpha to a canvas
+  Given c ← canvas(3, 2)
+  When set_alpha(c, 1, 1, 0.25)
+  Then alpha_at(c, 1, 1) = 0.25
+    And alpha_at(c, 0, 1) = 1
+
 Scenario: Writing pixels to a canvas
   Given c ← canvas(10, 20)
   And red ← color(1, 0, 0)
@@ -38,7 +48,752 @@ Scenario: Constructing the PPM pixel data
   0 0 0 0 0 0 0 0 0 0 0 0 0 0 255
   """
 
+Scenario: Splitting long lines in PPM files
+  Given c ← canvas(10, 2)
+  When every pixel of c is set to color(1, 0.8, 0.6)
+  And ppm ← canvas_to_ppm(c)
+  Then lines 4-7 of ppm are
+  """
+  255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204
+  153 255 204 153 255 204 153 255 204 153 255 204 153
+  255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204
+  153 255 204 153 255 204 153 255 204 153 255 204 153
+  """
+
 Scenario: PPM files are terminated by a newline character
   Given c ← canvas(5, 3)
   When ppm ← canvas_to_ppm(c)
-  Then ppm ends with a newline character
\ No newline at end of file
+  Then ppm ends with a newline character
+
+Scenario Outline: Quantizing a channel for output
+  Then quantize(<value>, <transfer>, <bits>) = <sample>
+
+  Examples:
+    | value | transfer  | bits | sample |
+    | 0.5   | linear    | 8    | 128    |
+    | 0.5   | srgb      | 8    | 188    |
+    | 0.5   | gamma 2.2 | 8    | 186    |
+    | 0.2   | srgb      | 8    | 124    |
+    | 0.001 | srgb      | 8    | 3      |
+    | 1.5   | srgb      | 8    | 255    |
+    | -0.5  | srgb      | 8    | 0      |
+    | 0.5   | linear    | 16   | 32768  |
+    | 0.5   | srgb      | 16   | 48192  |
+    | 1.5   | linear    | 16   | 65535  |
+
+Scenario: Constructing the PPM header for 16-bit output
+  Given c ← canvas(5, 3)
+  When ppm ← canvas_to_ppm(c, linear, 16)
+  Then lines 1-3 of ppm are
+  """
+  P3
+  5 3
+  65535
+  """
+
+Scenario: Constructing sRGB-encoded PPM pixel data
+  Given c ← canvas(5, 3)
+  And c1 ← color(1.5, 0, 0)
+  And c2 ← color(0, 0.5, 0)
+  And c3 ← color(-0.5, 0, 1)
+  When write_pixel(c, 0, 0, c1)
+  And write_pixel(c, 2, 1, c2)
+  And write_pixel(c, 4, 2, c3)
+  And ppm ← canvas_to_ppm(c, srgb, 8)
+  Then lines 4-6 of ppm are
+  """
+  255 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+  0 0 0 0 0 0 0 188 0 0 0 0 0 0 0
+  0 0 0 0 0 0 0 0 0 0 0 0 0 0 255
+  """
+
+Scenario: 16-bit PNG data is written big-endian
+  Given c ← canvas(1, 1)
+  And c1 ← color(0.5, 1, 0)
+  When write_pixel(c, 0, 0, c1)
+  Then png_data(c, linear, 16) = [128, 0, 255, 255, 0, 0]
+    And png_data(c, srgb, 8) = [188, 255, 0]
+
+Scenario Outline: Tone mapping a gray
+  Then tone_map(<operator>, <value>) = <mapped>
+
+  Examples:
+    | operator            | value | mapped  |
+    | clamp               | 2.0   | 2.0     |
+    | reinhard            | 1.0   | 0.5     |
+    | reinhard            | 3.0   | 0.75    |
+    | reinhard_extended 4 | 4.0   | 1.0     |
+    | reinhard_extended 4 | 1.0   | 0.53125 |
+    | aces                | 1.0   | 0.80380 |
+    | aces                | 0.0   | 0.0     |
+
+Scenario: Tone mapping preserves the hue of a bright color
+  Then tone_map(reinhard, color(2, 1, 0)) is proportional to color(2, 1, 0)
+
+Scenario: Exposure is measured in stops
+  Given c ← canvas(1, 1)
+  And c1 ← color(0.25, 0.125, 1)
+  When write_pixel(c, 0, 0, c1)
+  Then png_data(c, linear, 8, exposure +1) = [128, 64, 255]
+    And png_data(c, linear, 8, exposure -1) = [32, 16, 128]
+
+Scenario: Tone mapping keeps highlights from clipping
+  Given c ← canvas(1, 1)
+  And c1 ← color(3, 3, 3)
+  When write_pixel(c, 0, 0, c1)
+  Then png_data(c, linear, 8, reinhard) = [191, 191, 191]
+
+Scenario: The log-average luminance of a uniform canvas is its luminance
+  Given c ← canvas(4, 4)
+  When every pixel of c is set to color(0.5, 0.5, 0.5)
+  Then log_average_luminance(c) = 0.5
+
+Scenario: Automatic exposure brings the log-average luminance to the key
+  Given c ← canvas(2, 2)
+  When every pixel of c is set to color(0.72, 0.72, 0.72)
+  Then png_data(c, linear, 8, auto exposure 0.18) = [46, 46, 46, 46, 46, 46, 46, 46, 46, 46, 46, 46]
+
+Scenario: Encoding a color as RGBE
+  Then color_to_rgbe(color(1, 0.5, 0.25)) = [128, 64, 32, 129]
+    And color_to_rgbe(color(0, 0, 0)) = [0, 0, 0, 0]
+    And color_to_rgbe(color(12, 3, -1)) = [192, 48, 0, 132]
+
+Scenario Outline: Channels RGBE can't hold are clamped
+  Then color_to_rgbe(color(<r>, <g>, <b>)) = [<rgbe>]
+
+  Examples:
+    | r     | g     | b   | rgbe             |
+    | inf   | 0     | 0   | 255, 0, 0, 255   |
+    | 1e300 | 1e300 | -1  | 255, 255, 0, 255 |
+    | NaN   | 1     | 0.5 | 0, 128, 64, 129  |
+    | NaN   | NaN   | NaN | 0, 0, 0, 0       |
+
+Scenario: Decoding an RGBE color
+  Then rgbe_to_color([128, 64, 32, 129]) = color(1.00390625, 0.50390625, 0.25390625)
+    And rgbe_to_color([0, 0, 0, 0]) = color(0, 0, 0)
+
+Scenario: A gradient canvas survives a round trip through a run-length encoded HDR file
+  Given c ← gradient canvas(32, 4) up to 10
+  When hdr ← canvas_to_hdr(c)
+    And d ← canvas_from_hdr(hdr)
+  Then d matches c within RGBE precision
+
+Scenario: A narrow canvas survives a round trip through a flat HDR file
+  Given c ← gradient canvas(3, 2) up to 4
+  When hdr ← canvas_to_hdr(c)
+    And d ← canvas_from_hdr(hdr)
+  Then d matches c within RGBE precision
+
+Scenario: Uniform scanlines are run-length encoded
+  Given c ← canvas(100, 1)
+  When hdr ← canvas_to_hdr(c)
+  Then hdr is smaller than 100 flat pixels
+
+Scenario: Parsing a hand-built HDR header
+  Given hdr ← the HDR header
+  """
+  #?RADIANCE
+  # Written by hand
+  FORMAT=32-bit_rle_rgbe
+  EXPOSURE=1.0
+
+  -Y 2 +X 1
+  """
+    And hdr continues with the bytes [128, 64, 32, 129, 0, 0, 0, 0]
+  When d ← canvas_from_hdr(hdr)
+  Then d.width = 1
+    And d.height = 2
+    And pixel_at(d, 0, 0) = color(1.00390625, 0.50390625, 0.25390625)
+    And pixel_at(d, 0, 1) = color(0, 0, 0)
+
+Scenario: Parsing an HDR file undoes its exposure
+  Given hdr ← the HDR header
+  """
+  #?RADIANCE
+  FORMAT=32-bit_rle_rgbe
+  EXPOSURE=4
+  EXPOSURE=0.5
+
+  -Y 1 +X 1
+  """
+    And hdr continues with the bytes [128, 64, 32, 129]
+  When d ← canvas_from_hdr(hdr)
+  Then pixel_at(d, 0, 0) = color(0.501953125, 0.251953125, 0.126953125)
+
+Scenario Outline: Parsing an HDR file whose resolution doesn't fit its data fails
+  Given hdr ← the HDR header
+  """
+  #?RADIANCE
+  FORMAT=32-bit_rle_rgbe
+
+  -Y <height> +X <width>
+  """
+    And hdr continues with the bytes [128, 64, 32, 129]
+  Then canvas_from_hdr(hdr) fails
+
+  Examples:
+    | width       | height      |
+    | 99999999999 | 99999999999 |
+    | 100000      | 100000      |
+    | 2           | 1           |
+
+Scenario: Parsing an HDR file with the wrong pixel format fails
+  Given hdr ← the HDR header
+  """
+  #?RADIANCE
+  FORMAT=32-bit_rle_xyze
+
+  -Y 1 +X 1
+  """
+    And hdr continues with the bytes [128, 64, 32, 129]
+  Then canvas_from_hdr(hdr) fails
+
+Scenario: Parsing a file that is not HDR fails
+  Given hdr ← the HDR header
+  """
+  P3
+  1 1
+  255
+  """
+  Then canvas_from_hdr(hdr) fails
+
+Scenario Outline: Converting floats to half precision
+  Then f32_to_half(<value>) = <bits>
+
+  Examples:
+    | value    | bits  |
+    | 0        | 0     |
+    | 1        | 15360 |
+    | -2       | 49152 |
+    | 0.5      | 14336 |
+    | 65504    | 31743 |
+    | 1000000  | 31744 |
+    | 0.000061 | 1023  |
+    | 0.00006  | 1007  |
+    | 2049     | 26624 |
+    | 2051     | 26626 |
+    | 2053     | 26626 |
+    | 2055     | 26628 |
+
+Scenario: An EXR file starts with the magic number and version
+  Given c ← canvas(5, 3)
+  When exr ← canvas_to_exr(c, half, none, rgb)
+  Then the first bytes of exr are [118, 47, 49, 1, 2, 0, 0, 0]
+
+Scenario: Constructing the EXR header
+  Given c ← canvas(5, 3)
+  When exr ← canvas_to_exr(c, half, zip, rgb)
+    And file ← read_exr(exr)
+  Then file.channels = "B:half, G:half, R:half"
+    And file.compression = 3
+    And file.dataWindow = [0, 0, 4, 2]
+    And file.displayWindow = [0, 0, 4, 2]
+    And file.lineOrder = 0
+    And file has the attributes "channels, compression, dataWindow, displayWindow, lineOrder, pixelAspectRatio, screenWindowCenter, screenWindowWidth"
+
+Scenario Outline: Writing pixel values to an EXR file
+  Given c ← gradient canvas(37, 21) up to 10
+  When exr ← canvas_to_exr(c, <type>, <compression>, rgb)
+    And file ← read_exr(exr)
+  Then file.compression = <code>
+    And the pixels of file match c within <type> precision
+
+  Examples:
+    | type  | compression | code |
+    | half  | none        | 0    |
+    | half  | rle         | 1    |
+    | half  | zips        | 2    |
+    | half  | zip         | 3    |
+    | float | none        | 0    |
+    | float | rle         | 1    |
+    | float | zips        | 2    |
+    | float | zip         | 3    |
+
+Scenario Outline: Compressing a flat EXR image
+  Given c ← canvas(64, 32)
+  When exr ← canvas_to_exr(c, half, <compression>, rgb)
+  Then exr is smaller than the uncompressed EXR
+
+  Examples:
+    | compression |
+    | rle         |
+    | zips        |
+    | zip         |
+
+Scenario: Writing an EXR file with alpha
+  Given c ← canvas(4, 2)
+  When set_alpha(c, 2, 1, 0.25)
+    And exr ← canvas_to_exr(c, float, none, rgba)
+    And file ← read_exr(exr)
+  Then file.channels = "A:float, B:float, G:float, R:float"
+    And channel A of file at (3, 1) = 1
+    And channel A of file at (2, 1) = 0.25
+
+Scenario: Writing depth and normals as extra EXR channels
+  Given c ← canvas(4, 2)
+  When exr ← canvas_to_exr(c, half, zips, rgb) with depth and normals
+    And file ← read_exr(exr)
+  Then file.channels = "B:half, G:half, N.X:half, N.Y:half, N.Z:half, R:half, Z:float"
+    And channel Z of file at (3, 1) = 7.25
+    And channel N.X of file at (3, 1) = 0
+    And channel N.Y of file at (3, 1) = 1
+    And channel N.Z of file at (0, 0) = -0.5
+
+Scenario: Writing an extra EXR channel of the wrong size fails
+  Given c ← canvas(4, 2)
+  Then canvas_to_exr(c, half, zips, rgb) with a 7-value depth channel fails
+
+Scenario Outline: Writing an EXR channel twice fails
+  Given c ← canvas(4, 2)
+  Then canvas_to_exr(c, half, zips, <channels>) with extra channels <names> fails
+
+  Examples:
+    | channels | names |
+    | rgb      | R     |
+    | rgba     | A     |
+    | rgb      | Z, Z  |
+
+Scenario: Writing an empty canvas to an EXR file fails
+  Given c ← canvas(0, 0)
+  Then canvas_to_exr(c, half, none, rgb) fails
+
+Scenario: Constructing a binary PPM file
+  Given c ← canvas(2, 1)
+  And c1 ← color(1.5, 0, 0)
+  And c2 ← color(0, 0.5, 1)
+  When write_pixel(c, 0, 0, c1)
+  And write_pixel(c, 1, 0, c2)
+  And p6 ← canvas_to_p6(c)
+  Then p6 starts with the header "P6\n2 1\n255\n"
+  And p6 continues with the bytes [255, 0, 0, 0, 128, 255]
+
+Scenario: Constructing a 16-bit binary PPM file
+  Given c ← canvas(3, 2)
+  When p6 ← canvas_to_p6(c, linear, 16)
+  Then p6 starts with the header "P6\n3 2\n65535\n"
+  And p6 continues with 36 bytes
+
+Scenario: Reading a plain PPM file with comments
+  Given ppm ← the PPM file
+  """
+  P3
+  # Written by hand
+  2 2 # width and height
+  100
+  100 50 0  0 0 0
+  # A comment between rows
+  0 0 0  25 100 75
+  """
+  When d ← canvas_from_ppm(ppm)
+  Then d.width = 2
+  And d.height = 2
+  And pixel_at(d, 0, 0) = color(1, 0.5, 0)
+  And pixel_at(d, 1, 0) = color(0, 0, 0)
+  And pixel_at(d, 1, 1) = color(0.25, 1, 0.75)
+
+Scenario Outline: Reading back a binary PPM file
+  Given c ← gradient canvas(9, 4) up to 1
+  When p6 ← canvas_to_p6(c, linear, <bits>)
+  And d ← canvas_from_p6(p6)
+  Then d matches c within <bits>-bit precision
+
+  Examples:
+    | bits |
+    | 8    |
+    | 16   |
+
+Scenario: Reading back a wrapped plain PPM file
+  Given c ← gradient canvas(30, 3) up to 1
+  When ppm ← canvas_to_ppm(c)
+  And d ← canvas_from_ppm(ppm)
+  Then d matches c within 8-bit precision
+
+Scenario Outline: Reading an invalid PPM file fails
+  Given ppm ← the PPM file
+  """
+  <magic>
+  1 1
+  <max>
+  <samples>
+  """
+  Then canvas_from_ppm(ppm) fails
+
+  Examples:
+    | magic | max | samples   |
+    | P2    | 255 | 0 0 0     |
+    | P3    | 255 | 0 0       |
+    | P3    | 255 | 0 256 0   |
+    | P3    | 0   | 0 0 0     |
+    | P3    | 255 | 0 red 0   |
+    | P6    | 255 | 0         |
+
+Scenario Outline: Reading a PPM file whose resolution doesn't fit its data fails
+  Given ppm ← the PPM file
+  """
+  <magic>
+  <width> <height>
+  255
+  0 0 0
+  """
+  Then canvas_from_ppm(ppm) fails
+
+  Examples:
+    | magic | width      | height     |
+    | P3    | 4294967296 | 4294967296 |
+    | P6    | 4294967296 | 4294967296 |
+    | P3    | 100000     | 100000     |
+    | P6    | 100000     | 100000     |
+    | P3    | 2          | 1          |
+
+Scenario Outline: Saving a canvas into a nonexistent directory fails
+  Given c ← canvas(5, 3)
+  Then saving c as <format> into a nonexistent directory fails with an I/O error
+
+  Examples:
+    | format |
+    | ppm    |
+    | p6     |
+    | png    |
+    | hdr    |
+    | exr    |
+
+Scenario Outline: Loading a missing file fails
+  Then loading a missing <format> file fails with an I/O error
+
+  Examples:
+    | format |
+    | ppm    |
+    | hdr    |
+
+Scenario: Parsing a malformed file reports a format error
+  Given ppm ← the PPM file
+  """
+  P3
+  1 1
+  255
+  0 0
+  """
+  Then canvas_from_ppm(ppm) fails with a format error
+
+Scenario Outline: Reading a PNG file
+  Given png ← a 2x1 <type> PNG with <bits>-bit samples [<samples>]
+  When d ← canvas_from_png(png)
+  Then d.width = 2
+    And d.height = 1
+    And pixel_at(d, 0, 0) ≈ color(<r>, <g>, <b>)
+    And pixel_at(d, 1, 0) ≈ color(0, 0, 0)
+
+  Examples:
+    | type            | bits | samples                                 | r   | g       | b   |
+    | rgb             | 8    | 255, 128, 0, 0, 0, 0                    | 1   | 0.50196 | 0   |
+    | rgb             | 16   | 255, 255, 128, 0, 0, 0, 0, 0, 0, 0, 0, 0 | 1   | 0.50001 | 0   |
+    | rgba            | 8    | 0, 51, 255, 255, 0, 0, 0, 255           | 0   | 0.2     | 1   |
+    | grayscale       | 8    | 51, 0                                   | 0.2 | 0.2     | 0.2 |
+    | grayscale       | 16   | 51, 51, 0, 0                            | 0.2 | 0.2     | 0.2 |
+    | grayscale       | 4    | 240                                     | 1   | 1       | 1   |
+    | grayscale alpha | 8    | 102, 255, 0, 255                        | 0.4 | 0.4     | 0.4 |
+
+Scenario: Reading a palette PNG file
+  Given png ← a 2x1 palette PNG with palette [0, 0, 0, 255, 51, 0] and 8-bit samples [1, 0]
+  When d ← canvas_from_png(png)
+  Then pixel_at(d, 0, 0) ≈ color(1, 0.2, 0)
+    And pixel_at(d, 1, 0) ≈ color(0, 0, 0)
+
+Scenario: Decoding sRGB samples from a PNG file
+  Given png ← a 2x1 rgb PNG with 8-bit samples [188, 255, 0, 0, 0, 0]
+  When d ← canvas_from_png(png, srgb)
+  Then pixel_at(d, 0, 0) ≈ color(0.50289, 1, 0)
+
+Scenario: Decoding sRGB samples from a PPM file
+  Given ppm ← the PPM file
+  """
+  P3
+  1 1
+  255
+  188 255 0
+  """
+  When d ← canvas_from_ppm(ppm, srgb)
+  Then pixel_at(d, 0, 0) ≈ color(0.50289, 1, 0)
+
+Scenario Outline: Reading back a PNG file
+  Given c ← gradient canvas(9, 4) up to 1
+  When c is saved as a <bits>-bit PNG file
+    And d ← the saved PNG file
+  Then d matches c within <bits>-bit precision
+
+  Examples:
+    | bits |
+    | 8    |
+    | 16   |
+
+Scenario: Reading a file that is not a PNG fails
+  Given ppm ← the PPM file
+  """
+  P3
+  1 1
+  255
+  0 0 0
+  """
+  Then canvas_from_png(ppm) fails with a PNG error
+
+Scenario: Writing RGBA PNG data un-premultiplies the colors
+  Given c ← canvas(3, 1)
+  And c1 ← color(0.25, 0, 0.5)
+  When write_pixel(c, 0, 0, c1)
+  And set_alpha(c, 0, 0, 0.5)
+  And set_alpha(c, 2, 0, 0)
+  Then png_data(c, linear, 8, alpha) = [128, 0, 255, 128, 0, 0, 0, 255, 0, 0, 0, 0]
+    And png_data(c, linear, 16, alpha) = [128, 0, 0, 0, 255, 255, 128, 0, 0, 0, 0, 0, 0, 0, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0]
+
+Scenario: Reading a PNG file with alpha premultiplies the colors
+  Given png ← a 2x1 rgba PNG with 8-bit samples [255, 51, 0, 51, 0, 0, 0, 0]
+  When d ← canvas_from_png(png)
+  Then pixel_at(d, 0, 0) ≈ color(0.2, 0.04, 0)
+    And alpha_at(d, 0, 0) = 0.2
+    And alpha_at(d, 1, 0) = 0
+
+Scenario: Comparing a canvas with itself
+  Given c ← gradient canvas(5, 3) up to 1
+    And d ← a copy of c
+  Then compare(c, d).rmse = 0
+    And compare(c, d).max_error = 0
+    And compare(c, d).psnr is infinite
+
+Scenario: Comparing two canvases
+  Given c ← canvas(2, 1)
+    And d ← canvas(2, 1)
+    And c1 ← color(0.5, 0, 0)
+  When write_pixel(c, 0, 0, c1)
+  Then compare(c, d).mean_absolute_error = 0.08333
+    And compare(c, d).rmse = 0.20412
+    And compare(c, d).psnr = 13.80211
+    And compare(c, d).max_error = 0.5
+    And pixel_at(abs_diff(c, d), 0, 0) = color(0.5, 0, 0)
+    And pixel_at(abs_diff(c, d), 1, 0) = color(0, 0, 0)
+
+Scenario: Visualizing the differences between two canvases
+  Given c ← canvas(3, 1)
+    And d ← canvas(3, 1)
+    And c1 ← color(0.5, 0, 0)
+    And c2 ← color(0, 0.25, 0)
+  When write_pixel(c, 0, 0, c1)
+    And write_pixel(c, 1, 0, c2)
+  Then pixel_at(diff_visualization(c, d), 0, 0) = color(1, 0, 0)
+    And pixel_at(diff_visualization(c, d), 1, 0) = color(1, 0.5, 0)
+    And pixel_at(diff_visualization(c, d), 2, 0) = color(0, 0, 0)
+
+Scenario: Canvases of different sizes can't be compared
+  Given c ← canvas(3, 1)
+    And d ← canvas(1, 3)
+  Then compare(c, d), abs_diff(c, d) and diff_visualization(c, d) are nothing
+
+Scenario: Drawing a canvas in the terminal with truecolor
+  Given c ← canvas(2, 2)
+  And c1 ← color(1, 0, 0)
+  And c2 ← color(0, 0, 1)
+  When write_pixel(c, 0, 0, c1)
+  And write_pixel(c, 1, 1, c2)
+  Then canvas_to_ansi(c, 80, truecolor) = "\e[38;2;255;0;0m\e[48;2;0;0;0m▀\e[38;2;0;0;0m\e[48;2;0;0;255m▀\e[0m\n"
+
+Scenario: The last line of a canvas with an odd height has no background
+  Given c ← canvas(1, 3)
+  Then canvas_to_ansi(c, 80, truecolor) = "\e[38;2;0;0;0m\e[48;2;0;0;0m▀\e[0m\n\e[38;2;0;0;0m\e[49m▀\e[0m\n"
+
+Scenario: A wide canvas is scaled down to fit the terminal
+  Given c ← canvas(4, 2)
+  And c1 ← color(1, 1, 1)
+  When write_pixel(c, 0, 0, c1)
+  Then canvas_to_ansi(c, 2, truecolor) = "\e[38;2;64;64;64m\e[49m▀\e[38;2;0;0;0m\e[49m▀\e[0m\n"
+
+Scenario: Drawing a canvas in the terminal with 256 colors
+  Given c ← canvas(1, 2)
+  And red ← color(1, 0, 0)
+  When write_pixel(c, 0, 0, red)
+  Then canvas_to_ansi(c, 80, 256) = "\e[38;5;196m\e[48;5;16m▀\e[0m\n"
+
+Scenario Outline: Finding the nearest color in the 256-color palette
+  Then ansi256(<r>, <g>, <b>) = <index>
+
+  Examples:
+    | r   | g   | b   | index |
+    | 0   | 0   | 0   | 16    |
+    | 255 | 0   | 0   | 196   |
+    | 255 | 255 | 255 | 231   |
+    | 95  | 135 | 175 | 67    |
+    | 188 | 188 | 188 | 250   |
+    | 10  | 12  | 9   | 232   |
+
+Scenario: Cropping a canvas
+  Given c ← numbered canvas(4, 3)
+  When d ← crop(c, 1, 1, 2, 2)
+  Then d.width = 2
+    And d.height = 2
+    And pixel_at(d, 0, 0) = color(1, 1, 0)
+    And pixel_at(d, 1, 1) = color(2, 2, 0)
+
+Scenario: A crop is clipped to the canvas
+  Given c ← numbered canvas(4, 3)
+  When d ← crop(c, 3, 2, 5, 5)
+  Then d.width = 1
+    And d.height = 1
+    And pixel_at(d, 0, 0) = color(3, 2, 0)
+
+Scenario Outline: Flipping and rotating a canvas
+  Given c ← numbered canvas(3, 2)
+  When d ← <operation>(c)
+  Then d.width = <width>
+    And d.height = <height>
+    And pixel_at(d, 0, 0) = color(<first_x>, <first_y>, 0)
+    And pixel_at(d, <last_x>, <last_y>) = color(<x>, <y>, 0)
+
+  Examples:
+    | operation       | width | height | first_x | first_y | last_x | last_y | x | y |
+    | flip_horizontal | 3     | 2      | 2       | 0       | 2      | 1      | 0 | 1 |
+    | flip_vertical   | 3     | 2      | 0       | 1       | 2      | 1      | 2 | 0 |
+    | rotate_90       | 2     | 3      | 0       | 1       | 1      | 2      | 2 | 0 |
+    | rotate_180      | 3     | 2      | 2       | 1       | 2      | 1      | 0 | 0 |
+    | rotate_270      | 2     | 3      | 2       | 0       | 1      | 2      | 0 | 1 |
+
+Scenario: Four quarter turns give back the canvas
+  Given c ← numbered canvas(3, 2)
+  When d ← rotate_90(rotate_90(rotate_90(rotate_90(c))))
+  Then d = c
+
+Scenario: Resizing with the nearest pixel
+  Given c ← numbered canvas(4, 2)
+  When d ← resize(c, 2, 1, nearest)
+  Then pixel_at(d, 0, 0) = color(1, 1, 0)
+    And pixel_at(d, 1, 0) = color(3, 1, 0)
+
+Scenario: Enlarging with bilinear filtering blends neighboring pixels
+  Given c ← gray canvas(2, 1) with values [0, 1]
+  When d ← resize(c, 4, 1, bilinear)
+  Then row 0 of d is gray [0, 0.25, 0.75, 1]
+
+Scenario Outline: Resizing keeps a uniform canvas uniform
+  Given c ← canvas(5, 3)
+  When every pixel of c is set to color(0.2, 0.4, 0.6)
+    And d ← resize(c, <width>, <height>, <filter>)
+  Then d.width = <width>
+    And d.height = <height>
+    And every pixel of d ≈ color(0.2, 0.4, 0.6)
+    And every pixel of d has alpha ≈ 1
+
+  Examples:
+    | filter   | width | height |
+    | nearest  | 2     | 7      |
+    | bilinear | 2     | 7      |
+    | bilinear | 11    | 1      |
+    | lanczos3 | 2     | 7      |
+    | lanczos3 | 11    | 1      |
+
+Scenario Outline: Resizing to the same size changes nothing
+  Given c ← gradient canvas(6, 4) up to 3
+  When d ← resize(c, 6, 4, <filter>)
+  Then d ≈ c
+
+  Examples:
+    | filter   |
+    | nearest  |
+    | bilinear |
+    | lanczos3 |
+
+Scenario: Compositing a partly covered canvas over another
+  Given c ← canvas(2, 1)
+    And d ← canvas(1, 1)
+  When every pixel of c is set to color(1, 0, 0)
+    And every pixel of d is set to color(0, 0, 0.5) with alpha 0.5
+    And d is composited over c at (1, 0)
+  Then pixel_at(c, 0, 0) ≈ color(1, 0, 0)
+    And pixel_at(c, 1, 0) ≈ color(0.5, 0, 0.5)
+    And alpha_at(c, 1, 0) = 1
+
+Scenario: Compositing over an uncovered canvas
+  Given c ← canvas(1, 1)
+    And d ← canvas(1, 1)
+  When every pixel of c is set to color(0, 0, 0) with alpha 0
+    And every pixel of d is set to color(0, 0, 0.5) with alpha 0.5
+    And d is composited over c at (0, 0)
+  Then pixel_at(c, 0, 0) ≈ color(0, 0, 0.5)
+    And alpha_at(c, 0, 0) = 0.5
+
+Scenario: Blitting replaces the pixels underneath, clipped to the canvas
+  Given c ← canvas(2, 2)
+    And d ← canvas(2, 2)
+  When every pixel of c is set to color(1, 0, 0)
+    And every pixel of d is set to color(0, 0, 0.5) with alpha 0.5
+    And d is blitted onto c at (-1, 1)
+  Then pixel_at(c, 0, 0) ≈ color(1, 0, 0)
+    And pixel_at(c, 1, 1) ≈ color(1, 0, 0)
+    And pixel_at(c, 0, 1) ≈ color(0, 0, 0.5)
+    And alpha_at(c, 0, 1) = 0.5
+
+Scenario: A box blur spreads a bright pixel evenly
+  Given c ← canvas(3, 3)
+    And c1 ← color(9, 9, 9)
+  When write_pixel(c, 1, 1, c1)
+    And d ← convolve(c, box_blur(1))
+  Then every pixel of d ≈ color(1, 1, 1)
+
+Scenario: Sharpening leaves a uniform canvas alone
+  Given c ← canvas(4, 4)
+  When every pixel of c is set to color(0.5, 0.25, 0.125)
+    And d ← convolve(c, sharpen)
+  Then every pixel of d ≈ color(0.5, 0.25, 0.125)
+
+Scenario: Sharpening exaggerates an edge
+  Given c ← gray canvas(4, 1) with values [0, 0, 1, 1]
+  When d ← convolve(c, sharpen)
+  Then row 0 of d is gray [0, -1, 2, 1]
+
+Scenario: Convolving with an arbitrary kernel
+  Given c ← numbered canvas(3, 1)
+  When d ← convolve(c, kernel [[0, 0, 0], [0, 0, 1], [0, 0, 0]])
+  Then pixel_at(d, 0, 0) = color(1, 0, 0)
+    And pixel_at(d, 1, 0) = color(2, 0, 0)
+    And pixel_at(d, 2, 0) = color(2, 0, 0)
+
+Scenario: A Gaussian kernel is normalized and symmetric
+  Then the weights of gaussian(1.5) sum to 1
+    And gaussian(1.5) is 11x11
+    And gaussian(1.5) = transpose(gaussian(1.5))
+
+Scenario: A Gaussian blur in two passes matches the full kernel
+  Given c ← gradient canvas(7, 5) up to 4
+  When d ← gaussian_blur(c, 0.8)
+  Then d ≈ convolve(c, gaussian(0.8))
+
+Scenario: The bright pass keeps only the light above the threshold
+  Given c ← canvas(3, 1)
+    And c1 ← color(3, 3, 3)
+    And c2 ← color(0.5, 0.5, 0.5)
+    And c3 ← color(20, 0, 0)
+  When write_pixel(c, 0, 0, c1)
+    And write_pixel(c, 1, 0, c2)
+    And write_pixel(c, 2, 0, c3)
+    And d ← bright_pass(c, 1)
+  Then pixel_at(d, 0, 0) ≈ color(2, 2, 2)
+    And pixel_at(d, 1, 0) ≈ color(0, 0, 0)
+    And pixel_at(d, 2, 0) ≈ color(15.29633, 0, 0)
+
+Scenario: Bloom leaves a canvas with nothing above the threshold alone
+  Given c ← gradient canvas(8, 6) up to 1
+  When d ← bloom(c) with threshold 1 and intensity 0.5
+  Then d = c
+
+Scenario: Bloom spreads a bright highlight around it in the same hue
+  Given c ← canvas(16, 16)
+    And c1 ← color(40, 0, 0)
+  When write_pixel(c, 8, 8, c1)
+    And d ← bloom(c) with threshold 1 and intensity 0.5
+  Then pixel_at(d, 8, 8) is at least color(40, 0, 0)
+    And pixel_at(d, 11, 8) glows red
+    And pixel_at(d, 8, 5) glows red
+    And every pixel of d has alpha ≈ 1
+
+Scenario: The glow carries the intensity times the light above the threshold
+  Given c ← canvas(64, 64)
+    And c1 ← color(10, 10, 10)
+  When write_pixel(c, 32, 32, c1)
+    And d ← bloom(c) with threshold 1 and intensity 0.5
+  Then the light d adds to c is within 5% of 0.5 × the light in bright_pass(c, 1)
@@ -0,0 +1,187 @@
+Feature: Command Line
+
+Background:
+  Given a scene file scene.yml containing
+    """
+    - add: camera
+      width: 16
+      height: 12
+      field-of-view: 1.0471975511965976
+      from: [ 0, 1.5, -5 ]
+      to: [ 0, 1, 0 ]
+      up: [ 0, 1, 0 ]
+
+    - add: light
+      at: [ -10, 10, -10 ]
+      intensity: [ 1, 1, 1 ]
+
+    - add: plane
+      material:
+        color: [ 1, 0.9, 0.9 ]
+        specular: 0
+
+    - add: sphere
+      transform:
+        - [ translate, -0.5, 1, 0.5 ]
+      material:
+        color: [ 0.1, 1, 0.5 ]
+        diffuse: 0.7
+        specular: 0.3
+    """
+
+Scenario: Rendering a scene at its own resolution
+  When sunhouse is run with "scene.yml -o out.png"
+  Then the command succeeds
+    And out.png is a 16×12 PNG
+
+Scenario: Overriding the resolution
+  When sunhouse is run with "scene.yml --width 8 --height 4 -o out.png"
+  Then the command succeeds
+    And out.png is a 8×4 PNG
+
+Scenario: Giving only a width keeps the aspect ratio
+  When sunhouse is run with "scene.yml --width 32 -o out.png"
+  Then the command succeeds
+    And out.png is a 32×24 PNG
+
+Scenario: Rendering on several threads with more samples
+  When sunhouse is run with "scene.yml --threads 3 --samples 4 -o out.png"
+  Then the command succeeds
+    And out.png is a 16×12 PNG
+
+Scenario Outline: A transparent background is written as alpha
+  Given a scene file clear.yml containing
+    """
+    - background: transparent
+    - add: camera
+      width: 16
+      height: 12
+      field-of-view: 1.0471975511965976
+      from: [ 0, 1.5, -5 ]
+      to: [ 0, 1, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ -10, 10, -10 ]
+      intensity: [ 1, 1, 1 ]
+    - add: sphere
+    """
+  When sunhouse is run with "clear.yml -o <file>"
+  Then the command succeeds
+    And <check>
+
+  Examples:
+    | file      | check                                           |
+    | clear.png | the top left pixel of clear.png is transparent  |
+    | clear.exr | clear.exr has the EXR channels A, B, G, R       |
+
+Scenario: An opaque background is written without alpha
+  When sunhouse is run with "scene.yml -o out.exr"
+  Then the command succeeds
+    And out.exr has the EXR channels B, G, R
+
+Scenario: More samples smooth out depth of field
+  When sunhouse is run with "scene.yml --aperture 0.3 --focal-distance 5 --samples 2 -o few.png"
+    And sunhouse is run with "scene.yml --aperture 0.3 --focal-distance 5 --samples 16 -o many.png"
+    And sunhouse is run with "scene.yml --samples 16 -o pinhole.png"
+  Then many.png differs from few.png
+    And many.png differs from pinhole.png
+
+Scenario: An open aperture without samples is reported
+  When sunhouse is run with "scene.yml --aperture 0.3 -o out.png"
+  Then the command fails
+    And stderr contains "more than one sample"
+
+Scenario: A moving sphere is blurred while the scene's shutter is open
+  Given a scene file moving.yml containing
+    """
+    - add: camera
+      width: 16
+      height: 12
+      field-of-view: 1.0471975511965976
+      from: [ 0, 1.5, -5 ]
+      to: [ 0, 1, 0 ]
+      up: [ 0, 1, 0 ]
+      samples: 16
+      shutter-open: 0
+      shutter-close: 1
+    - add: light
+      at: [ -10, 10, -10 ]
+      intensity: [ 1, 1, 1 ]
+    - add: sphere
+      transform:
+        - [ translate, -1, 1, 0 ]
+      end-transform:
+        - [ translate, 1, 1, 0 ]
+    """
+  When sunhouse is run with "moving.yml -o blurred.png"
+    And sunhouse is run with "moving.yml --shutter-close 0 -o still.png"
+  Then blurred.png differs from still.png
+
+Scenario: A bad lens setting is reported
+  When sunhouse is run with "scene.yml --focal-distance 0 -o out.png"
+  Then the command fails
+    And stderr contains "focal distance"
+
+Scenario: Choosing the output format
+  When sunhouse is run with "scene.yml --format p6 -o out.img"
+  Then the command succeeds
+    And out.img is a 16×12 PPM
+
+Scenario: Overriding the camera
+  When sunhouse is run with "scene.yml --from 0,10,-0.1 --to 0,0,0 --fov 0.5 -o above.png"
+    And sunhouse is run with "scene.yml -o front.png"
+  Then above.png differs from front.png
+
+Scenario: Previewing the image in the terminal
+  When sunhouse is run with "scene.yml -o out.png --preview --preview-width 8 --preview-colors truecolor"
+  Then the command succeeds
+    And out.png is a 16×12 PNG
+    And stdout is a truecolor preview 8 characters wide and 3 lines high
+
+Scenario: Previewing the image with 256 colors
+  When sunhouse is run with "scene.yml -o out.png --preview --preview-width 4 --preview-colors 256"
+  Then the command succeeds
+    And stdout is a 256-color preview 4 characters wide and 2 lines high
+
+Scenario: A missing scene file is reported
+  When sunhouse is run with "missing.yml -o out.png"
+  Then the command fails
+    And stderr contains "missing.yml"
+
+Scenario: An unknown output format is reported
+  When sunhouse is run with "scene.yml -o out.jpg"
+  Then the command fails
+    And stderr contains "use --format"
+
+Scenario: A bad camera override is reported
+  When sunhouse is run with "scene.yml --from 1,2 -o out.png"
+  Then the command fails
+    And stderr contains "expected three numbers"
+
+Scenario Outline: A camera override that can't be used is reported
+  When sunhouse is run with "scene.yml <override> -o out.png"
+  Then the command fails
+    And stderr contains "<message>"
+
+  Examples:
+    | override                    | message                 |
+    | --from 1,2,3 --to 1,2,3     | the point it is at      |
+    | --to 0,1.5,-5               | the point it is at      |
+    | --to 0,1.5,0 --up 0,0,1     | up direction            |
+    | --up 0,0,0                  | up direction            |
+    | --fov 0                     | field of view           |
+    | --fov=-1                    | field of view           |
+    | --fov NaN                   | field of view           |
+    | --fov 4                     | field of view           |
+
+Scenario: A mistake in the scene file is reported with its line
+  Given a scene file broken.yml containing
+    """
+    - add: camera
+      width: 16
+      height: twelve
+    """
+  When sunhouse is run with "broken.yml -o out.png"
+  Then the command fails
+    And stderr contains "line 3"
+    And stderr contains "twelve"
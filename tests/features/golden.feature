@@ -0,0 +1,22 @@
+Feature: Golden Images
+
+Scenario Outline: Rendering an example scene matches its reference image
+  Given image ← render(<example>)
+  When reference ← the reference image for <example>
+  Then image is the same size as reference
+    And the PSNR of image against reference is at least 40 dB
+    And the max error of image against reference is at most 0.05
+
+  Examples:
+    | example  |
+    | example5 |
+    | example6 |
+    | example8 |
+    | example9 |
+
+Scenario: Rendering the example scene file matches the example it describes
+  Given image ← render_scene(examples/scenes/example9.yml) as example9
+  When reference ← the reference image for example9
+  Then image is the same size as reference
+    And the PSNR of image against reference is at least 40 dB
+    And the max error of image against reference is at most 0.05
@@ -36,3 +36,15 @@ Scenario: A ray intersecting a plane from below
   Then xs.count = 1
     And xs[0].t = 1
     And xs[0].object = p
+
+Scenario: Intersecting a translated plane
+  Given p ← plane() with transform translation(0, -1, 0)
+    And r ← ray(point(0, 1, 0), vector(0, -1, 0))
+  When xs ← local_intersect(p, r)
+  Then xs.count = 1
+    And xs[0].t = 2
+
+Scenario: The normal of a rotated plane
+  Given p ← plane() with transform rotation_x(π/2)
+  When n1 ← local_normal_at(p, point(0, 0, 0))
+  Then n1 = vector(0, 0, 1)
@@ -27,3 +27,15 @@ Scenario: Scaling a ray
   When r2 ← transform(r, m)
   Then r2.origin = point(2, 6, 12)
     And r2.direction = vector(0, 3, 0)
+
+Scenario: A ray is cast at time zero by default
+  Given r ← ray(point(1, 2, 3), vector(0, 1, 0))
+  Then r.time = 0
+
+Scenario: Transforming a ray preserves its time
+  Given r ← ray(point(1, 2, 3), vector(0, 1, 0))
+    And r ← with_time(r, 0.25)
+    And m ← translation(3, 4, 5)
+  When r2 ← transform(r, m)
+  Then r2.origin = point(4, 6, 8)
+    And r2.time = 0.25
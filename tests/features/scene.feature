@@ -0,0 +1,353 @@
+Feature: Scene Files
+
+Scenario: Reading a camera
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 100
+      height: 50
+      field-of-view: 0.785
+      from: [ -6, 6, -10 ]
+      to: [ 6, 0, 6 ]
+      up: [ -0.45, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then c.hsize = 100
+    And c.vsize = 50
+    And c.field_of_view = 0.785
+    And c.transform = view_transform(point(-6, 6, -10), point(6, 0, 6), vector(-0.45, 1, 0))
+
+Scenario: Reading a camera's projection, lens and shutter
+  Given scene_yaml ←
+    """
+    - define: view
+      value:
+        - [ translate, 0, 0, -5 ]
+    - add: camera
+      width: 40
+      height: 20
+      projection: orthographic
+      view-size: 4
+      transform:
+        - view
+      aperture: 0.25
+      focal-distance: 5
+      aperture-blades: 6
+      samples: 8
+      shutter-open: 0.5
+      shutter-close: 1.5
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then c.projection = orthographic
+    And c.half_view = 2
+    And c.transform = translation(0, 0, -5)
+    And c.aperture = 0.25
+    And c.focal_distance = 5
+    And c.aperture_blades = 6
+    And c.samples = 8
+    And c.shutter_open = 0.5
+    And c.shutter_close = 1.5
+    And parse(to_yaml(scene)) = scene
+
+Scenario: Reading lights
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    - add: light
+      at: [ -400, 50, -10 ]
+      intensity: [ 0.2, 0.2, 0.2 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then w.lights.count = 2
+    And w.lights[0] = point_light(point(50, 100, -50), color(1, 1, 1))
+    And w.lights[1] = point_light(point(-400, 50, -10), color(0.2, 0.2, 0.2))
+
+Scenario: Reading shapes with transforms and materials
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    # Transforms are applied in the order they are listed.
+    - add: sphere
+      transform:
+        - [ translate, 1, 2, 3 ]
+        - [ scale, 0.5, 0.5, 0.5 ]
+      material:
+        color: [ 0.373, 0.404, 0.550 ]
+        diffuse: 0.2
+        ambient: 0.0
+        specular: 1.0
+        shininess: 200
+    - add: plane
+      transform:
+        - [ rotate-x, 1.5707963267948966 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then w.objects.count = 2
+    And w.objects[0] is a sphere
+    And w.objects[0].transform = scaling(0.5, 0.5, 0.5) * translation(1, 2, 3)
+    And w.objects[0].material.color = color(0.373, 0.404, 0.550)
+    And w.objects[0].material.diffuse = 0.2
+    And w.objects[0].material.ambient = 0.0
+    And w.objects[0].material.specular = 1.0
+    And w.objects[0].material.shininess = 200
+    And w.objects[1] is a plane
+    And w.objects[1].transform = rotation_x(1.5707963267948966)
+    And w.objects[1].material = material()
+
+Scenario: Defining and extending materials
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    - define: white-material
+      value:
+        color: [ 1, 1, 1 ]
+        diffuse: 0.7
+        ambient: 0.1
+        specular: 0.0
+    - define: blue-material
+      extend: white-material
+      value:
+        color: [ 0.537, 0.831, 0.914 ]
+    - add: sphere
+      material: white-material
+    - add: sphere
+      material: blue-material
+    """
+  When scene ← parse(scene_yaml)
+  Then w.objects[0].material.color = color(1, 1, 1)
+    And w.objects[0].material.diffuse = 0.7
+    And w.objects[1].material.color = color(0.537, 0.831, 0.914)
+    And w.objects[1].material.diffuse = 0.7
+    And w.objects[1].material.specular = 0.0
+
+Scenario: Defining transforms
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    - define: standard-transform
+      value:
+        - [ translate, 1, -1, 1 ]
+        - [ scale, 0.5, 0.5, 0.5 ]
+    - define: large-object
+      value:
+        - standard-transform
+        - [ scale, 3.5, 3.5, 3.5 ]
+    - add: sphere
+      transform:
+        - large-object
+        - [ translate, 8.5, 1.5, -0.5 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then w.objects[0].transform = translation(8.5, 1.5, -0.5) * scaling(3.5, 3.5, 3.5) * scaling(0.5, 0.5, 0.5) * translation(1, -1, 1)
+
+Scenario: Loading the example scene file
+  When scene ← load(examples/scenes/example9.yml)
+  Then c.hsize = 256
+    And c.vsize = 256
+    And w.lights.count = 1
+    And w.objects.count = 4
+
+Scenario Outline: Mistakes in a scene file are reported with their line
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    <item>
+    """
+  When scene ← parse(scene_yaml)
+  Then parsing fails on line <line> with "<message>"
+
+  Examples:
+    | item                                              | line | message                               |
+    | - add: cube                                       | 11   | don't know how to add `cube`          |
+    | - add: sphere\n  material: chrome                 | 12   | `chrome` hasn't been defined          |
+    | - add: sphere\n  transform:\n    - [ twist, 1 ]   | 13   | unknown transformation `twist`        |
+    | - add: sphere\n  transform:\n    - [ scale, 1 ]   | 13   | `scale` takes 3 numbers               |
+    | - add: sphere\n  material:\n    colour: [ 1 ]     | 13   | unknown material property             |
+    | - add: sphere\n  material:\n    diffuse: lots     | 13   | expected a number, found `lots`       |
+    | - add: light\n  at: [ 1, 2 ]                      | 12   | expected three numbers                |
+    | - add: sphere\n    material: chrome               | 12   | check the indentation                 |
+    | - define: t\n  value: 3                           | 12   | expected a material or a list         |
+    | - define: t\n  extend: u\n  value: { diffuse: 1 } | 12   | `u` hasn't been defined               |
+    | - color: [ 1, 1, 1 ]                              | 11   | expected an `add`, `define`           |
+    | - add: sphere\n  material: { color: [ 1, 1 }      | 12   | expected `,` or `]`, found `}`        |
+    | - add: plane\n  start-time: 1                     | 11   | `start-time` needs an `end-transform` |
+    | - add: plane\n  end-transform: []\n  end-time: 0  | 12   | a motion must end after it starts     |
+    | - add: plane\n  parent:\n    add: light          | 13   | don't know how to add `light`         |
+    | - add: sphere\n  transform:\n    - [ scale, 0, 0, 0 ] | 13 | can't be undone                |
+    | - add: plane\n  end-transform:\n    - [ scale, 1, 0, 1 ] | 13 | can't be undone            |
+
+Scenario Outline: Mistakes in a camera are reported with their line
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      <lines>
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then parsing fails on line <line> with "<message>"
+
+  Examples:
+    | lines                                                   | line | message                       |
+    | projection: pinhole                                     | 4    | unknown projection `pinhole`  |
+    | projection: orthographic\n  field-of-view: 1            | 5    | unknown camera property       |
+    | projection: orthographic\n  transform: []               | 1    | camera is missing `view-size` |
+    | field-of-view: 1\n  from: [ 0, 0, -5 ]\n  transform: [] | 6    | can't also have `from`        |
+    | field-of-view: 1\n  transform: []\n  aperture: 0.5      | 6    | more than one of `samples`    |
+    | field-of-view: 1\n  transform:\n    - [ scale, 0, 1, 1 ] | 6 | can't be undone          |
+    | field-of-view: 1\n  from: [ 1, 2, 3 ]\n  to: [ 1, 2, 3 ]\n  up: [ 0, 1, 0 ] | 6 | must differ from `from` |
+    | field-of-view: 1\n  from: [ 0, 0, 0 ]\n  to: [ 0, 1, 0 ]\n  up: [ 0, 2, 0 ] | 7 | `up` can't be along    |
+
+Scenario Outline: A camera without any pixels is rejected
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: <width>
+      height: <height>
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then parsing fails on line <line> with "at least one pixel"
+
+  Examples:
+    | width | height | line |
+    | 0     | 10     | 2    |
+    | 10    | 0      | 3    |
+
+Scenario Outline: Deeply nested values are rejected
+  Given scene_yaml ← "<repeated>" repeated 100000 times, then "x"
+  When scene ← parse(scene_yaml)
+  Then parsing fails on line 1 with "<message>"
+
+  Examples:
+    | repeated | message                                          |
+    | [        | lists and mappings can't be nested more than 100 |
+    | { a:     | lists and mappings can't be nested more than 100 |
+    | -        | blocks can't be nested more than 100             |
+
+Scenario: A scene without a camera is rejected
+  Given scene_yaml ←
+    """
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then parsing fails on line 1 with "no camera"
+
+Scenario: Saving a scene and loading it again gives the same scene
+  When scene ← load(examples/scenes/example9.yml)
+  Then parse(to_yaml(scene)) = scene
+
+Scenario: Saving keeps backgrounds, sphere sizes, motion, parents and arbitrary transforms
+  Given scene_yaml ←
+    """
+    - add: camera
+      width: 10
+      height: 10
+      field-of-view: 1
+      from: [ 0, 0, -5 ]
+      to: [ 0, 0, 0 ]
+      up: [ 0, 1, 0 ]
+    - background: transparent
+    - add: light
+      at: [ 50, 100, -50 ]
+      intensity: [ 1, 1, 1 ]
+    - add: sphere
+      center: [ 1, 2, 3 ]
+      radius: 0.25
+      transform:
+        - [ rotate-y, 0.7 ]
+        - [ shear, 1, 0, 0, 0, 0, 0.5 ]
+      end-transform:
+        - [ translate, 2, 0, 0 ]
+      start-time: 0.5
+      end-time: 1.5
+    - add: plane
+      parent:
+        add: sphere
+        radius: 2
+        parent:
+          add: plane
+          transform:
+            - [ translate, 0, 1, 0 ]
+    """
+  When scene ← parse(scene_yaml)
+  Then w.background = transparent
+    And w.objects[0].center = point(1, 2, 3)
+    And w.objects[0].radius = 0.25
+    And w.objects[0].end_transform = translation(2, 0, 0)
+    And w.objects[0] moves from time 0.5 to 1.5
+    And w.objects[0] has no parent
+    And w.objects[1].parent is a sphere
+    And w.objects[1].parent.parent is a plane
+    And w.objects[1].parent.parent.transform = translation(0, 1, 0)
+    And parse(to_yaml(scene)) = scene
+
+Scenario: Randomly generated scenes survive saving and loading
+  Then 500 random scenes survive to_yaml and from_yaml
+
+Scenario: Test shapes can't be saved
+  When scene ← load(examples/scenes/example9.yml)
+    And a test shape is added to scene
+  Then to_yaml(scene) fails with a format error
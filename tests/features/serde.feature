@@ -0,0 +1,37 @@
+Feature: Serde
+
+Scenario Outline: Tuples are encoded as arrays
+  Given value ← <value>
+  Then json(value) = <json>
+    And from_json(json(value)) = value
+
+  Examples:
+    | value                  | json              |
+    | point(1, -2.5, 3)      | [1.0,-2.5,3.0]    |
+    | vector(0, 1, 0)        | [0.0,1.0,0.0]     |
+    | color(0.5, 0.25, 1)    | [0.5,0.25,1.0]    |
+
+Scenario Outline: Values round-trip through JSON
+  Given value ← <value>
+  Then from_json(json(value)) = value
+
+  Examples:
+    | value                                                      |
+    | translation(1, 2, 3) * rotation_y(0.5) * scaling(2, 2, 2)  |
+    | material()                                                 |
+    | glass()                                                    |
+    | point_light(point(-10, 10, -10), color(1, 1, 1))           |
+    | camera(160, 120, 0.785)                                    |
+    | fisheye_camera(64, 64, 3.14)                               |
+    | sphere()                                                   |
+    | plane()                                                    |
+    | test_shape()                                               |
+    | default_world()                                            |
+    | transparent_world()                                        |
+    | world(examples/scenes/example9.yml)                        |
+    | camera(examples/scenes/example9.yml)                       |
+
+Scenario: A shape's parent is serialized with it
+  Given value ← sphere() with a parent
+  Then from_json(json(value)) = value
+    And the parent of from_json(json(value)) is sphere()
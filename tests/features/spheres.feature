@@ -127,3 +127,44 @@ Scenario: A helper for producing a sphere with a glassy material
   Then s.transform = identity_matrix
     And s.material.transparency = 1.0
     And s.material.refractive_index = 1.5
+
+Scenario: A moving sphere is intersected where it is at the ray's time
+  Given s ← sphere()
+    And set_motion(s, translation(5, 0, 0))
+    And r ← ray(point(2.5, 0, -5), vector(0, 0, 1)) at time 0.5
+  When xs1 ← intersect(s, r)
+  Then xs1.count = 2
+    And xs1[0] = 4.0
+    And xs1[1] = 6.0
+
+Scenario: A moving sphere has left the ray's path by the end of the shutter
+  Given s ← sphere()
+    And set_motion(s, translation(5, 0, 0))
+    And r ← ray(point(0, 0, -5), vector(0, 0, 1)) at time 1
+  When xs1 ← intersect(s, r)
+  Then xs1.count = 0
+
+Scenario Outline: A sphere moves over its own span of time
+  Given s ← sphere()
+    And set_motion(s, translation(5, 0, 0)) from time 2 to 4
+    And r ← ray(point(<x>, 0, -5), vector(0, 0, 1)) at time <time>
+  When xs1 ← intersect(s, r)
+  Then xs1.count = 2
+    And xs1[0] = 4.0
+    And xs1[1] = 6.0
+
+  Examples:
+    | time | x   |
+    | 0    | 0   |
+    | 2    | 0   |
+    | 3    | 2.5 |
+    | 4    | 5   |
+    | 10   | 5   |
+
+Scenario: The normal on a moving sphere follows the sphere
+  Given s ← sphere()
+    And set_motion(s, translation(5, 0, 0))
+    And r ← ray(point(2.5, 0, -5), vector(0, 0, 1)) at time 0.5
+  When xs1 ← intersect(s, r)
+    And n ← normal_at(xs1[0].object, point(2.5, 0, -1))
+  Then n = vector(0, 0, -1)
@@ -148,3 +148,55 @@ Scenario: An arbitrary view transformation
       |  0.76772 | 0.60609 |  0.12122 | -2.82843 |
       | -0.35857 | 0.59761 | -0.71714 |  0.00000 |
       |  0.00000 | 0.00000 |  0.00000 |  1.00000 |
+
+Scenario: Interpolating between two translations
+  Given start ← translation(0, 0, 0)
+    And end ← translation(10, -4, 2)
+  When t ← interpolate(start, end, 0.5)
+  Then t is the following 4x4 matrix:
+    | 1 | 0 | 0 |  5 |
+    | 0 | 1 | 0 | -2 |
+    | 0 | 0 | 1 |  1 |
+    | 0 | 0 | 0 |  1 |
+
+Scenario: Interpolating between two scalings
+  Given start ← scaling(1, 1, 1)
+    And end ← scaling(3, 5, 1)
+  When t ← interpolate(start, end, 0.5)
+  Then t is the following 4x4 matrix:
+    | 2 | 0 | 0 | 0 |
+    | 0 | 3 | 0 | 0 |
+    | 0 | 0 | 1 | 0 |
+    | 0 | 0 | 0 | 1 |
+
+Scenario: Interpolating between two rotations keeps the rotation rigid
+  Given start ← rotation_y(0°)
+    And end ← rotation_y(90°)
+  When t ← interpolate(start, end, 0.5)
+  Then t is the following 4x4 matrix:
+    |  0.70711 | 0 | 0.70711 | 0 |
+    |  0       | 1 | 0       | 0 |
+    | -0.70711 | 0 | 0.70711 | 0 |
+    |  0       | 0 | 0       | 1 |
+
+Scenario: Interpolation reproduces the start transform
+  Given start ← rotation_y(30°) * scaling(2, 3, 4)
+    And end ← translation(10, -4, 2)
+  When t ← interpolate(start, end, 0)
+  Then t = start
+
+Scenario: Interpolation reproduces the end transform
+  Given start ← rotation_y(30°) * scaling(2, 3, 4)
+    And end ← translation(10, -4, 2) * rotation_y(-60°) * scaling(1, 2, 1)
+  When t ← interpolate(start, end, 1)
+  Then t = end
+
+Scenario: A motion blends its ends the same way as interpolate
+  Given start ← rotation_y(0°)
+    And end ← translation(10, -4, 2) * rotation_y(90°)
+  When t ← transform_at(motion(start, end), 0.5)
+  Then t is the following 4x4 matrix:
+    |  0.70711 | 0 | 0.70711 |  5 |
+    |  0       | 1 | 0       | -2 |
+    | -0.70711 | 0 | 0.70711 |  1 |
+    |  0       | 0 | 0       |  1 |
@@ -53,6 +53,29 @@ Scenario: The color when a ray misses
   When c ← color_at(w, r)
   Then c = color(0, 0, 0)
 
+Scenario: The color when a ray misses a world with a colored background
+  Given w ← default_world()
+    And w.background ← color(0.2, 0.4, 0.6)
+    And r ← ray(point(0, 0, -5), vector(0, 1, 0))
+  When c ← color_at(w, r)
+  Then c = color(0.2, 0.4, 0.6)
+
+Scenario: A ray that misses a world with a transparent background is uncovered
+  Given w ← default_world()
+    And w.background ← transparent
+    And r ← ray(point(0, 0, -5), vector(0, 1, 0))
+  When c, alpha ← color_and_alpha_at(w, r)
+  Then c = color(0, 0, 0)
+    And alpha = 0
+
+Scenario: A ray that hits a world with a transparent background is covered
+  Given w ← default_world()
+    And w.background ← transparent
+    And r ← ray(point(0, 0, -5), vector(0, 0, 1))
+  When c, alpha ← color_and_alpha_at(w, r)
+  Then c = color(0.38066, 0.47583, 0.2855)
+    And alpha = 1
+
 Scenario: The color when a ray hits
   Given w ← default_world()
     And r ← ray(point(0, 0, -5), vector(0, 0, 1))
@@ -69,6 +92,41 @@ Scenario: The color with an intersection behind the ray
   When c ← color_at(w, r)
   Then c = inner.material.color
 
+Scenario: Tracing a ray records the hit and the light it received
+  Given w ← default_world()
+    And r ← ray(point(0, 0, -5), vector(0, 0, 1))
+  When trace ← trace_ray(w, r)
+  Then trace.intersections.count = 4
+    And trace.hit.comps.t = 4
+    And trace.hit.shadow.in_shadow is false
+    And trace.hit.lights.count = 1
+    And trace.color = color_at(w, r)
+
+Scenario: Tracing a ray in shadow records the shadow ray
+  Given w ← world()
+    And w.light ← point_light(point(0, 0, -10), color(1, 1, 1))
+    And s1 ← sphere()
+    And s1 is added to w
+    And s2 ← sphere() with:
+      | transform | translation(0, 0, 10) |
+    And s2 is added to w
+    And r ← ray(point(0, 0, 5), vector(0, 0, 1))
+  When trace ← trace_ray(w, r)
+  Then trace.hit.shadow.in_shadow is true
+    And trace.hit.shadow.intersections.count = 4
+    And trace.hit.lights[0].diffuse = color(0, 0, 0)
+    And trace.hit.lights[0].specular = color(0, 0, 0)
+    And trace.color = color_at(w, r)
+
+Scenario: Tracing a ray that misses records the background
+  Given w ← default_world()
+    And w.background ← color(0.2, 0.3, 0.4)
+    And r ← ray(point(0, 0, -5), vector(0, 1, 0))
+  When trace ← trace_ray(w, r)
+  Then trace.intersections.count = 0
+    And trace.hit is none
+    And trace.color = color_at(w, r)
+
 Scenario: There is no shadow when nothing is collinear with point and light
   Given w ← default_world()
     And p ← point(0, 10, 0)
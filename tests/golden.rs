@@ -0,0 +1,124 @@
+use cucumber::{given, then, when, World};
+use std::path::{Path, PathBuf};
+use sunhouse::canvas::Canvas;
+use sunhouse::output::OutputOptions;
+use sunhouse::scene::Scene;
+
+#[allow(dead_code)]
+#[path = "../examples/example5.rs"]
+mod example5;
+#[allow(dead_code)]
+#[path = "../examples/example6.rs"]
+mod example6;
+#[allow(dead_code)]
+#[path = "../examples/example8.rs"]
+mod example8;
+#[allow(dead_code)]
+#[path = "../examples/example9.rs"]
+mod example9;
+
+// `GoldenWorld` is your shared, likely mutable state.
+// Cucumber constructs it via `Default::default()` for each scenario.
+#[derive(Debug, Default, World)]
+pub struct GoldenWorld {
+  name: String,
+  image: Canvas,
+  reference: Canvas,
+}
+
+/// Where the reference image for an example lives.
+fn reference_path(name: &str) -> PathBuf {
+  PathBuf::from("tests/golden").join(format!("{}.png", name))
+}
+
+/// Write a picture of the differences next to the build output, for a human
+/// to look at when a comparison fails.
+fn save_diff(world: &GoldenWorld) -> PathBuf {
+  let path = PathBuf::from("target/golden").join(format!("{}-diff.png", world.name));
+  std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+  let diff = world.image.diff_visualization(&world.reference).unwrap();
+  diff.to_png(&path).unwrap();
+  path
+}
+
+fn size_mismatch(world: &GoldenWorld) -> ! {
+  panic!(
+    "image is {}x{} but reference is {}x{}",
+    world.image.width, world.image.height, world.reference.width, world.reference.height
+  );
+}
+
+#[given(regex = r"^image ← render\((example\d+)\)$")]
+fn render_example(world: &mut GoldenWorld, name: String) {
+  let image = match name.as_str() {
+    "example5" => example5::render(),
+    "example6" => example6::render(),
+    "example8" => example8::render(),
+    "example9" => example9::render(),
+    _ => panic!("Unknown example: {}", name),
+  };
+  // Compare what would actually be written, after quantizing.
+  let png = image.to_png_bytes(&OutputOptions::default()).unwrap();
+  world.image = Canvas::from_png(&png).unwrap();
+  world.name = name;
+}
+
+#[given(regex = r"^image ← render_scene\((\S+)\) as (example\d+)$")]
+fn render_scene_file(world: &mut GoldenWorld, path: String, name: String) {
+  let scene = Scene::load(Path::new(&path)).unwrap();
+  let image = scene.camera().render_parallel(&scene.world, 4);
+  let png = image.to_png_bytes(&OutputOptions::default()).unwrap();
+  world.image = Canvas::from_png(&png).unwrap();
+  world.name = format!("{}-scene", name);
+}
+
+/// Set `SUNHOUSE_BLESS=1` to replace the reference images with new renders.
+#[when(regex = r"^reference ← the reference image for (example\d+)$")]
+fn load_reference(world: &mut GoldenWorld, name: String) {
+  let path = reference_path(&name);
+  // Only an example's own render may replace its reference.
+  if std::env::var_os("SUNHOUSE_BLESS").is_some() && world.name == name {
+    world.image.to_png(&path).unwrap();
+  }
+  world.reference = Canvas::load_png(&path).unwrap();
+}
+
+#[then(regex = r"^image is the same size as reference$")]
+fn check_size(world: &mut GoldenWorld) {
+  assert_eq!(world.image.width, world.reference.width);
+  assert_eq!(world.image.height, world.reference.height);
+}
+
+#[then(regex = r"^the PSNR of image against reference is at least (\d+\.?\d*) dB$")]
+fn check_psnr(world: &mut GoldenWorld, minimum: f64) {
+  let psnr = world
+    .image
+    .psnr(&world.reference)
+    .unwrap_or_else(|| size_mismatch(world));
+  if psnr < minimum {
+    let path = save_diff(world);
+    panic!("PSNR {:.2} dB is below {} dB; see {}", psnr, minimum, path.display());
+  }
+}
+
+#[then(regex = r"^the max error of image against reference is at most (\d+\.?\d*)$")]
+fn check_max_error(world: &mut GoldenWorld, maximum: f64) {
+  let max_error = world
+    .image
+    .max_error(&world.reference)
+    .unwrap_or_else(|| size_mismatch(world));
+  if max_error > maximum {
+    let path = save_diff(world);
+    panic!(
+      "Max error {:.4} is above {}; see {}",
+      max_error,
+      maximum,
+      path.display()
+    );
+  }
+}
+
+// This runs before everything else, so you can setup things here.
+fn main() {
+  futures::executor::block_on(GoldenWorld::run("tests/features/golden.feature"));
+}
@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, when, World};
@@ -202,9 +202,10 @@ fn shape_is_with(world: &mut TestWorld, step: &Step) {
           } else {
             panic!("Unknown transform: {}", value);
           };
-          let mut sphere = Sphere::default();
-          sphere.transform = transform;
-          world.shape = Object::Sphere(sphere);
+          world.shape = Object::Sphere(Sphere {
+            transform,
+            ..Sphere::default()
+          });
         },
         _ => panic!("Unknown key: {}", key),
       }
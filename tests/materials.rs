@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::{given, then, when, World};
 use sunhouse::color::Color;
@@ -1,4 +1,5 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::explicit_counter_loop)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, World};
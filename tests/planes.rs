@@ -3,6 +3,7 @@ use cucumber::{given, then, when, World};
 
 use sunhouse::intersection::Intersection;
 
+use sunhouse::matrix::Matrix;
 use sunhouse::object::Object;
 use sunhouse::plane::Plane;
 use sunhouse::point::Point;
@@ -27,6 +28,16 @@ fn plane_is(world: &mut TestWorld) {
   world.p = Plane::default();
 }
 
+#[given(regex = r#"^p ← plane\(\) with transform translation\((.*), (.*), (.*)\)$"#)]
+fn plane_with_translation_is(world: &mut TestWorld, x: f64, y: f64, z: f64) {
+  world.p = Plane::default().with_transform(Matrix::translation(x, y, z));
+}
+
+#[given(regex = r#"^p ← plane\(\) with transform rotation_x\(π/2\)$"#)]
+fn plane_with_rotation_is(world: &mut TestWorld) {
+  world.p = Plane::default().with_transform(Matrix::rotation_x(std::f64::consts::FRAC_PI_2));
+}
+
 #[when(regex = r#"^n1 ← local_normal_at\(p, point\((.*), (.*), (.*)\)\)$"#)]
 fn n1_is_normal_at(world: &mut TestWorld, x: f64, y: f64, z: f64) {
   world.n1 = world.p.normal_at(Point(x, y, z));
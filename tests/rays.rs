@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use cucumber::{given, then, when, World};
 use sunhouse::matrix::Matrix;
 use sunhouse::point::Point;
@@ -76,6 +76,21 @@ fn m_is_scaling(world: &mut TestWorld, x: f64, y: f64, z: f64) {
   world.m = Matrix::scaling(x, y, z);
 }
 
+#[given(regex = r#"^r ← with_time\(r, (.*)\)$"#)]
+fn ray_with_time(world: &mut TestWorld, time: f64) {
+  world.ray = world.ray.with_time(time);
+}
+
+#[then(regex = r#"^r\.time = (.*)$"#)]
+fn ray_time_is(world: &mut TestWorld, time: f64) {
+  assert_eq!(world.ray.time, time);
+}
+
+#[then(regex = r#"^r2\.time = (.*)$"#)]
+fn r2_time_is(world: &mut TestWorld, time: f64) {
+  assert_eq!(world.r2.time, time);
+}
+
 // This runs before everything else, so you can setup things here.
 fn main() {
   futures::executor::block_on(TestWorld::run("tests/features/rays.feature"));
@@ -0,0 +1,446 @@
+#![allow(clippy::too_many_arguments)]
+use assert_approx_eq::assert_approx_eq;
+use cucumber::gherkin::Step;
+use cucumber::{given, then, when, World};
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use std::path::Path;
+use sunhouse::camera::Camera;
+use sunhouse::color::Color;
+use sunhouse::material::Material;
+use sunhouse::matrix::Matrix;
+use sunhouse::object::Object;
+use sunhouse::plane::Plane;
+use sunhouse::point::Point;
+use sunhouse::point_light::PointLight;
+use sunhouse::scene::Scene;
+use sunhouse::sphere::Sphere;
+use sunhouse::vector::Vector;
+use sunhouse::world::{Background, World as RenderWorld};
+use sunhouse::Error;
+
+// `SceneWorld` is your shared, likely mutable state.
+// Cucumber constructs it via `Default::default()` for each scenario.
+#[derive(Debug, Default, World)]
+pub struct SceneWorld {
+  scene_yaml: String,
+  scene: Option<sunhouse::Result<Scene>>,
+}
+
+impl SceneWorld {
+  fn scene(&self) -> &Scene {
+    match self.scene.as_ref().expect("No scene parsed") {
+      Ok(scene) => scene,
+      Err(error) => panic!("Parsing failed: {}", error),
+    }
+  }
+
+  fn camera(&self) -> Camera {
+    self.scene().camera()
+  }
+
+  fn object(&self, index: usize) -> &Object {
+    &self.scene().world.objects[index]
+  }
+
+  fn sphere(&self, index: usize) -> &Sphere {
+    match self.object(index) {
+      Object::Sphere(sphere) => sphere,
+      object => panic!("Expected a sphere, found {:?}", object),
+    }
+  }
+}
+
+/// A scene made of random parts, for checking that saving and loading lose
+/// nothing.
+#[derive(Clone, Debug)]
+struct RandomScene(Scene);
+
+fn number(g: &mut Gen) -> f64 {
+  // Any finite number, including the awkward ones quickcheck likes to pick.
+  let x = f64::arbitrary(g);
+  if x.is_finite() {
+    x
+  } else {
+    0.0
+  }
+}
+
+fn point(g: &mut Gen) -> Point {
+  Point(number(g), number(g), number(g))
+}
+
+fn color(g: &mut Gen) -> Color {
+  Color::new(number(g), number(g), number(g))
+}
+
+fn matrix(g: &mut Gen) -> Matrix {
+  let matrix = match u8::arbitrary(g) % 4 {
+    0 => Matrix::identity(),
+    1 => Matrix::translation(number(g), number(g), number(g)),
+    2 => Matrix::scaling(number(g), number(g), number(g)),
+    _ => Matrix::from_rows((0..4).map(|_| (0..4).map(|_| number(g)).collect()).collect()),
+  };
+  // Scene files only take transforms that can be undone.
+  if matrix.is_invertible() {
+    matrix
+  } else {
+    Matrix::identity()
+  }
+}
+
+fn material(g: &mut Gen) -> Material {
+  Material::new(color(g), number(g), number(g), number(g), number(g))
+}
+
+/// Make a random shape, with up to the given number of parents above it.
+fn shape(g: &mut Gen, parents: usize) -> Object {
+  let object = if bool::arbitrary(g) {
+    let mut sphere = Sphere::unit();
+    if bool::arbitrary(g) {
+      sphere.center = point(g);
+      sphere.radius = number(g);
+    }
+    Object::Sphere(sphere)
+  } else {
+    Object::Plane(Plane::default())
+  };
+  let object = object.with_transform(matrix(g)).with_material(material(g));
+  let object = if bool::arbitrary(g) {
+    let (start_time, end_time) = match (number(g), number(g)) {
+      (start, end) if start < end => (start, end),
+      _ => (0.0, 1.0),
+    };
+    object.with_motion_over(matrix(g), start_time, end_time)
+  } else {
+    object
+  };
+  if parents > 0 && bool::arbitrary(g) {
+    object.with_parent(shape(g, parents - 1))
+  } else {
+    object
+  }
+}
+
+fn camera(g: &mut Gen) -> Camera {
+  let (hsize, vsize) = (usize::arbitrary(g).max(1), usize::arbitrary(g).max(1));
+  let mut camera = match u8::arbitrary(g) % 4 {
+    0 => Camera::new(hsize, vsize, number(g)),
+    1 => Camera::orthographic(hsize, vsize, number(g)),
+    2 => Camera::equirectangular(hsize, vsize),
+    _ => Camera::fisheye(hsize, vsize, number(g)),
+  };
+  camera.transform = if bool::arbitrary(g) {
+    matrix(g)
+  } else {
+    // Looking at the eye itself, or along the up vector, has no transform.
+    let view = Matrix::view_transform(point(g), point(g), Vector(number(g), number(g), number(g)));
+    let finite = (0..4).all(|row| (0..4).all(|col| view.get_value(row, col).is_finite()));
+    if finite && view.is_invertible() {
+      view
+    } else {
+      Matrix::identity()
+    }
+  };
+  if bool::arbitrary(g) {
+    camera.aperture = number(g);
+    camera.focal_distance = number(g);
+    camera.aperture_blades = usize::arbitrary(g) % 8;
+  }
+  if bool::arbitrary(g) {
+    camera.samples = usize::arbitrary(g);
+    camera.shutter_open = number(g);
+    camera.shutter_close = number(g);
+  }
+  if camera.aperture > 0.0 {
+    camera.samples = camera.samples.max(2);
+  }
+  camera
+}
+
+impl Arbitrary for RandomScene {
+  fn arbitrary(g: &mut Gen) -> Self {
+    let camera = camera(g);
+    let lights = (0..1 + usize::arbitrary(g) % 3)
+      .map(|_| PointLight::new(point(g), color(g)))
+      .collect();
+    let objects = (0..usize::arbitrary(g) % 6).map(|_| shape(g, 2)).collect();
+    let background = match u8::arbitrary(g) % 3 {
+      0 => Background::default(),
+      1 => Background::Color(color(g)),
+      _ => Background::Transparent,
+    };
+    let world = RenderWorld::new(objects, lights).with_background(background);
+    RandomScene(Scene::new(camera, world))
+  }
+}
+
+fn survives_round_trip(scene: RandomScene) -> bool {
+  let yaml = scene.0.to_yaml().unwrap();
+  Scene::from_yaml(&yaml).is_ok_and(|loaded| loaded == scene.0)
+}
+
+/// Build a matrix from a product such as `scaling(1, 2, 3) * translation(4, 5, 6)`.
+fn parse_matrix(text: &str) -> Matrix {
+  text.split(" * ").fold(Matrix::identity(), |matrix, factor| {
+    let (name, args) = factor.trim_end_matches(')').split_once('(').unwrap();
+    let args: Vec<f64> = args.split(',').map(|a| a.trim().parse().unwrap()).collect();
+    let factor = match name {
+      "translation" => Matrix::translation(args[0], args[1], args[2]),
+      "scaling" => Matrix::scaling(args[0], args[1], args[2]),
+      "rotation_x" => Matrix::rotation_x(args[0]),
+      "rotation_y" => Matrix::rotation_y(args[0]),
+      "rotation_z" => Matrix::rotation_z(args[0]),
+      _ => panic!("Unknown transformation: {}", name),
+    };
+    matrix * factor
+  })
+}
+
+fn assert_matrix_eq(actual: Matrix, expected: Matrix) {
+  for row in 0..4 {
+    for col in 0..4 {
+      assert_approx_eq!(actual.get_value(row, col), expected.get_value(row, col), 1e-5);
+    }
+  }
+}
+
+#[given(regex = r"^scene_yaml ←$")]
+fn scene_yaml_is(world: &mut SceneWorld, step: &Step) {
+  let docstring = step.docstring.as_ref().expect("No docstring found");
+  // The docstring starts with the newline after its opening quotes, and table
+  // cells substituted into it may spell newlines as `\n`.
+  world.scene_yaml = docstring.strip_prefix('\n').unwrap_or(docstring).replace("\\n", "\n");
+}
+
+#[given(regex = r#"^scene_yaml ← "(.*)" repeated (\d+) times, then "(.*)"$"#)]
+fn scene_yaml_repeats(world: &mut SceneWorld, repeated: String, times: usize, end: String) {
+  world.scene_yaml = vec![repeated; times].join(" ") + " " + &end;
+}
+
+#[when(regex = r"^scene ← parse\(scene_yaml\)$")]
+fn parse_scene(world: &mut SceneWorld) {
+  world.scene = Some(Scene::from_yaml(&world.scene_yaml));
+}
+
+#[when(regex = r"^scene ← load\((\S+)\)$")]
+fn load_scene(world: &mut SceneWorld, path: String) {
+  world.scene = Some(Scene::load(Path::new(&path)));
+}
+
+#[then(regex = r"^c.(hsize|vsize|aperture_blades|samples) = (\d+)$")]
+fn camera_count_is(world: &mut SceneWorld, property: String, value: usize) {
+  let camera = world.camera();
+  let actual = match property.as_str() {
+    "hsize" => camera.hsize,
+    "vsize" => camera.vsize,
+    "aperture_blades" => camera.aperture_blades,
+    _ => camera.samples,
+  };
+  assert_eq!(actual, value);
+}
+
+#[then(regex = r"^c.projection = (perspective|orthographic|equirectangular|fisheye)$")]
+fn camera_projection_is(world: &mut SceneWorld, projection: String) {
+  assert_eq!(format!("{:?}", world.camera().projection).to_lowercase(), projection);
+}
+
+#[then(regex = r"^c.(half_view|aperture|focal_distance|shutter_open|shutter_close) = (-?[\d.]+)$")]
+fn camera_setting_is(world: &mut SceneWorld, property: String, value: f64) {
+  let camera = world.camera();
+  let actual = match property.as_str() {
+    "half_view" => camera.half_view,
+    "aperture" => camera.aperture,
+    "focal_distance" => camera.focal_distance,
+    "shutter_open" => camera.shutter_open,
+    _ => camera.shutter_close,
+  };
+  assert_approx_eq!(actual, value, 1e-5);
+}
+
+#[then(regex = r"^c.field_of_view = (-?\d+(?:\.\d+)?)$")]
+fn camera_field_of_view_is(world: &mut SceneWorld, value: f64) {
+  assert_approx_eq!(world.camera().field_of_view, value, 1e-5);
+}
+
+#[then(
+  regex = r"^c.transform = view_transform\(point\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\), point\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\), vector\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\)\)$"
+)]
+fn camera_transform_is(
+  world: &mut SceneWorld,
+  fx: f64,
+  fy: f64,
+  fz: f64,
+  tx: f64,
+  ty: f64,
+  tz: f64,
+  ux: f64,
+  uy: f64,
+  uz: f64,
+) {
+  let expected = Matrix::view_transform(Point(fx, fy, fz), Point(tx, ty, tz), Vector(ux, uy, uz));
+  assert_matrix_eq(world.camera().transform, expected);
+}
+
+#[then(regex = r"^c.transform = ((?:translation|scaling|rotation_[xyz])\(.+)$")]
+fn camera_transform_is_product(world: &mut SceneWorld, expected: String) {
+  assert_matrix_eq(world.camera().transform, parse_matrix(&expected));
+}
+
+#[then(regex = r"^w.(lights|objects).count = (\d+)$")]
+fn count_is(world: &mut SceneWorld, collection: String, count: usize) {
+  let world = &world.scene().world;
+  match collection.as_str() {
+    "lights" => assert_eq!(world.lights.len(), count),
+    _ => assert_eq!(world.objects.len(), count),
+  }
+}
+
+#[then(
+  regex = r"^w.lights\[(\d+)\] = point_light\(point\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\), color\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\)\)$"
+)]
+fn light_is(world: &mut SceneWorld, index: usize, x: f64, y: f64, z: f64, r: f64, g: f64, b: f64) {
+  let expected = PointLight::new(Point(x, y, z), Color::new(r, g, b));
+  assert_eq!(world.scene().world.lights[index], expected);
+}
+
+#[then(regex = r"^w.objects\[(\d+)\] is a (sphere|plane)$")]
+fn object_is_a(world: &mut SceneWorld, index: usize, kind: String) {
+  match (world.object(index), kind.as_str()) {
+    (Object::Sphere(_), "sphere") | (Object::Plane(_), "plane") => {},
+    (object, _) => panic!("Expected a {}, found {:?}", kind, object),
+  }
+}
+
+#[then(regex = r"^w.objects\[(\d+)\] has no parent$")]
+fn object_has_no_parent(world: &mut SceneWorld, index: usize) {
+  assert_eq!(world.object(index).parent(), None);
+}
+
+#[then(regex = r"^w.objects\[(\d+)\]((?:\.parent)+) is a (sphere|plane)$")]
+fn ancestor_is_a(world: &mut SceneWorld, index: usize, parents: String, kind: String) {
+  match (ancestor(world.object(index), &parents), kind.as_str()) {
+    (Object::Sphere(_), "sphere") | (Object::Plane(_), "plane") => {},
+    (object, _) => panic!("Expected a {}, found {:?}", kind, object),
+  }
+}
+
+#[then(regex = r"^w.objects\[(\d+)\]((?:\.parent)+).transform = (.+)$")]
+fn ancestor_transform_is(world: &mut SceneWorld, index: usize, parents: String, expected: String) {
+  assert_matrix_eq(
+    ancestor(world.object(index), &parents).transform(),
+    parse_matrix(&expected),
+  );
+}
+
+/// Follow one `.parent` up from the object for each in `parents`.
+fn ancestor(object: &Object, parents: &str) -> Object {
+  parents.matches(".parent").fold(object.clone(), |object, _| {
+    object
+      .parent()
+      .map(|parent| (*parent).clone())
+      .expect("The object has no parent")
+  })
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].transform = (.+)$")]
+fn object_transform_is(world: &mut SceneWorld, index: usize, expected: String) {
+  assert_matrix_eq(world.object(index).transform(), parse_matrix(&expected));
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].material = material\(\)$")]
+fn object_material_is_default(world: &mut SceneWorld, index: usize) {
+  assert_eq!(world.object(index).material(), Material::default());
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].material.color = color\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\)$")]
+fn object_color_is(world: &mut SceneWorld, index: usize, r: f64, g: f64, b: f64) {
+  assert_eq!(world.object(index).material().color, Color::new(r, g, b));
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].material.(ambient|diffuse|specular|shininess) = (-?[\d.]+)$")]
+fn object_material_property_is(world: &mut SceneWorld, index: usize, property: String, value: f64) {
+  let material = world.object(index).material();
+  let actual = match property.as_str() {
+    "ambient" => material.ambient,
+    "diffuse" => material.diffuse,
+    "specular" => material.specular,
+    _ => material.shininess,
+  };
+  assert_approx_eq!(actual, value, 1e-5);
+}
+
+#[when(regex = r"^a test shape is added to scene$")]
+fn add_test_shape(world: &mut SceneWorld) {
+  if let Some(Ok(scene)) = world.scene.as_mut() {
+    scene.world.objects.push(Object::test_shape());
+  }
+}
+
+#[then(regex = r"^w.background = transparent$")]
+fn background_is_transparent(world: &mut SceneWorld) {
+  assert_eq!(world.scene().world.background, Background::Transparent);
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].center = point\((-?[\d.]+), (-?[\d.]+), (-?[\d.]+)\)$")]
+fn sphere_center_is(world: &mut SceneWorld, index: usize, x: f64, y: f64, z: f64) {
+  assert_eq!(world.sphere(index).center, Point(x, y, z));
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].radius = (-?[\d.]+)$")]
+fn sphere_radius_is(world: &mut SceneWorld, index: usize, radius: f64) {
+  assert_eq!(world.sphere(index).radius, radius);
+}
+
+#[then(regex = r"^w.objects\[(\d+)\] moves from time (-?[\d.]+) to (-?[\d.]+)$")]
+fn sphere_motion_times_are(world: &mut SceneWorld, index: usize, start: f64, end: f64) {
+  let motion = world.sphere(index).motion.as_ref().expect("The sphere doesn't move");
+  assert_eq!((motion.start_time(), motion.end_time()), (start, end));
+}
+
+#[then(regex = r"^w.objects\[(\d+)\].end_transform = (.+)$")]
+fn sphere_end_transform_is(world: &mut SceneWorld, index: usize, expected: String) {
+  let motion = world.sphere(index).motion.as_ref().expect("The sphere doesn't move");
+  assert_matrix_eq(motion.end_transform(), parse_matrix(&expected));
+}
+
+#[then(regex = r"^parse\(to_yaml\(scene\)\) = scene$")]
+fn round_trip_is_equal(world: &mut SceneWorld) {
+  let scene = world.scene();
+  let yaml = scene.to_yaml().unwrap();
+  let loaded = Scene::from_yaml(&yaml).unwrap_or_else(|error| panic!("{}\n{}", error, yaml));
+  assert_eq!(&loaded, scene);
+}
+
+#[then(regex = r"^(\d+) random scenes survive to_yaml and from_yaml$")]
+fn random_scenes_round_trip(_world: &mut SceneWorld, count: u64) {
+  QuickCheck::new()
+    .tests(count)
+    .quickcheck(survives_round_trip as fn(RandomScene) -> bool);
+}
+
+#[then(regex = r"^to_yaml\(scene\) fails with a format error$")]
+fn to_yaml_fails(world: &mut SceneWorld) {
+  assert!(matches!(world.scene().to_yaml(), Err(Error::Format(_))));
+}
+
+#[then(regex = r#"^parsing fails on line (\d+) with "(.*)"$"#)]
+fn parsing_fails(world: &mut SceneWorld, expected_line: usize, expected_message: String) {
+  match world.scene.as_ref().expect("No scene parsed") {
+    Err(Error::Parse { line, message }) => {
+      assert_eq!(*line, expected_line, "{}", message);
+      assert!(
+        message.contains(&expected_message),
+        "{:?} not found in {:?}",
+        expected_message,
+        message
+      );
+    },
+    Err(error) => panic!("Expected a parse error, found {}", error),
+    Ok(_) => panic!("Expected parsing to fail"),
+  }
+}
+
+// This runs before everything else, so you can setup things here.
+fn main() {
+  futures::executor::block_on(SceneWorld::run("tests/features/scene.feature"));
+}
@@ -0,0 +1,168 @@
+#![allow(clippy::too_many_arguments)]
+use cucumber::{given, then, World};
+use std::path::Path;
+use std::sync::Arc;
+use sunhouse::camera::Camera;
+use sunhouse::color::Color;
+use sunhouse::material::Material;
+use sunhouse::matrix::Matrix;
+use sunhouse::object::Object;
+use sunhouse::point::Point;
+use sunhouse::point_light::PointLight;
+use sunhouse::scene::Scene;
+use sunhouse::sphere::Sphere;
+use sunhouse::vector::Vector;
+use sunhouse::world::{Background, World as RenderWorld};
+
+/// Anything the scenarios serialize.
+#[derive(Debug, Default, PartialEq)]
+enum Value {
+  #[default]
+  Nothing,
+  Point(Point),
+  Vector(Vector),
+  Color(Color),
+  Matrix(Matrix),
+  Material(Material),
+  Light(PointLight),
+  Camera(Camera),
+  Object(Object),
+  World(RenderWorld),
+}
+
+impl Value {
+  fn to_json(&self) -> String {
+    match self {
+      Value::Nothing => panic!("No value"),
+      Value::Point(point) => serde_json::to_string(point),
+      Value::Vector(vector) => serde_json::to_string(vector),
+      Value::Color(color) => serde_json::to_string(color),
+      Value::Matrix(matrix) => serde_json::to_string(matrix),
+      Value::Material(material) => serde_json::to_string(material),
+      Value::Light(light) => serde_json::to_string(light),
+      Value::Camera(camera) => serde_json::to_string(camera),
+      Value::Object(object) => serde_json::to_string(object),
+      Value::World(world) => serde_json::to_string(world),
+    }
+    .unwrap()
+  }
+
+  /// Read JSON back as the same kind of value as this one.
+  fn read_json(&self, json: &str) -> Value {
+    match self {
+      Value::Nothing => panic!("No value"),
+      Value::Point(_) => Value::Point(serde_json::from_str(json).unwrap()),
+      Value::Vector(_) => Value::Vector(serde_json::from_str(json).unwrap()),
+      Value::Color(_) => Value::Color(serde_json::from_str(json).unwrap()),
+      Value::Matrix(_) => Value::Matrix(serde_json::from_str(json).unwrap()),
+      Value::Material(_) => Value::Material(serde_json::from_str(json).unwrap()),
+      Value::Light(_) => Value::Light(serde_json::from_str(json).unwrap()),
+      Value::Camera(_) => Value::Camera(serde_json::from_str(json).unwrap()),
+      Value::Object(_) => Value::Object(serde_json::from_str(json).unwrap()),
+      Value::World(_) => Value::World(serde_json::from_str(json).unwrap()),
+    }
+  }
+}
+
+// `SerdeWorld` is your shared, likely mutable state.
+// Cucumber constructs it via `Default::default()` for each scenario.
+#[derive(Debug, Default, World)]
+pub struct SerdeWorld {
+  value: Value,
+}
+
+/// Read the numbers between the outermost parentheses of `name(a, b, ...)`.
+fn args(text: &str) -> Vec<f64> {
+  let inner = &text[text.find('(').unwrap() + 1..text.rfind(')').unwrap()];
+  inner.split(',').map(|a| a.trim().parse().unwrap()).collect()
+}
+
+/// Build a matrix from a product such as `scaling(1, 2, 3) * rotation_y(0.5)`.
+fn matrix(text: &str) -> Matrix {
+  text.split(" * ").fold(Matrix::identity(), |matrix, factor| {
+    let a = args(factor);
+    let factor = match &factor[..factor.find('(').unwrap()] {
+      "translation" => Matrix::translation(a[0], a[1], a[2]),
+      "scaling" => Matrix::scaling(a[0], a[1], a[2]),
+      "rotation_y" => Matrix::rotation_y(a[0]),
+      name => panic!("Unknown transformation: {}", name),
+    };
+    matrix * factor
+  })
+}
+
+fn scene(path: &str) -> Scene {
+  Scene::load(Path::new(path)).unwrap()
+}
+
+#[given(regex = r"^value ← (.+)$")]
+fn value_is(world: &mut SerdeWorld, text: String) {
+  let name = &text[..text.find('(').unwrap()];
+  let inner = &text[name.len() + 1..text.rfind(')').unwrap()];
+  world.value = match name {
+    "point" => {
+      let a = args(&text);
+      Value::Point(Point(a[0], a[1], a[2]))
+    },
+    "vector" => {
+      let a = args(&text);
+      Value::Vector(Vector(a[0], a[1], a[2]))
+    },
+    "color" => {
+      let a = args(&text);
+      Value::Color(Color::new(a[0], a[1], a[2]))
+    },
+    "translation" | "scaling" | "rotation_y" => Value::Matrix(matrix(&text)),
+    "material" => Value::Material(Material::default()),
+    "glass" => Value::Material(Material::glass()),
+    "point_light" => {
+      let (position, intensity) = inner.split_once("), ").unwrap();
+      let (p, c) = (args(&format!("{})", position)), args(intensity));
+      Value::Light(PointLight::new(Point(p[0], p[1], p[2]), Color::new(c[0], c[1], c[2])))
+    },
+    "camera" if inner.ends_with(".yml") => Value::Camera(scene(inner).camera()),
+    "camera" => {
+      let a = args(&text);
+      Value::Camera(Camera::new(a[0] as usize, a[1] as usize, a[2]))
+    },
+    "fisheye_camera" => {
+      let a = args(&text);
+      Value::Camera(Camera::fisheye(a[0] as usize, a[1] as usize, a[2]))
+    },
+    "sphere" if text.ends_with("with a parent") => Value::Object(Object::Sphere(Sphere {
+      parent: Some(Arc::new(Object::sphere())),
+      ..Sphere::unit()
+    })),
+    "sphere" => Value::Object(Object::sphere()),
+    "plane" => Value::Object(Object::plane()),
+    "test_shape" => Value::Object(Object::test_shape()),
+    "default_world" => Value::World(RenderWorld::default()),
+    "transparent_world" => Value::World(RenderWorld::default().with_background(Background::Transparent)),
+    "world" => Value::World(scene(inner).world),
+    _ => panic!("Unknown value: {}", text),
+  };
+}
+
+#[then(regex = r"^json\(value\) = (.+)$")]
+fn json_is(world: &mut SerdeWorld, expected: String) {
+  assert_eq!(world.value.to_json(), expected);
+}
+
+#[then(regex = r"^from_json\(json\(value\)\) = value$")]
+fn round_trips(world: &mut SerdeWorld) {
+  let json = world.value.to_json();
+  assert_eq!(world.value.read_json(&json), world.value, "{}", json);
+}
+
+#[then(regex = r"^the parent of from_json\(json\(value\)\) is sphere\(\)$")]
+fn parent_is_sphere(world: &mut SerdeWorld) {
+  match world.value.read_json(&world.value.to_json()) {
+    Value::Object(object) => assert_eq!(object.parent().as_deref(), Some(&Object::sphere())),
+    value => panic!("Expected an object, found {:?}", value),
+  }
+}
+
+// This runs before everything else, so you can setup things here.
+fn main() {
+  futures::executor::block_on(SerdeWorld::run("tests/features/serde.feature"));
+}
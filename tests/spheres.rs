@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::{given, then, when, World};
 use sunhouse::intersection::Intersection;
@@ -30,6 +30,26 @@ fn ray_is_point_vector(world: &mut TestWorld, x1: f64, y1: f64, z1: f64, x2: f64
   world.ray = Ray::new(Point(x1, y1, z1), Vector(x2, y2, z2));
 }
 
+#[given(regex = r#"^r ← ray\(point\((.*), (.*), (.*)\), vector\((.*), (.*), (.*)\)\) at time (.*)$"#)]
+fn ray_is_point_vector_time(world: &mut TestWorld, x1: f64, y1: f64, z1: f64, x2: f64, y2: f64, z2: f64, time: f64) {
+  world.ray = Ray::new(Point(x1, y1, z1), Vector(x2, y2, z2)).with_time(time);
+}
+
+#[given(regex = r#"^set_motion\(s, translation\(([^)]*), ([^)]*), ([^)]*)\)\)$"#)]
+fn set_motion_translation(world: &mut TestWorld, x: f64, y: f64, z: f64) {
+  world.sphere = world.sphere.with_motion(Matrix::translation(x, y, z));
+}
+
+#[given(regex = r#"^set_motion\(s, translation\((.*), (.*), (.*)\)\) from time (.*) to (.*)$"#)]
+fn set_motion_translation_over(world: &mut TestWorld, x: f64, y: f64, z: f64, start: f64, end: f64) {
+  world.sphere = world.sphere.with_motion_over(Matrix::translation(x, y, z), start, end);
+}
+
+#[when(regex = r#"^n ← normal_at\(xs1\[(\d+)\]\.object, point\((.*), (.*), (.*)\)\)$"#)]
+fn n_is_normal_at_hit_object(world: &mut TestWorld, idx: usize, x: f64, y: f64, z: f64) {
+  world.n = world.xs1[idx].object.normal_at(Point(x, y, z));
+}
+
 #[given(regex = r#"^s ← sphere\(\)$"#)]
 fn sphere_is(world: &mut TestWorld) {
   world.sphere = Sphere::default();
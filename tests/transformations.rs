@@ -1,8 +1,9 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, when, World};
 use sunhouse::matrix::Matrix;
+use sunhouse::motion::Motion;
 use sunhouse::point::Point;
 use sunhouse::vector::Vector;
 
@@ -26,6 +27,8 @@ pub struct MatrixTransWorld {
   up: Vector,
   half_quarter: Matrix,
   full_quarter: Matrix,
+  start: Matrix,
+  end: Matrix,
 }
 
 #[given(regex = r#"^transform ← translation\((?P<x>-?\d+), (?P<y>-?\d+), (?P<z>-?\d+)\)$"#)]
@@ -306,6 +309,54 @@ fn t_matrix(world: &mut MatrixTransWorld, step: &Step, rows: usize, cols: usize)
   }
 }
 
+fn parse_transform(expression: &str) -> Matrix {
+  expression
+    .split(" * ")
+    .map(|term| {
+      let (name, args) = term.trim_end_matches(')').split_once('(').unwrap();
+      let args: Vec<f64> = args
+        .split(", ")
+        .map(|arg| arg.trim_end_matches('°').parse::<f64>().unwrap())
+        .collect();
+      match name {
+        "translation" => Matrix::translation(args[0], args[1], args[2]),
+        "scaling" => Matrix::scaling(args[0], args[1], args[2]),
+        "rotation_y" => Matrix::rotation_y(args[0].to_radians()),
+        _ => panic!("Unknown transform: {}", name),
+      }
+    })
+    .fold(Matrix::identity(), |acc, m| acc * m)
+}
+
+#[given(regex = r#"^(start|end) ← (.*)$"#)]
+fn start_or_end_is(world: &mut MatrixTransWorld, which: String, expression: String) {
+  let transform = parse_transform(&expression);
+  match which.as_str() {
+    "start" => world.start = transform,
+    _ => world.end = transform,
+  }
+}
+
+#[when(regex = r#"^t ← interpolate\(start, end, (\d+(?:\.\d+)?)\)$"#)]
+fn t_interpolate(world: &mut MatrixTransWorld, time: f64) {
+  world.t = world.start.interpolate(&world.end, time);
+}
+
+#[when(regex = r#"^t ← transform_at\(motion\(start, end\), (\d+(?:\.\d+)?)\)$"#)]
+fn t_motion_transform_at(world: &mut MatrixTransWorld, time: f64) {
+  world.t = Motion::new(world.start, world.end).transform_at(time);
+}
+
+#[then(regex = r#"^t = (start|end)$"#)]
+fn t_is_start_or_end(world: &mut MatrixTransWorld, which: String) {
+  let expected = if which == "start" { world.start } else { world.end };
+  for row in 0..4 {
+    for col in 0..4 {
+      assert_approx_eq!(world.t.get_value(row, col), expected.get_value(row, col), 1e-9);
+    }
+  }
+}
+
 // This runs before everything else, so you can setup things here.
 fn main() {
   futures::executor::block_on(MatrixTransWorld::run("tests/features/transformations.feature"));
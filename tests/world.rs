@@ -1,4 +1,4 @@
-#[allow(clippy::too_many_arguments)]
+#![allow(clippy::too_many_arguments)]
 use assert_approx_eq::assert_approx_eq;
 use cucumber::gherkin::Step;
 use cucumber::{given, then, when, World};
@@ -12,7 +12,9 @@ use sunhouse::point::Point;
 use sunhouse::point_light::PointLight;
 use sunhouse::ray::Ray;
 use sunhouse::sphere::Sphere;
+use sunhouse::trace::RayTrace;
 use sunhouse::vector::Vector;
+use sunhouse::world::Background;
 use sunhouse::world::World as RenderWorld;
 
 // `TestWorld` is your shared, likely mutable state.
@@ -32,6 +34,8 @@ pub struct TestWorld {
   pub outer_index: usize,
   pub inner_index: usize,
   pub p: Point,
+  pub alpha: f64,
+  pub trace: RayTrace,
 }
 
 #[given(regex = r#"^w ← world\(\)$"#)]
@@ -280,6 +284,81 @@ fn i_is_intersection(world: &mut TestWorld, t: f64) {
   world.i = Intersection::new(t, Object::Sphere(world.s2.clone()));
 }
 
+#[given(regex = r"^w\.background ← color\((-?\d+\.?\d*), (-?\d+\.?\d*), (-?\d+\.?\d*)\)$")]
+fn set_background_color(world: &mut TestWorld, r: f64, g: f64, b: f64) {
+  world.w = world.w.with_background(Background::Color(Color::new(r, g, b)));
+}
+
+#[given(regex = r"^w\.background ← transparent$")]
+fn set_background_transparent(world: &mut TestWorld) {
+  world.w = world.w.with_background(Background::Transparent);
+}
+
+#[when(regex = r"^c, alpha ← color_and_alpha_at\(w, r\)$")]
+fn color_and_alpha_at(world: &mut TestWorld) {
+  (world.c, world.alpha) = world.w.color_and_alpha_at(world.r);
+}
+
+#[then(regex = r"^alpha = (\d+\.?\d*)$")]
+fn check_alpha(world: &mut TestWorld, alpha: f64) {
+  assert_approx_eq!(world.alpha, alpha);
+}
+
+#[when(regex = r"^trace ← trace_ray\(w, r\)$")]
+fn trace_ray(world: &mut TestWorld) {
+  world.trace = world.w.trace_ray(world.r);
+}
+
+#[then(regex = r"^trace\.intersections\.count = (\d+)$")]
+fn trace_intersection_count(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.trace.intersections.len(), count);
+}
+
+#[then(regex = r"^trace\.hit\.comps\.t = (\d+\.?\d*)$")]
+fn trace_hit_t(world: &mut TestWorld, t: f64) {
+  assert_approx_eq!(world.trace.hit.as_ref().unwrap().comps.t, t);
+}
+
+#[then(regex = r"^trace\.hit\.shadow\.in_shadow is (true|false)$")]
+fn trace_in_shadow(world: &mut TestWorld, in_shadow: bool) {
+  assert_eq!(world.trace.hit.as_ref().unwrap().shadow.in_shadow, in_shadow);
+}
+
+#[then(regex = r"^trace\.hit\.shadow\.intersections\.count = (\d+)$")]
+fn trace_shadow_intersection_count(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.trace.hit.as_ref().unwrap().shadow.intersections.len(), count);
+}
+
+#[then(regex = r"^trace\.hit\.lights\.count = (\d+)$")]
+fn trace_light_count(world: &mut TestWorld, count: usize) {
+  assert_eq!(world.trace.hit.as_ref().unwrap().lights.len(), count);
+}
+
+#[then(
+  regex = r"^trace\.hit\.lights\[(\d+)\]\.(diffuse|specular) = color\((-?\d+\.?\d*), (-?\d+\.?\d*), (-?\d+\.?\d*)\)$"
+)]
+fn trace_light_term(world: &mut TestWorld, index: usize, term: String, r: f64, g: f64, b: f64) {
+  let light = world.trace.hit.as_ref().unwrap().lights[index];
+  let color = if term == "diffuse" {
+    light.diffuse
+  } else {
+    light.specular
+  };
+  assert_eq!(color, Color::new(r, g, b));
+}
+
+#[then(regex = r"^trace\.hit is none$")]
+fn trace_hit_is_none(world: &mut TestWorld) {
+  assert!(world.trace.hit.is_none());
+}
+
+#[then(regex = r"^trace\.color = color_at\(w, r\)$")]
+fn trace_color_is_color_at(world: &mut TestWorld) {
+  let (color, alpha) = world.w.color_and_alpha_at(world.r);
+  assert_eq!(world.trace.color, color);
+  assert_eq!(world.trace.alpha, alpha);
+}
+
 // This runs before everything else, so you can setup things here.
 fn main() {
   futures::executor::block_on(TestWorld::run("tests/features/world.feature"));